@@ -0,0 +1,426 @@
+use crate::components::chat::CHAT_VO;
+use crate::components::event::{Message, parse};
+use crate::components::recent_chat::ChatVo;
+use crate::config::ServerProfile;
+use crate::e2e::{E2E, Keyring};
+use crate::proxy;
+use crate::proxy::HOST;
+use crate::proxy::block::BLOCKED;
+use crate::proxy::directory::{self, USER_DIRECTORY};
+use crate::proxy::friend::PENDING_FRIEND_REQS;
+use crate::proxy::presence::{PRESENCES, PresenceStatus};
+use crate::proxy::remark::{REMARKS, Remark};
+use crate::session;
+use crate::session::{SavedSession, SessionKey, Store};
+use crate::token::{CURRENT_USER, User};
+use futures::StreamExt;
+use reqwest::StatusCode;
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, LazyLock, Mutex};
+use std::time::Duration;
+use tokio::sync::oneshot;
+use tokio::time::{interval, sleep};
+use tracing::warn;
+
+// 已登录的账号，当前账号使用 CURRENT_USER 与 HOST，其余账号在后台监听新消息
+pub(crate) static ACCOUNTS: LazyLock<Accounts> = LazyLock::new(|| Accounts(Mutex::new(Vec::new())));
+
+/// 后台账号续期 token 的间隔
+const RENEW_INTERVAL: Duration = Duration::from_secs(60);
+/// 后台事件流断开后的重连间隔
+const RETRY_INTERVAL: Duration = Duration::from_secs(10);
+
+pub(crate) struct Accounts(Mutex<Vec<Account>>);
+
+struct Account {
+    profile: Option<ServerProfile>,
+    user: User,
+    /// 后台时由事件流任务续期
+    token: Arc<Mutex<String>>,
+    /// 转入后台后收到的消息数
+    unread: Arc<AtomicUsize>,
+    /// 后台事件流的停止信号，当前账号为 None
+    stop_tx: Option<oneshot::Sender<()>>,
+    /// 记住登录时会话的保存位置
+    store: Option<Store>,
+    /// 转入后台时保存的全局缓存
+    caches: Caches,
+}
+
+/// 每个账号各自的全局缓存，切换账号时换入换出，不需要重新获取
+#[derive(Default)]
+struct Caches {
+    directory: HashMap<i32, directory::Entry>,
+    presences: HashMap<i32, PresenceStatus>,
+    blocked: HashSet<i32>,
+    remarks: HashMap<i32, Remark>,
+    e2e: Keyring,
+    pending_friend_reqs: usize,
+    chat_vo: Option<ChatVo>,
+}
+
+impl Caches {
+    /// 取出当前账号的缓存，全局缓存变为空
+    fn take() -> Self {
+        Self {
+            directory: USER_DIRECTORY.take(),
+            presences: PRESENCES.take(),
+            blocked: BLOCKED.take(),
+            remarks: REMARKS.take(),
+            e2e: E2E.take(),
+            pending_friend_reqs: PENDING_FRIEND_REQS.swap(0, Ordering::SeqCst),
+            chat_vo: CHAT_VO.lock().unwrap().take(),
+        }
+    }
+
+    fn restore(self) {
+        USER_DIRECTORY.restore(self.directory);
+        PRESENCES.restore(self.presences);
+        BLOCKED.restore(self.blocked);
+        REMARKS.restore(self.remarks);
+        E2E.restore(self.e2e);
+        PENDING_FRIEND_REQS.store(self.pending_friend_reqs, Ordering::SeqCst);
+        CHAT_VO.lock().unwrap().restore(self.chat_vo);
+    }
+}
+
+/// 账号切换列表中的一项
+pub(crate) struct AccountSummary {
+    pub(crate) label: String,
+    pub(crate) unread: usize,
+    pub(crate) active: bool,
+}
+
+impl Account {
+    fn label(&self) -> String {
+        match &self.profile {
+            Some(profile) => format!("{}@{}", self.user.name, profile.name),
+            None => self.user.name.clone(),
+        }
+    }
+
+    fn saved_session(&self) -> SavedSession {
+        SavedSession {
+            user_name: self.user.name.clone(),
+            token: self.token.lock().unwrap().clone(),
+        }
+    }
+
+    fn is_active(&self) -> bool {
+        self.stop_tx.is_none()
+    }
+
+    fn is(&self, profile: Option<&ServerProfile>, uid: i32) -> bool {
+        self.profile.as_ref().map(|p| &p.name) == profile.map(|p| &p.name) && self.user.id == uid
+    }
+
+    fn key(&self) -> SessionKey {
+        SessionKey {
+            profile: self.profile.as_ref().map(|p| p.name.clone()),
+            uid: self.user.id,
+        }
+    }
+
+    /// 保存当前用户信息和缓存，并开始在后台监听新消息
+    fn suspend(&mut self) {
+        let current = CURRENT_USER.get_user();
+        if let (Some(user), Some(token)) = (current.user, current.token) {
+            self.user = user;
+            *self.token.lock().unwrap() = token;
+        }
+        self.caches = Caches::take();
+        self.stop_tx = Some(listen_in_background(
+            self.profile.clone(),
+            self.user.id,
+            self.token.clone(),
+            self.unread.clone(),
+            self.caches.blocked.clone(),
+        ));
+    }
+
+    fn resume(&mut self) {
+        if let Some(stop_tx) = self.stop_tx.take() {
+            let _ = stop_tx.send(());
+        }
+        self.unread.store(0, Ordering::SeqCst);
+        HOST.set_profile(self.profile.clone());
+        let token = self.token.lock().unwrap().clone();
+        CURRENT_USER.set_user(Some(self.user.clone()), Some(token));
+        std::mem::take(&mut self.caches).restore();
+    }
+}
+
+impl Accounts {
+    /// 登录新账号前调用，当前账号转入后台
+    pub(crate) fn suspend_active(&self) {
+        if let Some(account) = self.0.lock().unwrap().iter_mut().find(|a| a.is_active()) {
+            account.suspend();
+        }
+    }
+
+    /// 登录成功后记录当前账号，重复登录同一账号时替换原有记录
+    pub(crate) fn add_active(&self) {
+        let current = CURRENT_USER.get_user();
+        let (Some(user), Some(token)) = (current.user, current.token) else {
+            return;
+        };
+        let profile = HOST.profile();
+        let mut accounts = self.0.lock().unwrap();
+        let mut store = None;
+        if let Some(idx) = accounts
+            .iter()
+            .position(|a| a.is(profile.as_ref(), user.id))
+        {
+            let mut account = accounts.remove(idx);
+            if let Some(stop_tx) = account.stop_tx.take() {
+                let _ = stop_tx.send(());
+            }
+            store = account.store;
+        }
+        accounts.push(Account {
+            profile,
+            user,
+            token: Arc::new(Mutex::new(token)),
+            unread: Arc::new(AtomicUsize::new(0)),
+            stop_tx: None,
+            store,
+            caches: Caches::default(),
+        });
+    }
+
+    /// 设置当前账号是否记住登录，不记住时只删除该账号保存的会话
+    pub(crate) fn remember(&self, store: Option<Store>) -> color_eyre::Result<()> {
+        let mut accounts = self.0.lock().unwrap();
+        let Some(account) = accounts.iter_mut().find(|a| a.is_active()) else {
+            return Ok(());
+        };
+        account.store = store;
+        match &account.store {
+            Some(store) => session::remember(&account.key(), store, &account.saved_session()),
+            None => {
+                session::forget(&account.key());
+                Ok(())
+            }
+        }
+    }
+
+    /// 当前账号的会话标识和 token，用于续期
+    pub(crate) fn active_session(&self) -> Option<(SessionKey, String)> {
+        let accounts = self.0.lock().unwrap();
+        let account = accounts.iter().find(|a| a.is_active())?;
+        Some((account.key(), CURRENT_USER.get_user().token?))
+    }
+
+    /// token 续期后更新当前账号，续期期间已切换账号时忽略
+    pub(crate) fn renewed(&self, key: &SessionKey, user: User, token: String) {
+        let mut accounts = self.0.lock().unwrap();
+        let Some(account) = accounts
+            .iter_mut()
+            .find(|a| a.is_active() && &a.key() == key)
+        else {
+            return;
+        };
+        account.user = user.clone();
+        *account.token.lock().unwrap() = token.clone();
+        CURRENT_USER.set_user(Some(user), Some(token));
+        if let Some(store) = &account.store
+            && let Err(err) = session::save(key, store, &account.saved_session())
+        {
+            warn!("fail to save session: {err}");
+        }
+    }
+
+    /// 退出当前账号，返回是否还有其他已登录的账号
+    pub(crate) fn remove_active(&self) -> bool {
+        let mut accounts = self.0.lock().unwrap();
+        accounts.retain(|a| !a.is_active());
+        !accounts.is_empty()
+    }
+
+    /// 切换到指定账号，返回是否发生了切换
+    pub(crate) fn switch(&self, idx: usize) -> bool {
+        let mut accounts = self.0.lock().unwrap();
+        if accounts.get(idx).is_none_or(|a| a.is_active()) {
+            return false;
+        }
+        if let Some(active) = accounts.iter_mut().find(|a| a.is_active()) {
+            active.suspend();
+        }
+        accounts[idx].resume();
+        true
+    }
+
+    /// 后台账号的未读消息总数
+    pub(crate) fn background_unread(&self) -> usize {
+        self.0
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|a| !a.is_active())
+            .map(|a| a.unread.load(Ordering::SeqCst))
+            .sum()
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.0.lock().unwrap().len()
+    }
+
+    pub(crate) fn summaries(&self) -> Vec<AccountSummary> {
+        self.0
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|a| AccountSummary {
+                label: a.label(),
+                unread: a.unread.load(Ordering::SeqCst),
+                active: a.is_active(),
+            })
+            .collect()
+    }
+
+    /// 当前账号的名称，只有一个账号时不显示
+    pub(crate) fn active_label(&self) -> Option<String> {
+        let accounts = self.0.lock().unwrap();
+        if accounts.len() < 2 {
+            return None;
+        }
+        accounts.iter().find(|a| a.is_active()).map(Account::label)
+    }
+}
+
+/// 退出账号后清空全局缓存
+pub(crate) fn reset_caches() {
+    USER_DIRECTORY.clear();
+    PRESENCES.clear();
+    BLOCKED.clear();
+    REMARKS.load_local();
+    E2E.init();
+    PENDING_FRIEND_REQS.store(0, Ordering::SeqCst);
+    CHAT_VO.lock().unwrap().clear();
+}
+
+fn listen_in_background(
+    profile: Option<ServerProfile>,
+    uid: i32,
+    token: Arc<Mutex<String>>,
+    unread: Arc<AtomicUsize>,
+    blocked: HashSet<i32>,
+) -> oneshot::Sender<()> {
+    let (stop_tx, stop_rx) = oneshot::channel();
+    tokio::spawn(async move {
+        tokio::select! {
+            _ = stop_rx => {}
+            _ = listen(profile, uid, token, unread, blocked) => {}
+        }
+    });
+    stop_tx
+}
+
+/// 监听后台账号的事件流，只统计新消息数（不含该账号屏蔽的用户），并定时续期 token
+async fn listen(
+    profile: Option<ServerProfile>,
+    uid: i32,
+    token: Arc<Mutex<String>>,
+    unread: Arc<AtomicUsize>,
+    blocked: HashSet<i32>,
+) {
+    let url = proxy::url_of(profile.as_ref());
+    let client = proxy::async_client_for(profile.as_ref());
+    let mut renew = interval(RENEW_INTERVAL);
+    loop {
+        let bearer = format!("Bearer {}", token.lock().unwrap());
+        let res = client
+            .get(format!("{url}/event/stream"))
+            .header("Authorization", bearer)
+            .header("User-Agent", "Chat-Tui")
+            .header("Accept", "application/event-stream")
+            .send()
+            .await;
+        match res {
+            Ok(res) if res.status() == StatusCode::OK => {
+                let mut stream = res.bytes_stream();
+                loop {
+                    tokio::select! {
+                        item = stream.next() => match item {
+                            Some(Ok(bytes)) => {
+                                let sse = String::from_utf8_lossy(&bytes).to_string();
+                                if let Some(Message::ChatMessage(msg)) = parse(sse)
+                                    && msg.payload.from_uid != uid
+                                    && !blocked.contains(&msg.payload.from_uid)
+                                {
+                                    unread.fetch_add(1, Ordering::SeqCst);
+                                }
+                            }
+                            _ => break,
+                        },
+                        _ = renew.tick() => renew_token(&client, &url, &token).await,
+                    }
+                }
+            }
+            Ok(res) => warn!("fail to fetch background event stream: {}", res.status()),
+            Err(err) => warn!("fail to get background event stream: {err}"),
+        }
+        sleep(RETRY_INTERVAL).await;
+    }
+}
+
+async fn renew_token(client: &reqwest::Client, url: &str, token: &Arc<Mutex<String>>) {
+    let bearer = format!("Bearer {}", token.lock().unwrap());
+    let res = client
+        .patch(format!("{url}/token/renew"))
+        .header("Authorization", bearer)
+        .send()
+        .await;
+    match res {
+        Ok(res) if res.status().is_success() => match res.text().await {
+            Ok(new_token) => *token.lock().unwrap() = new_token,
+            Err(err) => warn!("fail to parse renewed token: {err}"),
+        },
+        Ok(res) => warn!("fail to renew background token: {}", res.status()),
+        Err(err) => warn!("fail to renew background token: {err}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn account(id: i32, name: &str, active: bool) -> Account {
+        let mut user = User::default();
+        user.id = id;
+        user.name = name.to_string();
+        Account {
+            profile: None,
+            user,
+            token: Arc::new(Mutex::new(format!("token-{id}"))),
+            unread: Arc::new(AtomicUsize::new(3)),
+            stop_tx: (!active).then(|| oneshot::channel().0),
+            store: None,
+            caches: Caches::default(),
+        }
+    }
+
+    #[test]
+    fn test_logout_switches_to_remaining_account() {
+        let accounts = Accounts(Mutex::new(vec![
+            account(1, "alice", true),
+            account(2, "bob", false),
+        ]));
+        assert!(accounts.remove_active());
+        assert_eq!(accounts.len(), 1);
+        assert!(accounts.switch(0));
+
+        let summaries = accounts.summaries();
+        assert_eq!(summaries.len(), 1);
+        assert_eq!(summaries[0].label, "bob");
+        assert!(summaries[0].active);
+        assert_eq!(summaries[0].unread, 0);
+        let current = CURRENT_USER.get_user();
+        assert_eq!(current.user.map(|user| user.id), Some(2));
+        assert_eq!(current.token.as_deref(), Some("token-2"));
+        // 已是当前账号时不再切换
+        assert!(!accounts.switch(0));
+        assert!(!accounts.remove_active());
+    }
+}
@@ -1,4 +1,5 @@
 use crate::components::contact::ToChat;
+use crate::components::event::{FriendRequestMessage, ReactionMessage, TypingMessage};
 use crate::components::group_manager::ManageAction;
 use serde::{Deserialize, Serialize};
 
@@ -21,12 +22,48 @@ pub enum Action {
     Register,
     Group(i32),
     ToChat(ToChat),
+    Typing(TypingMessage),
+    /// 消息的表情回应发生变化
+    Reaction(ReactionMessage),
+    RefreshRecentChat,
+    AnnouncementChanged(i32),
+    FriendRequest(FriendRequestMessage),
+    /// 屏蔽列表发生变化
+    BlockListChanged,
+    /// 打开账号切换列表
+    SwitchAccount,
+    /// 登录新账号
+    AddAccount,
+    /// 退出当前账号
+    Logout,
 }
 
 #[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
 pub enum ConfirmEvent {
-    InviteFriend,
-    GroupManage(Option<ManageAction>),
-    AddFriend(i32),
+    InviteMembers(Vec<i32>),
+    /// 可执行的操作，以及选中的操作
+    GroupManage(Vec<ManageAction>, Option<ManageAction>),
+    /// 好友id，验证消息
+    AddFriend(i32, Option<String>),
+    /// 好友请求id，是否通过
+    ReviewFriendReqs(Vec<i32>, bool),
+    RemoveFriend(i32),
+    BlockUser(i32),
+    UnblockUser(i32),
+    /// 修改密码，密码不放入事件中
+    ChangePassword,
+    Logout,
+    /// 注销其他设备上的会话
+    RevokeSession(String),
     ConfirmFriendReq(Option<bool>),
+    LeaveGroup(i32),
+    DissolveGroup(i32),
+    RenameGroup(i32, String),
+    TransferOwner(i32),
+    RevokeManager(i32),
+    EvictMembers(Vec<i32>),
+    /// 群id，是否开启全员禁言
+    ForbidAll(i32, bool),
+    /// 信任好友变更后的公钥
+    TrustPeerKey(i32),
 }
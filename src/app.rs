@@ -6,7 +6,9 @@ use std::sync::{Arc, Mutex};
 use tokio::sync::{broadcast, mpsc};
 use tracing::debug;
 
+use crate::components::account_switcher::AccountSwitcher;
 use crate::components::alert::Alert;
+use crate::components::auto_away::AutoAway;
 use crate::components::chat::Chat;
 use crate::components::contact::Contact;
 use crate::components::group_manager::GroupManager;
@@ -18,6 +20,7 @@ use crate::{
     action::Action,
     components::Component,
     config::Config,
+    graphics::GRAPHICS,
     tui::{Event, Tui},
 };
 
@@ -44,6 +47,7 @@ pub enum Mode {
     Setting,
     GroupManager,
     Alert,
+    AccountSwitcher,
 }
 
 #[derive(Default)]
@@ -86,6 +90,8 @@ impl App {
         let contact = Contact::new(mode_holder.clone());
         let group_manager = GroupManager::new(mode_holder.clone());
         let setting = Setting::new(mode_holder.clone());
+        let auto_away = AutoAway::new();
+        let account_switcher = AccountSwitcher::new(mode_holder.clone());
         Ok(Self {
             tick_rate,
             frame_rate,
@@ -99,6 +105,8 @@ impl App {
                 Box::new(group_manager),
                 Box::new(alert),
                 Box::new(setting),
+                Box::new(auto_away),
+                Box::new(account_switcher),
             ],
             should_suspend: false,
             should_quit: false,
@@ -206,7 +214,10 @@ impl App {
                 }
                 Action::Suspend => self.should_suspend = true,
                 Action::Resume => self.should_suspend = false,
-                Action::ClearScreen => tui.terminal.clear()?,
+                Action::ClearScreen => {
+                    tui.terminal.clear()?;
+                    GRAPHICS.invalidate();
+                }
                 Action::Resize(w, h) => self.handle_resize(tui, w, h)?,
                 Action::Render => self.render(tui)?,
                 _ => {}
@@ -235,7 +246,10 @@ impl App {
                         .send(Action::Error(format!("Failed to draw: {:?}", err)));
                 }
             }
+            GRAPHICS.retain_visible(frame.buffer_mut());
         })?;
+        // 图片在 ratatui 输出缓冲区之后绘制
+        GRAPHICS.flush(tui.terminal.backend_mut())?;
         Ok(())
     }
 }
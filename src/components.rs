@@ -8,15 +8,22 @@ use tokio::sync::mpsc::UnboundedSender;
 
 use crate::{action::Action, config::Config, tui::Event};
 
+pub mod account_switcher;
 pub mod alert;
 mod area_util;
+pub mod auto_away;
 pub mod chat;
 pub mod contact;
+pub mod emoji_picker;
 pub mod event;
+pub mod file_picker;
+pub mod forward_picker;
 pub mod group_manager;
+pub mod image_preview;
 pub mod login;
 pub mod navigation;
 pub mod recent_chat;
+pub mod register;
 pub mod setting;
 pub mod user_input;
 
@@ -0,0 +1,136 @@
+use crate::account::ACCOUNTS;
+use crate::action::Action;
+use crate::app::{Mode, ModeHolderLock};
+use crate::components::recent_chat::SELECTED_STYLE;
+use crate::components::{Component, area_util};
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::layout::{Alignment, Constraint, Layout, Margin, Rect};
+use ratatui::style::{Color, Style, Stylize};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Clear, HighlightSpacing, List, ListItem, ListState};
+use ratatui::{Frame, symbols};
+
+/// 账号切换列表，最后一行为添加账号
+pub(crate) struct AccountSwitcher {
+    mode_holder: ModeHolderLock,
+    /// 打开列表前的页面
+    last_mode: Option<Mode>,
+    list_state: ListState,
+}
+
+impl AccountSwitcher {
+    pub(crate) fn new(mode_holder: ModeHolderLock) -> Self {
+        Self {
+            mode_holder,
+            last_mode: None,
+            list_state: ListState::default(),
+        }
+    }
+
+    fn open(&mut self) {
+        self.last_mode = Some(self.mode_holder.get_mode());
+        let active = ACCOUNTS.summaries().iter().position(|a| a.active);
+        self.list_state.select(active.or(Some(0)));
+        self.mode_holder.set_mode(Mode::AccountSwitcher);
+    }
+
+    fn close(&mut self) {
+        if let Some(mode) = self.last_mode.take() {
+            self.mode_holder.set_mode(mode);
+        }
+    }
+
+    fn submit(&mut self) -> Option<Action> {
+        let idx = self.list_state.selected()?;
+        if idx == ACCOUNTS.len() {
+            self.last_mode = None;
+            return Some(Action::AddAccount);
+        }
+        if ACCOUNTS.switch(idx) {
+            // 切换后回到最近聊天，各组件收到 LoginSuccess 后重新加载
+            self.last_mode = None;
+            self.mode_holder.set_mode(Mode::RecentChat);
+            Some(Action::LoginSuccess)
+        } else {
+            self.close();
+            None
+        }
+    }
+}
+
+impl Component for AccountSwitcher {
+    fn handle_key_event(&mut self, key: KeyEvent) -> color_eyre::Result<Option<Action>> {
+        if self.mode_holder.get_mode() != Mode::AccountSwitcher {
+            return Ok(None);
+        }
+        match key.code {
+            KeyCode::Up => self.list_state.select_previous(),
+            KeyCode::Down => self.list_state.select_next(),
+            KeyCode::Enter => return Ok(self.submit()),
+            KeyCode::Esc => self.close(),
+            _ => {}
+        }
+        Ok(None)
+    }
+
+    fn update(&mut self, action: Action) -> color_eyre::Result<Option<Action>> {
+        if action == Action::SwitchAccount && self.mode_holder.get_mode() != Mode::AccountSwitcher {
+            self.open();
+        }
+        Ok(None)
+    }
+
+    fn draw(&mut self, frame: &mut Frame, area: Rect) -> color_eyre::Result<()> {
+        if self.mode_holder.get_mode() != Mode::AccountSwitcher {
+            return Ok(());
+        }
+        let mut items: Vec<ListItem> = ACCOUNTS
+            .summaries()
+            .into_iter()
+            .map(|account| {
+                let mut spans = vec![Span::raw(account.label)];
+                if account.active {
+                    spans.push(" (当前)".fg(Color::Green));
+                }
+                if account.unread > 0 {
+                    spans.push(format!(" [{}]", account.unread).fg(Color::Red));
+                }
+                ListItem::new(Line::from(spans))
+            })
+            .collect();
+        items.push(ListItem::new("+ 添加账号".fg(Color::Yellow)));
+        // 选中项超出范围时选中最后一项
+        if self.list_state.selected().is_some_and(|i| i >= items.len()) {
+            self.list_state.select(Some(items.len() - 1));
+        }
+
+        let area = area_util::alert_area(area);
+        let [_, popup_area, _] = Layout::vertical([
+            Constraint::Fill(1),
+            Constraint::Length(items.len() as u16 + 2),
+            Constraint::Fill(1),
+        ])
+        .areas(area);
+        frame.render_widget(Clear, popup_area);
+        let block = Block::new()
+            .title("切换账号")
+            .title_bottom(Line::from("↑↓ To Select, Enter To Switch, Esc To Quit.").centered())
+            .title_alignment(Alignment::Center)
+            .borders(Borders::ALL)
+            .border_set(symbols::border::ROUNDED)
+            .style(Style::default());
+        frame.render_widget(block, popup_area);
+        let list = List::new(items)
+            .highlight_style(SELECTED_STYLE)
+            .highlight_spacing(HighlightSpacing::Always);
+        frame.render_stateful_widget(
+            list,
+            popup_area.inner(Margin {
+                horizontal: 1,
+                vertical: 1,
+            }),
+            &mut self.list_state,
+        );
+        Ok(())
+    }
+}
@@ -3,6 +3,7 @@ use crate::app::{Mode, ModeHolderLock};
 use crate::components::group_manager::ManageAction;
 use crate::components::recent_chat::SELECTED_STYLE;
 use crate::components::{Component, area_util};
+use crate::token::CURRENT_USER;
 use crossterm::event::{KeyCode, KeyEvent};
 use ratatui::layout::{Alignment, Constraint, Layout, Margin, Rect};
 use ratatui::prelude::Text;
@@ -12,7 +13,6 @@ use ratatui::widgets::{Block, Clear, Wrap};
 use ratatui::widgets::{Borders, HighlightSpacing, List, ListItem};
 use ratatui::widgets::{ListState, Paragraph};
 use ratatui::{Frame, symbols};
-use strum::IntoEnumIterator;
 
 pub struct Alert {
     /// alert message
@@ -31,30 +31,17 @@ impl Component for Alert {
     fn handle_key_event(&mut self, key: KeyEvent) -> color_eyre::Result<Option<Action>> {
         match self.mode_holder.get_mode() {
             Mode::Alert => match self.confirm_event {
-                Some(ConfirmEvent::InviteFriend) => match key.code {
+                Some(ConfirmEvent::GroupManage(ref actions, None)) => match key.code {
                     KeyCode::Enter => {
-                        let action = Action::Confirm(self.confirm_event.clone().unwrap());
+                        let action = self
+                            .list_state
+                            .selected()
+                            .and_then(|idx| actions.get(idx).copied())
+                            .map(|action| {
+                                Action::Confirm(ConfirmEvent::GroupManage(vec![], Some(action)))
+                            });
                         self.close();
-                        Ok(Some(action))
-                    }
-                    KeyCode::Esc => {
-                        self.close();
-                        Ok(None)
-                    }
-                    _ => Ok(None),
-                },
-                Some(ConfirmEvent::GroupManage(None)) => match key.code {
-                    KeyCode::Enter => {
-                        if let Some(idx) = self.list_state.selected() {
-                            let action = Action::Confirm(ConfirmEvent::GroupManage(Some(
-                                ManageAction::from_repr(idx as u8).unwrap(),
-                            )));
-                            self.close();
-                            Ok(Some(action))
-                        } else {
-                            self.close();
-                            Ok(None)
-                        }
+                        Ok(action)
                     }
                     KeyCode::Up => {
                         self.list_state.select_previous();
@@ -70,10 +57,29 @@ impl Component for Alert {
                     }
                     _ => Ok(None),
                 },
-                Some(ConfirmEvent::AddFriend(friend_uid)) => match key.code {
+                Some(
+                    ConfirmEvent::InviteMembers(_)
+                    | ConfirmEvent::LeaveGroup(_)
+                    | ConfirmEvent::DissolveGroup(_)
+                    | ConfirmEvent::RenameGroup(..)
+                    | ConfirmEvent::TransferOwner(_)
+                    | ConfirmEvent::RevokeManager(_)
+                    | ConfirmEvent::EvictMembers(_)
+                    | ConfirmEvent::ForbidAll(..)
+                    | ConfirmEvent::AddFriend(..)
+                    | ConfirmEvent::ReviewFriendReqs(..)
+                    | ConfirmEvent::RemoveFriend(_)
+                    | ConfirmEvent::BlockUser(_)
+                    | ConfirmEvent::UnblockUser(_)
+                    | ConfirmEvent::ChangePassword
+                    | ConfirmEvent::Logout
+                    | ConfirmEvent::RevokeSession(_)
+                    | ConfirmEvent::TrustPeerKey(_),
+                ) => match key.code {
                     KeyCode::Enter => {
+                        let action = self.confirm_event.take().map(Action::Confirm);
                         self.close();
-                        Ok(Some(Action::Confirm(ConfirmEvent::AddFriend(friend_uid))))
+                        Ok(action)
                     }
                     KeyCode::Esc => {
                         self.close();
@@ -126,8 +132,8 @@ impl Component for Alert {
 
     fn draw(&mut self, frame: &mut Frame, area: Rect) -> color_eyre::Result<()> {
         if self.mode_holder.get_mode() == Mode::Alert {
-            if let Some(ConfirmEvent::GroupManage(None)) = self.confirm_event {
-                self.draw_manage_action(frame, area);
+            if let Some(ConfirmEvent::GroupManage(actions, None)) = self.confirm_event.clone() {
+                self.draw_manage_action(frame, area, actions);
             } else {
                 self.draw_common(frame, area);
             }
@@ -152,7 +158,15 @@ impl Alert {
         self.msg = DEFAULT_ALERT_MSG.to_string();
         self.confirm_event.take();
         self.list_state.select(None);
-        self.mode_holder.set_mode(self.last_mode.unwrap());
+        // 关闭后清空上一个状态，避免后续的alert返回到错误的页面
+        let mode = self.last_mode.take().unwrap_or_else(|| {
+            if CURRENT_USER.get_user().user.is_some() {
+                Mode::RecentChat
+            } else {
+                Mode::Login
+            }
+        });
+        self.mode_holder.set_mode(mode);
     }
 
     fn draw_common(&mut self, frame: &mut Frame, area: Rect) {
@@ -181,9 +195,9 @@ impl Alert {
         frame.render_widget(msg, centered_area);
     }
 
-    fn draw_manage_action(&mut self, frame: &mut Frame, area: Rect) {
+    fn draw_manage_action(&mut self, frame: &mut Frame, area: Rect, actions: Vec<ManageAction>) {
         let area = area_util::alert_area(area);
-        let count = ManageAction::iter().count();
+        let count = actions.len();
         let [_, alert_area, _] = Layout::vertical([
             Constraint::Fill(1),
             Constraint::Length((count + 3) as u16),
@@ -218,7 +232,8 @@ impl Alert {
                 vertical: 0,
             }),
         );
-        let items: Vec<ListItem> = ManageAction::iter()
+        let items: Vec<ListItem> = actions
+            .into_iter()
             .map(|action| ListItem::new(Text::from(action)))
             .collect();
         let list = List::new(items)
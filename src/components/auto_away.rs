@@ -0,0 +1,81 @@
+use crate::action::Action;
+use crate::components::Component;
+use crate::proxy::presence;
+use crate::proxy::presence::{PRESENCES, Presence, PresenceStatus};
+use crate::token::CURRENT_USER;
+use crossterm::event::KeyEvent;
+use ratatui::Frame;
+use ratatui::layout::Rect;
+use std::time::{Duration, Instant};
+use tracing::error;
+
+/// 超过该时间没有键盘操作，自动切换为离开
+const AUTO_AWAY_AFTER: Duration = Duration::from_secs(5 * 60);
+
+/// 根据键盘活动自动切换在线/离开状态
+pub(crate) struct AutoAway {
+    last_active: Instant,
+    /// 当前的离开状态是否由自动切换产生
+    auto_away: bool,
+}
+
+impl AutoAway {
+    pub(crate) fn new() -> Self {
+        Self {
+            last_active: Instant::now(),
+            auto_away: false,
+        }
+    }
+
+    fn set_presence(&self, presence: Presence) {
+        let uid = CURRENT_USER.get_user().user.unwrap().id;
+        let status = PresenceStatus {
+            presence,
+            status_text: PRESENCES.get(uid).status_text,
+        };
+        if let Err(err) = presence::set(status) {
+            error!("fail to set presence: {err}");
+        }
+    }
+}
+
+impl Component for AutoAway {
+    fn handle_key_event(&mut self, _key: KeyEvent) -> color_eyre::Result<Option<Action>> {
+        self.last_active = Instant::now();
+        if self.auto_away {
+            self.auto_away = false;
+            self.set_presence(Presence::Online);
+        }
+        Ok(None)
+    }
+
+    fn update(&mut self, action: Action) -> color_eyre::Result<Option<Action>> {
+        if action == Action::Logout {
+            self.auto_away = false;
+        }
+        let Some(user) = CURRENT_USER.get_user().user else {
+            return Ok(None);
+        };
+        match action {
+            Action::LoginSuccess => {
+                self.last_active = Instant::now();
+                self.set_presence(Presence::Online);
+            }
+            // 仅在线状态会自动切换，忙碌、离线等手动设置的状态保持不变
+            Action::Tick
+                if !self.auto_away
+                    && self.last_active.elapsed() >= AUTO_AWAY_AFTER
+                    && PRESENCES.get(user.id).presence == Presence::Online =>
+            {
+                self.auto_away = true;
+                self.set_presence(Presence::Away);
+            }
+            _ => {}
+        }
+        Ok(None)
+    }
+
+    fn draw(&mut self, _frame: &mut Frame, _area: Rect) -> color_eyre::Result<()> {
+        Ok(())
+    }
+}
@@ -1,28 +1,55 @@
 use crate::action::Action;
 use crate::app::{Mode, ModeHolderLock};
-use crate::components::event::{ChatMessage, MessageTarget};
-use crate::components::recent_chat::ChatVo;
+use crate::components::emoji_picker::{EmojiPicked, EmojiPicker, RECENT_EMOJI};
+use crate::components::event::{
+    ChatMessage, MessageTarget, MessageTargetGroup, MessageTargetUser, ReactionMessage,
+    TypingMessage,
+};
+use crate::components::file_picker::{FilePicker, Picked};
+use crate::components::forward_picker::{ForwardPicked, ForwardPicker};
+use crate::components::image_preview::ImagePreviews;
+use crate::components::recent_chat::{ChatVo, SELECTED_STYLE};
 use crate::components::user_input::{InputData, UserInput};
 use crate::components::{area_util, Component};
+use crate::config::{Config, get_download_dir};
 use crate::datetime::datetime_format;
+use crate::e2e::E2E;
+use crate::graphics::GRAPHICS;
 use crate::proxy;
-use crate::proxy::{user, HOST};
+use crate::proxy::HOST;
+use crate::proxy::block::BLOCKED;
+use crate::proxy::directory::USER_DIRECTORY;
+use crate::proxy::file;
+use crate::proxy::file::{ATTACHMENT, Attachment, format_size};
+use crate::proxy::forward::{FORWARD, Forwarded};
+use crate::proxy::group;
+use crate::proxy::group::{Announcement, PinnedMsg};
+use crate::proxy::reaction;
+use crate::proxy::reaction::Reaction;
+use crate::proxy::remark::REMARKS;
 use crate::token::CURRENT_USER;
 use chrono::{DateTime, Local};
 use color_eyre::eyre::format_err;
-use crossterm::event::{KeyCode, KeyEvent};
-use ratatui::layout::{Alignment, Constraint, Layout, Rect};
-use ratatui::prelude::{Color, Line, Span, Style};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::layout::{Alignment, Constraint, Layout, Margin, Rect};
+use ratatui::prelude::{Color, Line, Modifier, Span, Style};
+use ratatui::text::Text;
 use ratatui::widgets::{
-    Block, Borders, Paragraph, Scrollbar, ScrollbarOrientation, ScrollbarState,
+    Block, Borders, Clear, Gauge, List, ListItem, Paragraph, Scrollbar, ScrollbarOrientation,
+    ScrollbarState, Wrap,
 };
 use ratatui::{symbols, Frame};
-use reqwest::blocking::Client;
 use reqwest::StatusCode;
 use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::{Arc, LazyLock, Mutex};
+use std::time::{Duration, Instant};
 use tokio::sync::broadcast::Receiver;
-use tracing::{debug, info};
+use tokio::sync::mpsc::UnboundedSender;
+use tracing::{debug, info, warn};
 
 pub(crate) static CHAT_VO: LazyLock<Arc<Mutex<ChatVoHolder>>> = LazyLock::new(|| {
     Arc::new(Mutex::new(ChatVoHolder {
@@ -42,8 +69,32 @@ impl ChatVoHolder {
         self.chat_vo = Some(chat_vo);
         self.need_fetch = true;
     }
+
+    pub(crate) fn chat_vo(&self) -> Option<ChatVo> {
+        self.chat_vo.clone()
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.chat_vo = None;
+        self.need_fetch = false;
+    }
+
+    /// 切换账号时取出当前会话
+    pub(crate) fn take(&mut self) -> Option<ChatVo> {
+        self.need_fetch = false;
+        self.chat_vo.take()
+    }
+
+    /// 切换回该账号时恢复会话，并重新获取历史消息
+    pub(crate) fn restore(&mut self, chat_vo: Option<ChatVo>) {
+        self.need_fetch = chat_vo.is_some();
+        self.chat_vo = chat_vo;
+    }
 }
 
+/// 文件以明文上传到服务端，加密会话中不允许发送
+const ENCRYPTED_ATTACHMENT: &str = "加密会话暂不支持发送文件，文件会以明文上传到服务器";
+
 pub(crate) struct Chat {
     mode_holder: ModeHolderLock,
     chat_history: Arc<Mutex<Vec<ChatHistory>>>,
@@ -51,6 +102,29 @@ pub(crate) struct Chat {
     user_input: UserInput,
     chat_state: ChatState,
     chat_rx: Arc<tokio::sync::Mutex<Receiver<ChatMessage>>>,
+    typing: Typing,
+    group_board: GroupBoard,
+    /// 选择模式下选中的消息索引
+    selected: Option<usize>,
+    /// 选择模式下标记要转发的消息id
+    marked: HashSet<i64>,
+    /// 历史消息区域的可见高度，用于选中消息时自动滚动
+    history_height: usize,
+    action_tx: Option<UnboundedSender<Action>>,
+    /// 附件下载目录
+    download_dir: PathBuf,
+    /// 正在进行的上传或下载，同一时间只有一个
+    transfer: Arc<Mutex<Option<Transfer>>>,
+    file_picker: Option<FilePicker>,
+    emoji_picker: Option<EmojiPicker>,
+    forward_picker: Option<ForwardPicker>,
+    /// 表情选择弹窗用于回应的消息id
+    reaction_mid: Option<i64>,
+    /// 短码补全中选中的表情
+    completion: usize,
+    previews: ImagePreviews,
+    /// 每条消息的起始行，最后一项为总行数
+    line_offsets: Vec<usize>,
 }
 
 impl Chat {
@@ -65,6 +139,21 @@ impl Chat {
             }),
             chat_state: Default::default(),
             chat_rx: Arc::new(tokio::sync::Mutex::new(chat_rx)),
+            typing: Typing::default(),
+            group_board: GroupBoard::default(),
+            selected: None,
+            marked: HashSet::new(),
+            history_height: 0,
+            action_tx: None,
+            download_dir: get_download_dir(),
+            transfer: Arc::new(Mutex::new(None)),
+            file_picker: None,
+            emoji_picker: None,
+            forward_picker: None,
+            reaction_mid: None,
+            completion: 0,
+            previews: ImagePreviews::default(),
+            line_offsets: Vec::new(),
         };
         chat.refresh();
         chat
@@ -76,9 +165,11 @@ impl Chat {
                 self.chat_state = ChatState::Chat;
                 self.user_input.is_editing = true;
             }
-            ChatState::Chat => {
+            ChatState::Chat | ChatState::Select => {
                 self.chat_state = ChatState::History;
                 self.user_input.is_editing = false;
+                self.selected = None;
+                self.marked.clear();
             }
         }
     }
@@ -89,6 +180,129 @@ enum ChatState {
     #[default]
     History,
     Chat,
+    /// 选择消息
+    Select,
+}
+
+/// 群公告与置顶消息
+#[derive(Default)]
+struct GroupBoard {
+    announcement: Option<Announcement>,
+    pins: Vec<PinnedMsg>,
+    /// 公告是否折叠为一行
+    collapsed: bool,
+    /// 是否显示置顶消息面板
+    show_pins: bool,
+    /// 当前用户是否为群主或管理员，打开群聊时获取
+    is_admin: bool,
+}
+
+impl GroupBoard {
+    fn clear(&mut self) {
+        self.announcement = None;
+        self.pins.clear();
+        self.is_admin = false;
+    }
+
+    fn banner_height(&self) -> u16 {
+        match &self.announcement {
+            None => 0,
+            Some(_) if self.collapsed => 3,
+            Some(announcement) => (announcement.content.lines().count() as u16 + 3).min(10),
+        }
+    }
+
+    fn banner(&self) -> Option<Paragraph<'_>> {
+        let announcement = self.announcement.as_ref()?;
+        let title = if self.collapsed {
+            "📢 群公告 (b: Expand)"
+        } else {
+            "📢 群公告 (b: Collapse)"
+        };
+        let block = Block::new()
+            .title(title)
+            .borders(Borders::ALL)
+            .border_set(symbols::border::ROUNDED)
+            .border_style(Style::default().fg(Color::Yellow));
+        let text = if self.collapsed {
+            Text::from(announcement.content.lines().next().unwrap_or_default())
+        } else {
+            let mut text = Text::from(announcement.content.as_str());
+            text.push_line(Line::from(Span::styled(
+                format!(
+                    "—— {} {}",
+                    announcement.update_by,
+                    announcement.update_time.format("%Y-%m-%d %H:%M")
+                ),
+                Style::default().fg(Color::DarkGray),
+            )));
+            text
+        };
+        Some(Paragraph::new(text).wrap(Wrap { trim: false }).block(block))
+    }
+
+    fn pins_panel(&self) -> Paragraph<'_> {
+        let block = Block::new()
+            .title(format!("📌 置顶消息({})", self.pins.len()))
+            .title_alignment(Alignment::Center)
+            .borders(Borders::ALL)
+            .border_set(symbols::border::ROUNDED);
+        let lines = self
+            .pins
+            .iter()
+            .flat_map(|pin| {
+                vec![
+                    Line::from(Span::styled(
+                        format!(
+                            "{} {}",
+                            REMARKS.display_name(pin.from_uid, &pin.name_of_from_uid),
+                            pin.time.format("%m-%d %H:%M")
+                        ),
+                        Style::default().fg(Color::White),
+                    )),
+                    Line::from(Span::styled(
+                        pin.msg.clone(),
+                        Style::default().fg(Color::Green),
+                    )),
+                    Line::from(Span::styled(
+                        format!("由 {} 置顶", pin.pinned_by),
+                        Style::default().fg(Color::DarkGray),
+                    )),
+                ]
+            })
+            .collect::<Vec<_>>();
+        Paragraph::new(lines)
+            .wrap(Wrap { trim: false })
+            .block(block)
+    }
+}
+
+/// 文件传输进度
+struct Transfer {
+    /// 如 "上传 a.pdf"
+    label: String,
+    total: u64,
+    done: Arc<AtomicU64>,
+}
+
+impl Transfer {
+    fn gauge(&self) -> Gauge<'_> {
+        let done = self.done.load(Ordering::Relaxed).min(self.total);
+        let ratio = if self.total == 0 {
+            1.0
+        } else {
+            done as f64 / self.total as f64
+        };
+        Gauge::default()
+            .gauge_style(Style::default().fg(Color::Cyan))
+            .ratio(ratio)
+            .label(format!(
+                "{} {} / {}",
+                self.label,
+                format_size(done),
+                format_size(self.total)
+            ))
+    }
 }
 
 #[derive(Default)]
@@ -104,20 +318,78 @@ impl ScrollBar {
     }
 }
 
+/// 短码补全最多显示的表情个数
+const COMPLETION_LIMIT: usize = 5;
+/// 两次发送正在输入通知的最小间隔
+const TYPING_THROTTLE: Duration = Duration::from_secs(3);
+/// 超过该时间未收到通知，则不再显示正在输入
+const TYPING_EXPIRE: Duration = Duration::from_secs(5);
+
+#[derive(Default)]
+struct Typing {
+    /// 当前会话中正在输入的用户，uid -> (name, 最近一次通知时间)
+    users: HashMap<i32, (String, Instant)>,
+    /// 最近一次发送正在输入通知的时间
+    last_sent: Option<Instant>,
+}
+
+impl Typing {
+    fn record(&mut self, uid: i32, name: String) {
+        self.users.insert(uid, (name, Instant::now()));
+    }
+
+    fn clear(&mut self) {
+        self.users.clear();
+        self.last_sent = None;
+    }
+
+    /// 是否需要发送正在输入通知，需要则记录发送时间
+    fn should_send(&mut self) -> bool {
+        match self.last_sent {
+            Some(last) if last.elapsed() < TYPING_THROTTLE => false,
+            _ => {
+                self.last_sent = Some(Instant::now());
+                true
+            }
+        }
+    }
+
+    fn indicator(&mut self) -> Option<String> {
+        self.users
+            .retain(|_, (_, time)| time.elapsed() < TYPING_EXPIRE);
+        if self.users.is_empty() {
+            return None;
+        }
+        let mut names = self
+            .users
+            .values()
+            .map(|(name, _)| name.clone())
+            .collect::<Vec<_>>();
+        names.sort();
+        Some(format!("{} 正在输入…", names.join("、")))
+    }
+}
+
 impl Chat {
     pub(crate) fn send_msg(&self) -> color_eyre::Result<Option<Action>> {
+        // 输入 /attach <路径> 发送文件
+        if let Some(msg) = self.user_input.data()
+            && let Some(path) = msg.strip_prefix("/attach ")
+        {
+            return Ok(self.attach(PathBuf::from(path.trim())));
+        }
         let guard = CHAT_VO.lock().unwrap();
         if let Some(msg) = self.user_input.data() {
             match guard.chat_vo.clone().unwrap() {
                 ChatVo::User { uid, .. } => {
-                    if let Err(err) = send_user_msg(uid, msg) {
+                    if let Err(err) = send_user_msg(uid, msg, None) {
                         Ok(Some(Action::Alert(err.to_string(), None)))
                     } else {
                         Ok(None)
                     }
                 }
                 ChatVo::Group { gid, .. } => {
-                    if let Err(err) = send_group_msg(gid, msg) {
+                    if let Err(err) = send_group_msg(gid, msg, None) {
                         info!("chat group err: {err}");
                         Ok(Some(Action::Alert(err.to_string(), None)))
                     } else {
@@ -130,6 +402,437 @@ impl Chat {
         }
     }
 
+    /// 记录新的文件传输，已有传输进行中时返回 false
+    fn start_transfer(&self, label: String, total: u64, done: Arc<AtomicU64>) -> bool {
+        let mut transfer = self.transfer.lock().unwrap();
+        if transfer.is_some() {
+            return false;
+        }
+        *transfer = Some(Transfer { label, total, done });
+        true
+    }
+
+    /// 后台上传文件，上传完成后发送附件消息
+    fn attach(&self, path: PathBuf) -> Option<Action> {
+        if self.is_encrypted() {
+            return Some(Action::Alert(ENCRYPTED_ATTACHMENT.to_string(), None));
+        }
+        let size = match fs::metadata(&path) {
+            Ok(meta) if meta.is_file() => meta.len(),
+            _ => {
+                return Some(Action::Alert(
+                    format!("文件不存在：{}", path.display()),
+                    None,
+                ));
+            }
+        };
+        let chat_vo = CHAT_VO.lock().unwrap().chat_vo.clone()?;
+        let name = path.file_name()?.to_string_lossy().to_string();
+        let done = Arc::new(AtomicU64::new(0));
+        if !self.start_transfer(format!("上传 {name}"), size, done.clone()) {
+            return Some(Action::Alert(
+                "已有文件正在传输，请稍后再试".to_string(),
+                None,
+            ));
+        }
+        let transfer = Arc::clone(&self.transfer);
+        let action_tx = self.action_tx.clone();
+        tokio::task::spawn_blocking(move || {
+            let res = file::upload(&path, done).and_then(|attachment| {
+                let msg = attachment.to_msg();
+                let content_type = Some(ATTACHMENT.to_string());
+                match chat_vo {
+                    ChatVo::User { uid, .. } => send_user_msg(uid, msg, content_type),
+                    ChatVo::Group { gid, .. } => send_group_msg(gid, msg, content_type),
+                }
+            });
+            transfer.lock().unwrap().take();
+            if let Err(err) = res
+                && let Some(tx) = action_tx
+            {
+                let _ = tx.send(Action::Alert(format!("发送文件失败：{err}"), None));
+            }
+        });
+        None
+    }
+
+    /// 后台下载选中的附件
+    fn download_selected(&self) -> Option<Action> {
+        let attachment = self
+            .chat_history
+            .lock()
+            .unwrap()
+            .get(self.selected?)?
+            .attachment()?;
+        let done = Arc::new(AtomicU64::new(0));
+        let label = format!("下载 {}", attachment.name);
+        if !self.start_transfer(label, attachment.size, done.clone()) {
+            return Some(Action::Alert(
+                "已有文件正在传输，请稍后再试".to_string(),
+                None,
+            ));
+        }
+        let dir = self.download_dir.clone();
+        let transfer = Arc::clone(&self.transfer);
+        let action_tx = self.action_tx.clone();
+        tokio::task::spawn_blocking(move || {
+            let msg = match file::download(&attachment, &dir, done) {
+                Ok(path) => format!("已下载到 {}", path.display()),
+                Err(err) => format!("下载失败：{err}\n再次下载将从中断处继续"),
+            };
+            transfer.lock().unwrap().take();
+            if let Some(tx) = action_tx {
+                let _ = tx.send(Action::Alert(msg, None));
+            }
+        });
+        None
+    }
+
+    fn on_completion_key(&mut self, code: KeyCode) {
+        let completions = self.user_input.emoji_completions(COMPLETION_LIMIT);
+        let len = completions.len();
+        match code {
+            KeyCode::Up => self.completion = (self.completion + len - 1) % len,
+            KeyCode::Down => self.completion = (self.completion + 1) % len,
+            _ => {
+                let emoji = completions[self.completion.min(len - 1)].as_str();
+                self.user_input.complete_emoji(emoji);
+                RECENT_EMOJI.record(emoji);
+                self.completion = 0;
+            }
+        }
+    }
+
+    /// 短码补全列表，显示在输入框上方
+    fn completion_popup(&self, chat_area: Rect) -> Option<(List<'static>, Rect)> {
+        if self.chat_state != ChatState::Chat {
+            return None;
+        }
+        let completions = self.user_input.emoji_completions(COMPLETION_LIMIT);
+        if completions.is_empty() {
+            return None;
+        }
+        let items = completions
+            .iter()
+            .enumerate()
+            .map(|(i, emoji)| {
+                let item = ListItem::new(format!(
+                    "{} :{}:",
+                    emoji.as_str(),
+                    emoji.shortcode().unwrap_or_default()
+                ));
+                if i == self.completion.min(completions.len() - 1) {
+                    item.style(SELECTED_STYLE)
+                } else {
+                    item
+                }
+            })
+            .collect::<Vec<_>>();
+        let height = items.len() as u16 + 2;
+        let area = Rect::new(
+            chat_area.x + 1,
+            chat_area.y.saturating_sub(height),
+            chat_area.width.saturating_sub(2).min(32),
+            height,
+        );
+        let list = List::new(items).block(
+            Block::new()
+                .borders(Borders::ALL)
+                .border_set(symbols::border::ROUNDED),
+        );
+        Some((list, area))
+    }
+
+    /// 输入内容变化时，节流发送正在输入通知
+    fn notify_typing(&mut self) {
+        if !self.typing.should_send() {
+            return;
+        }
+        let chat_vo = CHAT_VO.lock().unwrap().chat_vo.clone();
+        let target = match chat_vo {
+            Some(ChatVo::User { uid, .. }) => format!("{}/user/{uid}/typing", HOST.url()),
+            Some(ChatVo::Group { gid, .. }) => format!("{}/group/{gid}/typing", HOST.url()),
+            None => return,
+        };
+        tokio::task::spawn_blocking(move || {
+            if let Err(err) = send_typing(target) {
+                debug!("fail to send typing: {err}");
+            }
+        });
+    }
+
+    fn on_typing(&mut self, typing: TypingMessage) {
+        let current_uid = CURRENT_USER.get_user().user.unwrap().id;
+        if typing.from_uid == current_uid {
+            return;
+        }
+        let chat_vo = CHAT_VO.lock().unwrap().chat_vo.clone();
+        match (chat_vo, typing.target) {
+            (Some(ChatVo::User { uid, user_name, .. }), MessageTarget::User(target))
+                if uid == typing.from_uid && target.uid == current_uid =>
+            {
+                self.typing.record(uid, user_name);
+            }
+            (Some(ChatVo::Group { gid, .. }), MessageTarget::Group(target))
+                if gid == target.gid =>
+            {
+                let name = USER_DIRECTORY.name(typing.from_uid);
+                self.typing.record(typing.from_uid, name);
+            }
+            _ => {}
+        }
+    }
+
+    /// 获取群公告与置顶消息，失败时不影响聊天
+    fn fetch_board(&mut self, gid: i32) {
+        match group::announcement(gid) {
+            Ok(announcement) => self.group_board.announcement = announcement,
+            Err(err) => warn!("fail to get group announcement: {err}"),
+        }
+        let current_uid = CURRENT_USER.get_user().user.unwrap().id;
+        match group::detail(gid) {
+            Ok(detail) => {
+                self.group_board.is_admin = detail
+                    .users
+                    .iter()
+                    .any(|gu| gu.id == current_uid && (gu.admin || gu.owner))
+            }
+            Err(err) => warn!("fail to get group detail: {err}"),
+        }
+        self.fetch_pins(gid);
+    }
+
+    fn fetch_pins(&mut self, gid: i32) {
+        match group::pins(gid) {
+            Ok(pins) => self.group_board.pins = pins,
+            Err(err) => warn!("fail to get pinned messages: {err}"),
+        }
+    }
+
+    fn current_gid(&self) -> Option<i32> {
+        match CHAT_VO.lock().unwrap().chat_vo {
+            Some(ChatVo::Group { gid, .. }) => Some(gid),
+            _ => None,
+        }
+    }
+
+    /// 会话名称，私聊优先显示好友备注
+    fn conversation_name(&self) -> Option<String> {
+        match &CHAT_VO.lock().unwrap().chat_vo {
+            Some(ChatVo::User { uid, user_name, .. }) => {
+                Some(REMARKS.display_name(*uid, user_name))
+            }
+            Some(ChatVo::Group { group_name, .. }) => Some(group_name.clone()),
+            None => None,
+        }
+    }
+
+    /// 当前私聊是否已开启端到端加密
+    fn is_encrypted(&self) -> bool {
+        match CHAT_VO.lock().unwrap().chat_vo {
+            Some(ChatVo::User { uid, .. }) => E2E.is_enabled(uid),
+            _ => false,
+        }
+    }
+
+    /// 对方的公钥是否已变更
+    fn key_changed(&self) -> bool {
+        match CHAT_VO.lock().unwrap().chat_vo {
+            Some(ChatVo::User { uid, .. }) => E2E.key_changed(uid),
+            _ => false,
+        }
+    }
+
+    fn move_selected(&mut self, up: bool) {
+        let len = self.chat_history.lock().unwrap().len();
+        if len == 0 {
+            return;
+        }
+        let selected = match self.selected {
+            None => len - 1,
+            Some(i) if up => i.saturating_sub(1),
+            Some(i) => (i + 1).min(len - 1),
+        };
+        self.selected = Some(selected);
+        // 保持选中的消息可见，尚未绘制时按每条消息两行计算
+        let (top, bottom) = match (
+            self.line_offsets.get(selected),
+            self.line_offsets.get(selected + 1),
+        ) {
+            (Some(top), Some(bottom)) => (*top, *bottom),
+            _ => (selected * 2, selected * 2 + 2),
+        };
+        if top < self.scroll_bar.vertical_scroll {
+            self.scroll_bar.vertical_scroll = top;
+        } else if bottom > self.scroll_bar.vertical_scroll + self.history_height {
+            self.scroll_bar.vertical_scroll = bottom.saturating_sub(self.history_height);
+        }
+        self.scroll_bar.vertical_scroll_state = self
+            .scroll_bar
+            .vertical_scroll_state
+            .position(self.scroll_bar.vertical_scroll);
+    }
+
+    /// 置顶或取消置顶选中的群消息，仅管理员可操作
+    fn toggle_pin(&mut self, pin: bool) -> color_eyre::Result<Option<Action>> {
+        let (Some(gid), Some(selected)) = (self.current_gid(), self.selected) else {
+            return Ok(None);
+        };
+        let Some(mid) = self
+            .chat_history
+            .lock()
+            .unwrap()
+            .get(selected)
+            .map(|history| history.mid())
+        else {
+            return Ok(None);
+        };
+        if !self.group_board.is_admin {
+            return Ok(Some(Action::Alert(
+                "您不是管理员，无法操作".to_string(),
+                None,
+            )));
+        }
+        let res = if pin {
+            group::pin(gid, mid)
+        } else {
+            group::unpin(gid, mid)
+        };
+        if let Err(err) = res {
+            return Ok(Some(Action::Alert(err.to_string(), None)));
+        }
+        self.fetch_pins(gid);
+        self.group_board.show_pins = true;
+        Ok(None)
+    }
+
+    /// 标记或取消标记选中的消息
+    fn toggle_mark(&mut self) {
+        let Some(mid) = self.selected.and_then(|selected| {
+            self.chat_history
+                .lock()
+                .unwrap()
+                .get(selected)
+                .map(|history| history.mid())
+        }) else {
+            return;
+        };
+        if !self.marked.remove(&mid) {
+            self.marked.insert(mid);
+        }
+    }
+
+    /// 要转发的消息，没有标记时为选中的消息
+    fn forwarding(&self) -> Vec<Forwarded> {
+        let chat_history = self.chat_history.lock().unwrap();
+        if self.marked.is_empty() {
+            return self
+                .selected
+                .and_then(|selected| chat_history.get(selected))
+                .map(|history| vec![history.to_forwarded()])
+                .unwrap_or_default();
+        }
+        chat_history
+            .iter()
+            .filter(|history| self.marked.contains(&history.mid()))
+            .map(ChatHistory::to_forwarded)
+            .collect()
+    }
+
+    fn open_forward_picker(&mut self) -> Option<Action> {
+        let count = self.forwarding().len();
+        if count == 0 {
+            return None;
+        }
+        match ForwardPicker::new(count) {
+            Ok(picker) => self.forward_picker = Some(picker),
+            Err(err) => return Some(Action::Alert(err.to_string(), None)),
+        }
+        None
+    }
+
+    /// 按原顺序逐条转发，保留原发送者
+    fn forward(&mut self, target: MessageTarget, name: String) -> Option<Action> {
+        // 加密会话中的消息已解密，不能以明文转发出去
+        let encrypted_target = match target {
+            MessageTarget::User(MessageTargetUser { uid }) => E2E.is_enabled(uid),
+            MessageTarget::Group(_) => false,
+        };
+        if self.is_encrypted() && !encrypted_target {
+            return Some(Action::Alert(
+                "加密会话中的消息只能转发给已开启加密的好友".to_string(),
+                None,
+            ));
+        }
+        let forwarding = self.forwarding();
+        for forwarded in &forwarding {
+            let msg = forwarded.to_msg();
+            let content_type = Some(FORWARD.to_string());
+            let res = match target {
+                MessageTarget::User(MessageTargetUser { uid }) => {
+                    send_user_msg(uid, msg, content_type)
+                }
+                MessageTarget::Group(MessageTargetGroup { gid }) => {
+                    send_group_msg(gid, msg, content_type)
+                }
+            };
+            if let Err(err) = res {
+                return Some(Action::Alert(err.to_string(), None));
+            }
+        }
+        self.marked.clear();
+        Some(Action::Alert(
+            format!("已转发 {} 条消息到 {name}", forwarding.len()),
+            None,
+        ))
+    }
+
+    /// 添加或取消自己对消息的表情回应，已回应过该表情时取消
+    fn toggle_reaction(&mut self, mid: i64, emoji: &str) -> Option<Action> {
+        let target = match CHAT_VO.lock().unwrap().chat_vo {
+            Some(ChatVo::User { uid, .. }) => MessageTarget::User(MessageTargetUser { uid }),
+            Some(ChatVo::Group { gid, .. }) => MessageTarget::Group(MessageTargetGroup { gid }),
+            None => return None,
+        };
+        let uid = CURRENT_USER.get_user().user?.id;
+        let added = !self
+            .chat_history
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|history| history.mid() == mid)?
+            .reactions()
+            .iter()
+            .any(|r| r.emoji == emoji && r.uids.contains(&uid));
+        if let Err(err) = reaction::react(target, mid, emoji.to_string(), added) {
+            return Some(Action::Alert(err.to_string(), None));
+        }
+        // 先更新本地，事件流中的通知重复应用不影响结果
+        self.on_reaction(ReactionMessage {
+            mid,
+            from_uid: uid,
+            emoji: emoji.to_string(),
+            added,
+            time: Local::now(),
+        });
+        None
+    }
+
+    fn on_reaction(&mut self, reaction: ReactionMessage) {
+        let mut chat_history = self.chat_history.lock().unwrap();
+        if let Some(history) = chat_history
+            .iter_mut()
+            .find(|history| history.mid() == reaction.mid)
+        {
+            reaction::apply(
+                history.reactions_mut(),
+                &reaction.emoji,
+                reaction.from_uid,
+                reaction.added,
+            );
+        }
+    }
+
     fn refresh(&mut self) {
         let chat_history = Arc::clone(&self.chat_history);
         let chat_vo_current = Arc::clone(&CHAT_VO);
@@ -139,25 +842,38 @@ impl Chat {
                 debug!("received chat_message: {:?}", chat_message);
                 match chat_message.payload.target {
                     MessageTarget::User(target_user) => {
-                        if let Some(ChatVo::User { .. }) = chat_vo_current.lock().unwrap().chat_vo {
+                        let option = chat_vo_current.lock().unwrap().chat_vo.clone();
+                        if let Some(ChatVo::User { uid, .. }) = option {
                             let user = CURRENT_USER.get_user().user.unwrap();
-                            if user.id == target_user.uid {
+                            // 只追加当前会话的消息
+                            if user.id == target_user.uid && uid == chat_message.payload.from_uid {
                                 let history = UserHistoryMsg {
                                     mid: chat_message.mid,
                                     msg: chat_message.payload.detail.get_content(),
+                                    content_type: chat_message
+                                        .payload
+                                        .detail
+                                        .content_type()
+                                        .map(str::to_string),
+                                    reactions: Vec::new(),
                                     time: chat_message.payload.created_at,
                                     from_uid: chat_message.payload.from_uid,
-                                    from_name: user::detail_by_id(chat_message.payload.from_uid)
-                                        .unwrap()
-                                        .name
-                                        .clone(),
+                                    from_name: USER_DIRECTORY.name(chat_message.payload.from_uid),
                                 };
                                 let mut guard = chat_history.lock().unwrap();
                                 guard.push(ChatHistory::User(history));
-                            } else if user.id == chat_message.payload.from_uid {
+                            } else if user.id == chat_message.payload.from_uid
+                                && uid == target_user.uid
+                            {
                                 let history = UserHistoryMsg {
                                     mid: chat_message.mid,
                                     msg: chat_message.payload.detail.get_content(),
+                                    content_type: chat_message
+                                        .payload
+                                        .detail
+                                        .content_type()
+                                        .map(str::to_string),
+                                    reactions: Vec::new(),
                                     time: chat_message.payload.created_at,
                                     from_uid: chat_message.payload.from_uid,
                                     from_name: user.name.clone(),
@@ -169,28 +885,25 @@ impl Chat {
                     }
                     MessageTarget::Group(target_group) => {
                         let option = chat_vo_current.lock().unwrap().chat_vo.clone();
-                        if let Some(ChatVo::Group { gid, .. }) = option {
-                            if gid == target_group.gid {
-                                let from_name = if CURRENT_USER.get_user().user.unwrap().id
-                                    == chat_message.payload.from_uid
-                                {
-                                    CURRENT_USER.get_user().user.unwrap().name.clone()
-                                } else {
-                                    user::detail_by_id(chat_message.payload.from_uid)
-                                        .unwrap()
-                                        .name
-                                        .clone()
-                                };
-                                let history = GroupHistoryMsg {
-                                    mid: chat_message.mid,
-                                    msg: chat_message.payload.detail.get_content(),
-                                    time: chat_message.payload.created_at,
-                                    from_uid: chat_message.payload.from_uid,
-                                    name_of_from_uid: from_name,
-                                };
-                                let mut guard = chat_history.lock().unwrap();
-                                guard.push(ChatHistory::Group(history));
-                            }
+                        if let Some(ChatVo::Group { gid, .. }) = option
+                            && gid == target_group.gid
+                        {
+                            let from_name = USER_DIRECTORY.name(chat_message.payload.from_uid);
+                            let history = GroupHistoryMsg {
+                                mid: chat_message.mid,
+                                msg: chat_message.payload.detail.get_content(),
+                                content_type: chat_message
+                                    .payload
+                                    .detail
+                                    .content_type()
+                                    .map(str::to_string),
+                                reactions: Vec::new(),
+                                time: chat_message.payload.created_at,
+                                from_uid: chat_message.payload.from_uid,
+                                name_of_from_uid: from_name,
+                            };
+                            let mut guard = chat_history.lock().unwrap();
+                            guard.push(ChatHistory::Group(history));
                         }
                     }
                 }
@@ -200,10 +913,19 @@ impl Chat {
 
     fn fetch_history(&mut self, chat_vo: ChatVo) -> color_eyre::Result<Option<Action>> {
         self.chat_history.lock().unwrap().clear();
+        self.marked.clear();
+        self.typing.clear();
+        self.group_board.clear();
         match chat_vo {
             ChatVo::User { uid, .. } => {
+                if E2E.is_enabled(uid) {
+                    E2E.check_peer_key(uid);
+                }
                 match proxy::send_request(move || fetch_user_history(uid))? {
-                    Ok(chat_history) => {
+                    Ok(mut chat_history) => {
+                        chat_history
+                            .iter_mut()
+                            .for_each(|history| history.msg = E2E.decrypt(uid, &history.msg));
                         if chat_history.is_empty() {
                             return Ok(None);
                         }
@@ -211,6 +933,7 @@ impl Chat {
                         let mut guard = self.chat_history.lock().unwrap();
                         chat_history
                             .into_iter()
+                            .filter(|history| !BLOCKED.contains(history.from_uid))
                             .map(ChatHistory::User)
                             .for_each(|c| guard.push(c));
                         // 更新 已读索引
@@ -227,12 +950,17 @@ impl Chat {
                 }
             }
             ChatVo::Group { gid, .. } => {
+                self.fetch_board(gid);
                 match proxy::send_request(move || fetch_group_history(gid))? {
                     Ok(chat_history) => {
+                        if chat_history.is_empty() {
+                            return Ok(None);
+                        }
                         let last_mid = chat_history.last().unwrap().mid;
                         let mut guard = self.chat_history.lock().unwrap();
                         chat_history
                             .into_iter()
+                            .filter(|history| !BLOCKED.contains(history.from_uid))
                             .map(ChatHistory::Group)
                             .for_each(|c| guard.push(c));
                         // 更新 已读索引
@@ -253,45 +981,171 @@ impl Chat {
 }
 
 impl ChatHistory {
-    fn convert_lines(&self) -> Vec<Line> {
+    fn mid(&self) -> i64 {
         match self {
+            ChatHistory::User(history) => history.mid,
+            ChatHistory::Group(history) => history.mid,
+        }
+    }
+
+    fn reactions(&self) -> &[Reaction] {
+        match self {
+            ChatHistory::User(history) => &history.reactions,
+            ChatHistory::Group(history) => &history.reactions,
+        }
+    }
+
+    fn reactions_mut(&mut self) -> &mut Vec<Reaction> {
+        match self {
+            ChatHistory::User(history) => &mut history.reactions,
+            ChatHistory::Group(history) => &mut history.reactions,
+        }
+    }
+
+    /// 消息类型和内容
+    fn content(&self) -> (Option<&str>, &str) {
+        match self {
+            ChatHistory::User(history) => (history.content_type.as_deref(), &history.msg),
+            ChatHistory::Group(history) => (history.content_type.as_deref(), &history.msg),
+        }
+    }
+
+    fn forwarded(&self) -> Option<Forwarded> {
+        let (content_type, msg) = self.content();
+        Forwarded::from_msg(content_type, msg)
+    }
+
+    /// 消息中的附件，包括转发的附件
+    fn attachment(&self) -> Option<Attachment> {
+        let (content_type, msg) = self.content();
+        match self.forwarded() {
+            Some(forwarded) => forwarded.attachment(),
+            None => Attachment::from_msg(content_type, msg),
+        }
+    }
+
+    /// 转发该消息，已转发的消息保留最初的发送者
+    fn to_forwarded(&self) -> Forwarded {
+        if let Some(forwarded) = self.forwarded() {
+            return forwarded;
+        }
+        let (from_uid, from_name, time) = match self {
+            ChatHistory::User(history) => (history.from_uid, &history.from_name, history.time),
+            ChatHistory::Group(history) => {
+                (history.from_uid, &history.name_of_from_uid, history.time)
+            }
+        };
+        let (content_type, msg) = self.content();
+        Forwarded {
+            from_uid,
+            from_name: from_name.clone(),
+            time,
+            msg: msg.to_string(),
+            content_type: content_type.map(str::to_string),
+        }
+    }
+
+    /// 消息内容，转发的消息显示为带原发送者的引用块
+    fn content_lines(&self) -> Vec<Line<'static>> {
+        let Some(forwarded) = self.forwarded() else {
+            let (content_type, msg) = self.content();
+            return vec![content_line(content_type, msg)];
+        };
+        let quote = Span::styled("┃ ", Style::default().fg(Color::DarkGray));
+        let mut content = content_line(forwarded.content_type.as_deref(), &forwarded.msg);
+        content.spans.insert(0, quote.clone());
+        vec![
+            Line::from(vec![
+                quote,
+                Span::styled(
+                    format!(
+                        "转发自 {} {}",
+                        REMARKS.display_name(forwarded.from_uid, &forwarded.from_name),
+                        forwarded.time.format("%Y-%m-%d %H:%M")
+                    ),
+                    Style::default()
+                        .fg(Color::DarkGray)
+                        .add_modifier(Modifier::ITALIC),
+                ),
+            ]),
+            content,
+        ]
+    }
+
+    /// 按表情聚合的回应及人数，自己回应过的高亮显示
+    fn reaction_line(&self) -> Option<Line<'_>> {
+        let reactions = self.reactions();
+        if reactions.is_empty() {
+            return None;
+        }
+        let uid = CURRENT_USER.get_user().user.map(|user| user.id);
+        let spans = reactions
+            .iter()
+            .flat_map(|reaction| {
+                let style = if uid.is_some_and(|uid| reaction.uids.contains(&uid)) {
+                    Style::default().fg(Color::Black).bg(Color::Yellow)
+                } else {
+                    Style::default().fg(Color::White).bg(Color::Indexed(238))
+                };
+                [
+                    Span::styled(
+                        format!(" {} {} ", reaction.emoji, reaction.uids.len()),
+                        style,
+                    ),
+                    Span::raw(" "),
+                ]
+            })
+            .collect::<Vec<_>>();
+        Some(Line::from(spans))
+    }
+
+    fn convert_lines(&self) -> Vec<Line<'_>> {
+        let header = match self {
             ChatHistory::User(UserHistoryMsg {
-                mid: _mid,
-                msg,
                 time,
-                from_uid: _,
+                from_uid,
                 from_name,
-            }) => {
-                vec![
-                    Line::from(Span::styled(
-                        format!("{from_name} {time}\n"),
-                        Style::default().fg(Color::White),
-                    )),
-                    Line::from(Span::styled(
-                        msg.to_string(),
-                        Style::default().fg(Color::Green),
-                    )),
-                ]
-            }
+                ..
+            }) => format!("{} {time}\n", REMARKS.display_name(*from_uid, from_name)),
             ChatHistory::Group(GroupHistoryMsg {
-                mid: _mid,
-                msg,
                 time,
-                from_uid: _,
+                from_uid,
                 name_of_from_uid,
-            }) => {
-                vec![
-                    Line::from(Span::styled(
-                        format!("{name_of_from_uid} {time}\n"),
-                        Style::default().fg(Color::White),
-                    )),
-                    Line::from(Span::styled(
-                        msg.to_string(),
-                        Style::default().fg(Color::Green),
-                    )),
-                ]
-            }
-        }
+                ..
+            }) => format!(
+                "{} {time}\n",
+                REMARKS.display_name(*from_uid, name_of_from_uid)
+            ),
+        };
+        let mut lines = vec![Line::from(Span::styled(
+            header,
+            Style::default().fg(Color::White),
+        ))];
+        lines.extend(self.content_lines());
+        lines.extend(self.reaction_line());
+        lines
+    }
+}
+
+/// 消息内容，附件显示为包含文件名、大小和类型的卡片
+fn content_line(content_type: Option<&str>, msg: &str) -> Line<'static> {
+    match Attachment::from_msg(content_type, msg) {
+        Some(attachment) => Line::from(vec![
+            Span::styled(
+                format!("📎 {}", attachment.name),
+                Style::default()
+                    .fg(Color::Cyan)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            Span::styled(
+                format!("  {} · {}", format_size(attachment.size), attachment.mime),
+                Style::default().fg(Color::DarkGray),
+            ),
+        ]),
+        None => Line::from(Span::styled(
+            msg.to_string(),
+            Style::default().fg(Color::Green),
+        )),
     }
 }
 
@@ -300,6 +1154,46 @@ impl Component for Chat {
         if self.mode_holder.get_mode() != Mode::Chat {
             return Ok(None);
         }
+        if let Some(emoji_picker) = self.emoji_picker.as_mut() {
+            match emoji_picker.handle_key_event(key) {
+                EmojiPicked::None => {}
+                EmojiPicked::Emoji(emoji) => {
+                    self.emoji_picker = None;
+                    RECENT_EMOJI.record(emoji);
+                    match self.reaction_mid.take() {
+                        Some(mid) => return Ok(self.toggle_reaction(mid, emoji)),
+                        None => self.user_input.insert_str(emoji),
+                    }
+                }
+                EmojiPicked::Cancel => {
+                    self.emoji_picker = None;
+                    self.reaction_mid = None;
+                }
+            }
+            return Ok(None);
+        }
+        if let Some(forward_picker) = self.forward_picker.as_mut() {
+            match forward_picker.handle_key_event(key) {
+                ForwardPicked::None => {}
+                ForwardPicked::Target(target, name) => {
+                    self.forward_picker = None;
+                    return Ok(self.forward(target, name));
+                }
+                ForwardPicked::Cancel => self.forward_picker = None,
+            }
+            return Ok(None);
+        }
+        if let Some(file_picker) = self.file_picker.as_mut() {
+            match file_picker.handle_key_event(key) {
+                Picked::None => {}
+                Picked::File(path) => {
+                    self.file_picker = None;
+                    return Ok(self.attach(path));
+                }
+                Picked::Cancel => self.file_picker = None,
+            }
+            return Ok(None);
+        }
         match self.chat_state {
             ChatState::History => match key.code {
                 KeyCode::Esc => {
@@ -324,6 +1218,29 @@ impl Component for Chat {
                 KeyCode::Char('e') => {
                     self.next_state();
                 }
+                KeyCode::Char('s') => {
+                    self.chat_state = ChatState::Select;
+                    self.move_selected(false);
+                }
+                KeyCode::Char('a') => {
+                    if self.is_encrypted() {
+                        return Ok(Some(Action::Alert(ENCRYPTED_ATTACHMENT.to_string(), None)));
+                    }
+                    self.file_picker = Some(FilePicker::new());
+                }
+                KeyCode::Char('b') => {
+                    self.group_board.collapsed = !self.group_board.collapsed;
+                }
+                KeyCode::Char('P') => {
+                    self.group_board.show_pins = !self.group_board.show_pins;
+                }
+                KeyCode::Char('k') => {
+                    if let Some(ChatVo::User { uid, .. }) = CHAT_VO.lock().unwrap().chat_vo
+                        && let Err(err) = E2E.toggle(uid)
+                    {
+                        return Ok(Some(Action::Alert(err.to_string(), None)));
+                    }
+                }
                 KeyCode::Char('m') => match CHAT_VO.lock().unwrap().chat_vo.clone() {
                     None => {}
                     Some(chat_vo) => match chat_vo {
@@ -334,24 +1251,122 @@ impl Component for Chat {
                 _ => {}
             },
             ChatState::Chat => match key.code {
+                // 有短码补全时，↑↓ 选择、Enter 确认补全
+                KeyCode::Up | KeyCode::Down | KeyCode::Enter
+                    if !self
+                        .user_input
+                        .emoji_completions(COMPLETION_LIMIT)
+                        .is_empty() =>
+                {
+                    self.on_completion_key(key.code);
+                }
+                KeyCode::Char('e') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                    self.emoji_picker = Some(EmojiPicker::new());
+                }
                 KeyCode::Enter => {
                     self.user_input.submit_message();
                     let result = self.send_msg();
                     self.user_input.reset();
                     return result;
                 }
-                KeyCode::Char(to_insert) => self.user_input.enter_char(to_insert),
-                KeyCode::Backspace => self.user_input.delete_char(),
+                KeyCode::Char(to_insert) => {
+                    self.user_input.enter_char(to_insert);
+                    if to_insert == ':'
+                        && let Some(emoji) = self.user_input.expand_shortcode()
+                    {
+                        RECENT_EMOJI.record(emoji);
+                    }
+                    self.completion = 0;
+                    self.notify_typing();
+                }
+                KeyCode::Backspace => {
+                    self.user_input.delete_char();
+                    self.completion = 0;
+                    self.notify_typing();
+                }
                 KeyCode::Left => self.user_input.move_cursor_left(),
                 KeyCode::Right => self.user_input.move_cursor_right(),
                 KeyCode::Esc => self.next_state(),
                 _ => {}
             },
+            ChatState::Select => match key.code {
+                KeyCode::Up => self.move_selected(true),
+                KeyCode::Down => self.move_selected(false),
+                KeyCode::Char('p') => return self.toggle_pin(true),
+                KeyCode::Char('u') => return self.toggle_pin(false),
+                KeyCode::Char('d') => return Ok(self.download_selected()),
+                KeyCode::Char(' ') => self.toggle_mark(),
+                KeyCode::Char('f') => return Ok(self.open_forward_picker()),
+                KeyCode::Char('r') => {
+                    let mid = self.selected.and_then(|selected| {
+                        self.chat_history
+                            .lock()
+                            .unwrap()
+                            .get(selected)
+                            .map(|history| history.mid())
+                    });
+                    if mid.is_some() {
+                        self.reaction_mid = mid;
+                        self.emoji_picker = Some(EmojiPicker::new());
+                    }
+                }
+                KeyCode::Esc => self.next_state(),
+                _ => {}
+            },
         }
         Ok(None)
     }
 
-    fn update(&mut self, _action: Action) -> color_eyre::Result<Option<Action>> {
+    fn register_action_handler(&mut self, tx: UnboundedSender<Action>) -> color_eyre::Result<()> {
+        self.action_tx = Some(tx);
+        Ok(())
+    }
+
+    fn register_config_handler(&mut self, config: Config) -> color_eyre::Result<()> {
+        if let Some(dir) = config.download_dir {
+            self.download_dir = dir;
+        }
+        if let Some(protocol) = config.image_protocol {
+            GRAPHICS.set_protocol(protocol);
+        }
+        Ok(())
+    }
+
+    fn update(&mut self, action: Action) -> color_eyre::Result<Option<Action>> {
+        match action {
+            Action::Typing(typing) => {
+                self.on_typing(typing);
+                return Ok(None);
+            }
+            Action::Reaction(reaction) => {
+                self.on_reaction(reaction);
+                return Ok(None);
+            }
+            // 当前会话已不存在，如退出群聊或切换账号
+            Action::LoginSuccess | Action::RefreshRecentChat | Action::Logout
+                if CHAT_VO.lock().unwrap().chat_vo.is_none() =>
+            {
+                self.chat_state = ChatState::History;
+                self.user_input.is_editing = false;
+                self.selected = None;
+                self.marked.clear();
+                self.file_picker = None;
+                self.forward_picker = None;
+                self.emoji_picker = None;
+                self.reaction_mid = None;
+                self.chat_history.lock().unwrap().clear();
+                self.typing.clear();
+                self.scroll_bar.reset();
+                self.group_board.clear();
+                return Ok(None);
+            }
+            Action::AnnouncementChanged(gid) if self.current_gid() == Some(gid) => {
+                self.fetch_board(gid);
+                self.group_board.collapsed = false;
+                return Ok(None);
+            }
+            _ => {}
+        }
         match self.mode_holder.get_mode() {
             Mode::RecentChat => {
                 let mut chat_vo_guard = CHAT_VO.lock().unwrap();
@@ -376,34 +1391,123 @@ impl Component for Chat {
         match self.mode_holder.get_mode() {
             Mode::RecentChat | Mode::Chat => {
                 let area = area_util::chat(area);
-                let [chat_history_area, chat_area] =
-                    Layout::vertical([Constraint::Fill(1), Constraint::Length(6)]).areas(area);
+                let is_group = self.current_gid().is_some();
+                let banner_height = if is_group {
+                    self.group_board.banner_height()
+                } else {
+                    0
+                };
+                let transfer = self.transfer.lock().unwrap();
+                let [banner_area, chat_history_area, transfer_area, chat_area] =
+                    Layout::vertical([
+                        Constraint::Length(banner_height),
+                        Constraint::Fill(1),
+                        Constraint::Length(transfer.is_some() as u16),
+                        Constraint::Length(6),
+                    ])
+                    .areas(area);
+                if let Some(transfer) = transfer.as_ref() {
+                    frame.render_widget(transfer.gauge(), transfer_area);
+                }
+                drop(transfer);
+                if is_group && let Some(banner) = self.group_board.banner() {
+                    frame.render_widget(banner, banner_area);
+                }
+                let chat_history_area = if is_group && self.group_board.show_pins {
+                    let [chat_history_area, pins_area] =
+                        Layout::horizontal([Constraint::Fill(1), Constraint::Percentage(35)])
+                            .areas(chat_history_area);
+                    frame.render_widget(self.group_board.pins_panel(), pins_area);
+                    chat_history_area
+                } else {
+                    chat_history_area
+                };
+                self.history_height = chat_history_area.height.saturating_sub(2) as usize;
 
-                let chat_history_title = match CHAT_VO.lock().unwrap().chat_vo.clone() {
-                    Some(ChatVo::Group { .. }) => "Press ↑↓ To Scroll, Press m To Manage Group.",
-                    _ => "Press ↑↓ To Scroll.",
+                let chat_history_title = match (&self.chat_state, is_group) {
+                    (ChatState::Select, true) => {
+                        "↑↓: Select, Space: Mark, f: Forward, r: React, d: Download, p: Pin, u: Unpin, Esc: Back"
+                    }
+                    (ChatState::Select, false) => {
+                        "↑↓: Select, Space: Mark, f: Forward, r: React, d: Download, Esc: Back"
+                    }
+                    (_, true) => {
+                        "↑↓: Scroll, s: Select, a: Attach, m: Manage Group, b: Announcement, P: Pinned"
+                    }
+                    _ => "↑↓: Scroll, s: Select, a: Attach, k: Toggle Encryption",
                 };
-                let chat_history_block = Block::new()
+                let mut chat_history_block = Block::new()
                     .title(chat_history_title)
                     .title_alignment(Alignment::Center)
                     .borders(Borders::ALL)
                     .border_set(symbols::border::ROUNDED);
+                if let Some(name) = self.conversation_name() {
+                    let name = if self.key_changed() {
+                        format!("⚠ {name}（对方公钥已变更，已停止加密）")
+                    } else if self.is_encrypted() {
+                        format!("🔒 {name}")
+                    } else {
+                        name
+                    };
+                    chat_history_block = chat_history_block.title(
+                        Line::from(Span::styled(
+                            name,
+                            Style::default()
+                                .fg(Color::Cyan)
+                                .add_modifier(Modifier::BOLD),
+                        ))
+                        .left_aligned(),
+                    );
+                }
+                if let Some(indicator) = self.typing.indicator() {
+                    chat_history_block = chat_history_block.title_bottom(
+                        Line::from(Span::styled(indicator, Style::default().fg(Color::Yellow)))
+                            .left_aligned(),
+                    );
+                }
                 let chat_history = self.chat_history.lock().unwrap();
-                let items = chat_history
-                    .iter()
-                    .flat_map(|chat_history| chat_history.convert_lines())
-                    .collect::<Vec<_>>();
+                // 去掉边框和滚动条后缩略图可用的宽度
+                let max_cols = chat_history_area.width.saturating_sub(3);
+                let mut items = Vec::new();
+                // 图片缩略图：(起始行, 附件, 列数, 行数)
+                let mut images = Vec::new();
+                self.line_offsets.clear();
+                for (i, history) in chat_history.iter().enumerate() {
+                    self.line_offsets.push(items.len());
+                    let mut lines = history.convert_lines();
+                    if self.marked.contains(&history.mid()) {
+                        lines[0]
+                            .spans
+                            .insert(0, Span::styled("✔ ", Style::default().fg(Color::Green)));
+                    }
+                    if self.selected == Some(i) {
+                        items.extend(
+                            lines
+                                .into_iter()
+                                .map(|line| line.patch_style(Style::default().bg(Color::DarkGray))),
+                        );
+                    } else {
+                        items.extend(lines);
+                    }
+                    if let Some(attachment) = history.attachment()
+                        && let Some((cols, rows)) =
+                            self.previews
+                                .size(&attachment, max_cols, self.history_height as u16)
+                    {
+                        // 缩略图显示在消息内容和表情回应之间
+                        let at = items.len() - !history.reactions().is_empty() as usize;
+                        items.splice(at..at, self.previews.lines(&attachment, cols, rows));
+                        images.push((at, attachment, cols, rows));
+                    }
+                }
+                self.line_offsets.push(items.len());
                 let scrollbar = Scrollbar::new(ScrollbarOrientation::VerticalRight)
                     .begin_symbol(Some("↑"))
                     .end_symbol(Some("↓"));
-                let content_length = chat_history.len();
-                // let view_length = (chat_history_area.height as usize - 2) / 2;
-                // info!("view_length: {}", view_length);
                 self.scroll_bar.vertical_scroll_state = self
                     .scroll_bar
                     .vertical_scroll_state
-                    .content_length(content_length);
-                // .viewport_content_length(view_length);
+                    .content_length(items.len());
                 let chat_history = Paragraph::new(items)
                     .block(chat_history_block)
                     .scroll((self.scroll_bar.vertical_scroll as u16, 0));
@@ -413,11 +1517,32 @@ impl Component for Chat {
                     chat_history_area,
                     &mut self.scroll_bar.vertical_scroll_state,
                 );
-                let block = Block::new()
+                // 只绘制完整显示在可见区域内的缩略图
+                let inner = chat_history_area.inner(Margin {
+                    horizontal: 1,
+                    vertical: 1,
+                });
+                for (offset, attachment, cols, rows) in images {
+                    let Some(y) = offset.checked_sub(self.scroll_bar.vertical_scroll) else {
+                        continue;
+                    };
+                    if y + rows as usize > inner.height as usize {
+                        continue;
+                    }
+                    let area = Rect::new(inner.x, inner.y + y as u16, cols, rows);
+                    self.previews.place(&attachment, area, frame.buffer_mut());
+                }
+                let mut block = Block::new()
                     .title(self.user_input.input_data.label())
                     .title_alignment(Alignment::Center)
                     .borders(Borders::ALL)
                     .border_set(symbols::border::ROUNDED);
+                if self.chat_state == ChatState::Chat {
+                    block = block.title_bottom(
+                        Line::from("Ctrl-e: Emoji, :name: Shortcode, /attach <path>: Send File")
+                            .centered(),
+                    );
+                }
                 let user_input =
                     Paragraph::new(self.user_input.input.clone().unwrap_or("".to_string()))
                         .style(self.user_input.select_style())
@@ -426,6 +1551,19 @@ impl Component for Chat {
                 if self.chat_state == ChatState::Chat {
                     self.user_input.set_cursor_position(chat_area)
                 }
+                if let Some((completions, completion_area)) = self.completion_popup(chat_area) {
+                    frame.render_widget(Clear, completion_area);
+                    frame.render_widget(completions, completion_area);
+                }
+                if let Some(file_picker) = self.file_picker.as_mut() {
+                    file_picker.draw(frame, area_util::centered_rect(80, 70, area));
+                }
+                if let Some(emoji_picker) = self.emoji_picker.as_mut() {
+                    emoji_picker.draw(frame, area_util::centered_rect(80, 70, area));
+                }
+                if let Some(forward_picker) = self.forward_picker.as_mut() {
+                    forward_picker.draw(frame, area_util::centered_rect(60, 70, area));
+                }
             }
             _ => {}
         }
@@ -438,24 +1576,48 @@ impl Component for Chat {
 pub struct SendMsgReq {
     /// Message content
     pub msg: String,
+    /// Content type, 文本消息不发送
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content_type: Option<String>,
 }
 
-fn send_user_msg(uid: i32, msg: String) -> color_eyre::Result<()> {
-    let url = format!("{}/user/{uid}/send", HOST.as_str());
-    proxy::send_request(|| send_msg(msg, url))?
+fn send_user_msg(uid: i32, msg: String, content_type: Option<String>) -> color_eyre::Result<()> {
+    // 开启加密的私聊只发送密文
+    let msg = if E2E.is_enabled(uid) {
+        E2E.encrypt(uid, &msg)?
+    } else {
+        msg
+    };
+    let url = format!("{}/user/{uid}/send", HOST.url());
+    proxy::send_request(|| send_msg(msg, content_type, url))?
 }
 
-fn send_group_msg(gid: i32, msg: String) -> color_eyre::Result<()> {
-    let url = format!("{}/group/{gid}/send", HOST.as_str());
-    proxy::send_request(|| send_msg(msg, url))?
+fn send_group_msg(gid: i32, msg: String, content_type: Option<String>) -> color_eyre::Result<()> {
+    let url = format!("{}/group/{gid}/send", HOST.url());
+    proxy::send_request(|| send_msg(msg, content_type, url))?
+}
+
+fn send_typing(url: String) -> color_eyre::Result<()> {
+    let token = CURRENT_USER.get_user().token.clone().unwrap();
+    let res = proxy::client()
+        .put(url)
+        .header("Authorization", format!("Bearer {token}"))
+        .send();
+    match res {
+        Ok(res) => match res.status() {
+            StatusCode::OK => Ok(()),
+            _ => Err(format_err!("Failed to send typing: {}", res.status())),
+        },
+        Err(err) => Err(format_err!("Failed to send typing:{err}")),
+    }
 }
 
-fn send_msg(msg: String, url: String) -> color_eyre::Result<()> {
+fn send_msg(msg: String, content_type: Option<String>, url: String) -> color_eyre::Result<()> {
     let token = CURRENT_USER.get_user().token.clone().unwrap();
     debug!("sending msg to {url}, msg: {msg}");
-    let res = Client::new()
+    let res = proxy::client()
         .put(url)
-        .json(&SendMsgReq { msg })
+        .json(&SendMsgReq { msg, content_type })
         .header("Authorization", format!("Bearer {token}"))
         .send();
     match res {
@@ -481,9 +1643,9 @@ enum ChatHistory {
 }
 
 fn fetch_user_history(target_uid: i32) -> color_eyre::Result<Vec<UserHistoryMsg>> {
-    let url = format!("{}/user/{target_uid}/history", HOST.as_str());
+    let url = format!("{}/user/{target_uid}/history", HOST.url());
     let token = CURRENT_USER.get_user().token.clone().unwrap();
-    let res = Client::new()
+    let res = proxy::client()
         .get(url)
         .header("Authorization", format!("Bearer {token}"))
         .send();
@@ -505,6 +1667,12 @@ struct UserHistoryMsg {
     mid: i64,
     /// 消息内容
     msg: String,
+    /// 消息类型，文本消息为空
+    #[serde(default)]
+    content_type: Option<String>,
+    /// 表情回应
+    #[serde(default)]
+    reactions: Vec<Reaction>,
     /// 消息发送时间
     #[serde(with = "datetime_format")]
     time: DateTime<Local>,
@@ -518,6 +1686,10 @@ struct UserHistoryMsg {
 struct GroupHistoryMsg {
     pub mid: i64,
     pub msg: String,
+    #[serde(default)]
+    pub content_type: Option<String>,
+    #[serde(default)]
+    pub reactions: Vec<Reaction>,
     #[serde(with = "datetime_format")]
     pub time: DateTime<Local>,
     pub from_uid: i32,
@@ -525,9 +1697,9 @@ struct GroupHistoryMsg {
 }
 
 fn fetch_group_history(gid: i32) -> color_eyre::Result<Vec<GroupHistoryMsg>> {
-    let url = format!("{}/group/{gid}/history", HOST.as_str());
+    let url = format!("{}/group/{gid}/history", HOST.url());
     let token = CURRENT_USER.get_user().token.clone().unwrap();
-    let res = Client::new()
+    let res = proxy::client()
         .get(url)
         .header("Authorization", format!("Bearer {token}"))
         .send();
@@ -551,8 +1723,8 @@ enum UpdateReadIndex {
 
 fn set_read_index(ri: UpdateReadIndex) -> color_eyre::Result<()> {
     let token = CURRENT_USER.get_user().token.clone().unwrap();
-    let res = Client::new()
-        .put(format!("{}/ri", HOST.as_str()))
+    let res = proxy::client()
+        .put(format!("{}/ri", HOST.url()))
         .header("Authorization", format!("Bearer {token}"))
         .json(&ri)
         .send();
@@ -3,8 +3,15 @@ use crate::app::{Mode, ModeHolderLock};
 use crate::components::recent_chat::SELECTED_STYLE;
 use crate::components::user_input::{InputData, UserInput};
 use crate::components::{Component, area_util};
-use crate::proxy::friend::{Friend, FriendReq, FriendRequestStatus};
-use crate::proxy::{friend, user};
+use crate::e2e::E2E;
+use crate::proxy::block::BLOCKED;
+use crate::proxy::directory::USER_DIRECTORY;
+use crate::proxy::friend::{
+    Friend, FriendReq, FriendRequestStatus, PENDING_FRIEND_REQS, SentFriendReq,
+};
+use crate::proxy::presence::PRESENCES;
+use crate::proxy::remark::REMARKS;
+use crate::proxy::{block, friend, group, presence, remark, user};
 use crate::token::CURRENT_USER;
 use crossterm::event::{KeyCode, KeyEvent};
 use ratatui::layout::{Alignment, Constraint, Layout, Rect};
@@ -15,6 +22,8 @@ use ratatui::text::{Line, Span};
 use ratatui::widgets::{Block, Borders, HighlightSpacing, List, ListItem, ListState, Paragraph};
 use ratatui::{Frame, symbols};
 use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::sync::atomic::Ordering;
 use std::sync::{Arc, Mutex};
 use tracing::error;
 
@@ -28,6 +37,89 @@ pub(crate) struct Contact {
     search_list_state: ListState,
     user_input: UserInput,
     state: State,
+    /// 创建群聊时选中的好友
+    group_members: HashSet<i32>,
+    /// 群名称输入框
+    group_name_input: UserInput,
+    /// 好友验证消息输入框
+    greeting_input: UserInput,
+    /// 等待填写验证消息的用户
+    pending_friend: Option<FriendSearchRes>,
+    /// 批量处理时选中的好友请求
+    selected_reqs: HashSet<i32>,
+    /// 已折叠的标签分组
+    collapsed_tags: HashSet<String>,
+    /// 好友备注输入框
+    remark_input: UserInput,
+    /// 好友标签输入框
+    tags_input: UserInput,
+    /// 正在编辑备注或标签的好友
+    editing_friend: Option<i32>,
+}
+
+/// 好友列表中的一行，按标签分组时包含分组标题
+#[derive(Clone)]
+enum FriendRow {
+    Tag {
+        name: String,
+        count: usize,
+        collapsed: bool,
+    },
+    Friend(Friend),
+}
+
+/// 未设置标签的好友所在分组
+const UNTAGGED: &str = "未分组";
+
+/// 按标签生成好友列表，没有任何标签时不显示分组，一个好友可以出现在多个分组中
+fn friend_rows(friends: &[Friend], collapsed: &HashSet<String>) -> Vec<FriendRow> {
+    if friends.iter().all(|f| f.tags.is_empty()) {
+        return friends.iter().cloned().map(FriendRow::Friend).collect();
+    }
+    let mut tags = friends
+        .iter()
+        .flat_map(|f| f.tags.iter().cloned())
+        .collect::<Vec<_>>();
+    tags.sort();
+    tags.dedup();
+    tags.push(UNTAGGED.to_string());
+    let mut rows = vec![];
+    for tag in tags {
+        let members = friends
+            .iter()
+            .filter(|f| {
+                if tag == UNTAGGED {
+                    f.tags.is_empty()
+                } else {
+                    f.tags.contains(&tag)
+                }
+            })
+            .collect::<Vec<_>>();
+        if members.is_empty() {
+            continue;
+        }
+        let is_collapsed = collapsed.contains(&tag);
+        rows.push(FriendRow::Tag {
+            name: tag,
+            count: members.len(),
+            collapsed: is_collapsed,
+        });
+        if !is_collapsed {
+            rows.extend(members.into_iter().cloned().map(FriendRow::Friend));
+        }
+    }
+    rows
+}
+
+/// 解析逗号分隔的标签，去除空白与重复
+fn parse_tags(input: &str) -> Vec<String> {
+    let mut tags = vec![];
+    for tag in input.split([',', '，']).map(str::trim) {
+        if !tag.is_empty() && !tags.iter().any(|t| t == tag) {
+            tags.push(tag.to_string());
+        }
+    }
+    tags
 }
 
 struct FriendsHolder {
@@ -38,6 +130,8 @@ struct FriendsHolder {
 struct FriendReqHolder {
     need_fetch: bool,
     friend_reqs: Arc<Mutex<Vec<FriendReq>>>,
+    /// 发出的好友请求
+    sent_reqs: Arc<Mutex<Vec<SentFriendReq>>>,
 }
 
 impl FriendReqHolder {
@@ -48,6 +142,39 @@ impl FriendReqHolder {
             .iter()
             .any(|req| req.status == FriendRequestStatus::WAIT)
     }
+
+    /// 有待处理的好友请求，或有发出的好友请求时，显示好友请求面板
+    fn should_show(&self) -> bool {
+        self.has_new_friend_reqs() || !self.sent_reqs.lock().unwrap().is_empty()
+    }
+
+    fn fetch(&mut self) {
+        self.need_fetch = false;
+        match friend::friend_reqs() {
+            Ok(mut friend_reqs) => {
+                friend_reqs.retain(|req| !BLOCKED.contains(req.request_id));
+                friend_reqs.sort_by_key(|f| f.create_time);
+                let pending = friend_reqs
+                    .iter()
+                    .filter(|req| req.status == FriendRequestStatus::WAIT)
+                    .count();
+                PENDING_FRIEND_REQS.store(pending, Ordering::SeqCst);
+                self.friend_reqs = Arc::new(Mutex::new(friend_reqs));
+            }
+            Err(err) => {
+                error!("Failed to get friend reqs: {}", err);
+            }
+        };
+        match friend::sent_friend_reqs() {
+            Ok(mut sent_reqs) => {
+                sent_reqs.sort_by_key(|f| f.create_time);
+                self.sent_reqs = Arc::new(Mutex::new(sent_reqs));
+            }
+            Err(err) => {
+                error!("Failed to get sent friend reqs: {}", err);
+            }
+        };
+    }
 }
 
 #[derive(Default, Eq, PartialEq)]
@@ -57,6 +184,16 @@ enum State {
     Search,
     AddFriend,
     FriendReq,
+    /// 选择群成员
+    CreateGroup,
+    /// 输入群名称
+    GroupName,
+    /// 输入好友验证消息
+    Greeting,
+    /// 编辑好友备注
+    Remark,
+    /// 编辑好友标签
+    Tags,
 }
 
 impl Contact {
@@ -71,6 +208,7 @@ impl Contact {
             friend_req_holder: FriendReqHolder {
                 need_fetch: true,
                 friend_reqs: Arc::new(Mutex::new(vec![])),
+                sent_reqs: Arc::new(Mutex::new(vec![])),
             },
             friend_list_state: Default::default(),
             friend_req_list_state: Default::default(),
@@ -80,6 +218,31 @@ impl Contact {
                 data: None,
             }),
             state: Default::default(),
+            group_members: HashSet::new(),
+            group_name_input: UserInput::new(InputData::Search {
+                label: Some("Input Group Name, Enter To Create, Esc To Back.".to_string()),
+                data: None,
+            }),
+            greeting_input: UserInput::new(InputData::Search {
+                label: Some("Input Greeting (Optional), Enter To Send, Esc To Back.".to_string()),
+                data: None,
+            }),
+            pending_friend: None,
+            selected_reqs: HashSet::new(),
+            collapsed_tags: HashSet::new(),
+            remark_input: UserInput::new(InputData::Search {
+                label: Some(
+                    "Input Remark (Empty To Clear), Enter To Save, Esc To Back.".to_string(),
+                ),
+                data: None,
+            }),
+            tags_input: UserInput::new(InputData::Search {
+                label: Some(
+                    "Input Tags Separated By Comma, Enter To Save, Esc To Back.".to_string(),
+                ),
+                data: None,
+            }),
+            editing_friend: None,
         }
     }
 
@@ -96,11 +259,201 @@ impl Contact {
             State::AddFriend => {
                 self.state = State::AddFriend;
                 self.user_input.is_editing = false;
+                self.greeting_input.is_editing = false;
             }
             State::FriendReq => {
                 self.state = State::FriendReq;
                 self.user_input.is_editing = false;
             }
+            State::CreateGroup => {
+                self.state = State::CreateGroup;
+                self.user_input.is_editing = false;
+                self.group_name_input.is_editing = false;
+            }
+            State::GroupName => {
+                self.state = State::GroupName;
+                self.group_name_input.is_editing = true;
+            }
+            State::Greeting => {
+                self.state = State::Greeting;
+                self.greeting_input.is_editing = true;
+            }
+            State::Remark => {
+                self.state = State::Remark;
+                self.remark_input.is_editing = true;
+            }
+            State::Tags => {
+                self.state = State::Tags;
+                self.tags_input.is_editing = true;
+            }
+        }
+    }
+
+    fn friend_rows(&self) -> Vec<FriendRow> {
+        friend_rows(
+            &self.friends_holder.friends.lock().unwrap(),
+            &self.collapsed_tags,
+        )
+    }
+
+    /// 当前选中的好友，选中分组标题时返回 None
+    fn selected_friend(&self) -> Option<Friend> {
+        let idx = self.friend_list_state.selected()?;
+        match self.friend_rows().into_iter().nth(idx)? {
+            FriendRow::Friend(friend) => Some(friend),
+            FriendRow::Tag { .. } => None,
+        }
+    }
+
+    /// 选中分组标题时展开或折叠该分组
+    fn toggle_selected_tag(&mut self) -> bool {
+        let Some(idx) = self.friend_list_state.selected() else {
+            return false;
+        };
+        match self.friend_rows().into_iter().nth(idx) {
+            Some(FriendRow::Tag { name, .. }) => {
+                if !self.collapsed_tags.remove(&name) {
+                    self.collapsed_tags.insert(name);
+                }
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn edit_remark(&mut self) {
+        if let Some(friend) = self.selected_friend() {
+            self.remark_input.reset();
+            if let Some(remark) = friend.remark {
+                self.remark_input.set_input(remark);
+            }
+            self.editing_friend = Some(friend.id);
+            self.change_state(State::Remark);
+        }
+    }
+
+    fn edit_tags(&mut self) {
+        if let Some(friend) = self.selected_friend() {
+            self.tags_input.reset();
+            if !friend.tags.is_empty() {
+                self.tags_input.set_input(friend.tags.join(", "));
+            }
+            self.editing_friend = Some(friend.id);
+            self.change_state(State::Tags);
+        }
+    }
+
+    fn close_edit(&mut self) {
+        self.remark_input.reset();
+        self.remark_input.is_editing = false;
+        self.tags_input.reset();
+        self.tags_input.is_editing = false;
+        self.editing_friend = None;
+        self.change_state(State::Friends);
+    }
+
+    /// 保存备注或标签，成功后同步更新好友列表
+    fn save_remark(&mut self) -> Option<Action> {
+        let friend_uid = self.editing_friend?;
+        let mut remark = REMARKS.get(friend_uid);
+        match self.state {
+            State::Remark => {
+                self.remark_input.submit_message();
+                remark.remark = self
+                    .remark_input
+                    .data()
+                    .map(|r| r.trim().to_string())
+                    .filter(|r| !r.is_empty());
+            }
+            State::Tags => {
+                self.tags_input.submit_message();
+                remark.tags = parse_tags(&self.tags_input.data().unwrap_or_default());
+            }
+            _ => return None,
+        }
+        if let Err(err) = remark::update(friend_uid, remark.clone()) {
+            error!("Failed to update remark: {}", err);
+            return Some(Action::Alert(err.to_string(), None));
+        }
+        if let Some(friend) = self
+            .friends_holder
+            .friends
+            .lock()
+            .unwrap()
+            .iter_mut()
+            .find(|f| f.id == friend_uid)
+        {
+            friend.remark = remark.remark;
+            friend.tags = remark.tags;
+        }
+        self.close_edit();
+        // 备注会显示在最近会话中
+        Some(Action::RefreshRecentChat)
+    }
+
+    fn close_greeting(&mut self) {
+        self.greeting_input.reset();
+        self.greeting_input.is_editing = false;
+        self.pending_friend = None;
+    }
+
+    fn toggle_selected_req(&mut self) {
+        if let Some(idx) = self.friend_req_list_state.selected()
+            && let Some(req) = self.friend_req_holder.friend_reqs.lock().unwrap().get(idx)
+            && req.status == FriendRequestStatus::WAIT
+            && !self.selected_reqs.remove(&req.id)
+        {
+            self.selected_reqs.insert(req.id);
+        }
+    }
+
+    fn review_selected_reqs(&self, approve: bool) -> Option<Action> {
+        if self.selected_reqs.is_empty() {
+            return Some(Action::Alert("请先使用空格选择好友请求".to_string(), None));
+        }
+        let ids = self.selected_reqs.iter().copied().collect::<Vec<_>>();
+        let verb = if approve { "通过" } else { "拒绝" };
+        Some(Action::Alert(
+            format!("确定{verb}选中的{}个好友请求么？", ids.len()),
+            Some(ConfirmEvent::ReviewFriendReqs(ids, approve)),
+        ))
+    }
+
+    fn clean_create_group(&mut self) {
+        self.group_members.clear();
+        self.group_name_input.reset();
+        self.group_name_input.is_editing = false;
+        self.friend_list_state.select(None);
+    }
+
+    fn toggle_group_member(&mut self) {
+        if let Some(friend) = self.selected_friend()
+            && !self.group_members.remove(&friend.id)
+        {
+            self.group_members.insert(friend.id);
+        }
+    }
+
+    fn create_group(&mut self) -> Option<Action> {
+        self.group_name_input.submit_message();
+        let Some(name) = self
+            .group_name_input
+            .data()
+            .filter(|name| !name.trim().is_empty())
+        else {
+            return Some(Action::Alert("请输入群名称".to_string(), None));
+        };
+        let members = self.group_members.iter().copied().collect();
+        match group::create(name.clone(), members) {
+            Ok(gid) => {
+                self.clean_create_group();
+                self.change_state(State::Friends);
+                Some(Action::ToChat(ToChat::Group(gid, name)))
+            }
+            Err(err) => {
+                error!("Failed to create group: {}", err);
+                Some(Action::Alert(err.to_string(), None))
+            }
         }
     }
 
@@ -130,14 +483,39 @@ impl Contact {
     }
 
     fn render_friends(&mut self, frame: &mut Frame, friend_area: Rect, block: Block) {
+        let selecting = matches!(self.state, State::CreateGroup | State::GroupName);
         // Iterate through all elements in the `items` and stylize them.
         let items: Vec<ListItem> = self
-            .friends_holder
-            .friends
-            .lock()
-            .unwrap()
+            .friend_rows()
             .iter()
-            .map(|friend| ListItem::new(Text::from(friend)))
+            .map(|row| {
+                let friend = match row {
+                    FriendRow::Friend(friend) => friend,
+                    FriendRow::Tag {
+                        name,
+                        count,
+                        collapsed,
+                    } => {
+                        let arrow = if *collapsed { "▶" } else { "▼" };
+                        return ListItem::new(Line::from(Span::styled(
+                            format!("{arrow} {name} ({count})"),
+                            Style::default().fg(Color::Cyan),
+                        )));
+                    }
+                };
+                if selecting {
+                    let mut line = friend_line(friend);
+                    let mark = if self.group_members.contains(&friend.id) {
+                        "[x] "
+                    } else {
+                        "[ ] "
+                    };
+                    line.spans.insert(0, Span::raw(mark));
+                    ListItem::new(line)
+                } else {
+                    ListItem::new(Text::from(friend))
+                }
+            })
             .collect();
 
         // Create a List from all list items and highlight the currently selected one
@@ -148,24 +526,58 @@ impl Contact {
         frame.render_stateful_widget(list, friend_area, &mut self.friend_list_state);
     }
     fn render_friend_reqs(&mut self, frame: &mut Frame, friend_area: Rect, block: Block) {
-        // Iterate through all elements in the `items` and stylize them.
-        if self.friend_req_holder.has_new_friend_reqs() {
-            let items: Vec<ListItem> = self
-                .friend_req_holder
-                .friend_reqs
-                .lock()
-                .unwrap()
-                .iter()
-                .map(|friend_req| ListItem::new(Text::from(friend_req)))
-                .collect();
-
-            // Create a List from all list items and highlight the currently selected one
-            let list = List::new(items)
-                .block(block)
-                .highlight_style(SELECTED_STYLE)
-                .highlight_spacing(HighlightSpacing::Always);
-            frame.render_stateful_widget(list, friend_area, &mut self.friend_req_list_state);
+        if !self.friend_req_holder.should_show() {
+            return;
         }
+        let [received_area, sent_area] =
+            Layout::vertical([Constraint::Fill(1), Constraint::Percentage(40)]).areas(friend_area);
+        // Iterate through all elements in the `items` and stylize them.
+        let items: Vec<ListItem> = self
+            .friend_req_holder
+            .friend_reqs
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|friend_req| {
+                let mut text = Text::from(friend_req);
+                if !self.selected_reqs.is_empty() && friend_req.status == FriendRequestStatus::WAIT
+                {
+                    let mark = if self.selected_reqs.contains(&friend_req.id) {
+                        "[x] "
+                    } else {
+                        "[ ] "
+                    };
+                    text.lines[0].spans.insert(0, Span::raw(mark));
+                }
+                ListItem::new(text)
+            })
+            .collect();
+
+        // Create a List from all list items and highlight the currently selected one
+        let list = List::new(items)
+            .block(block.title_bottom(
+                Line::from("Space: Select, a: Approve Selected, r: Reject Selected").centered(),
+            ))
+            .highlight_style(SELECTED_STYLE)
+            .highlight_spacing(HighlightSpacing::Always);
+        frame.render_stateful_widget(list, received_area, &mut self.friend_req_list_state);
+
+        let items: Vec<ListItem> = self
+            .friend_req_holder
+            .sent_reqs
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|sent_req| ListItem::new(Text::from(sent_req)))
+            .collect();
+        let list = List::new(items).block(
+            Block::new()
+                .title("Sent Requests")
+                .title_alignment(Alignment::Center)
+                .borders(Borders::ALL)
+                .border_set(symbols::border::ROUNDED),
+        );
+        frame.render_widget(list, sent_area);
     }
     fn render_friend_search_res(&mut self, frame: &mut Frame, friend_area: Rect, block: Block) {
         let items: Vec<ListItem> = self
@@ -234,16 +646,49 @@ impl Component for Contact {
                         self.friend_list_state.select(None);
                         self.change_state(State::FriendReq)
                     }
+                    KeyCode::Char('g') => {
+                        self.clean_create_group();
+                        self.change_state(State::CreateGroup)
+                    }
+                    KeyCode::Char('x') => {
+                        if let Some(friend) = self.selected_friend() {
+                            return Ok(Some(Action::Alert(
+                                format!("确定删除好友{}么？", friend.name),
+                                Some(ConfirmEvent::RemoveFriend(friend.id)),
+                            )));
+                        }
+                    }
+                    KeyCode::Char('b') => {
+                        if let Some(friend) = self.selected_friend() {
+                            return Ok(Some(Action::Alert(
+                                format!(
+                                    "确定屏蔽{}么？屏蔽后将不再显示其消息和好友请求",
+                                    friend.name
+                                ),
+                                Some(ConfirmEvent::BlockUser(friend.id)),
+                            )));
+                        }
+                    }
+                    KeyCode::Char('n') => self.edit_remark(),
+                    KeyCode::Char('t') => self.edit_tags(),
+                    KeyCode::Char('i') => {
+                        if let Some(friend) = self.selected_friend() {
+                            // 公钥变更时可在核对新指纹后按 Enter 信任
+                            let confirm = E2E
+                                .key_changed(friend.id)
+                                .then_some(ConfirmEvent::TrustPeerKey(friend.id));
+                            return Ok(Some(Action::Alert(friend_profile(&friend), confirm)));
+                        }
+                    }
                     KeyCode::Enter => {
-                        if let Some(idx) = self.friend_list_state.selected() {
-                            if let Some(friend) =
-                                self.friends_holder.friends.lock().unwrap().get(idx)
-                            {
-                                return Ok(Some(Action::ToChat(ToChat::User(
-                                    friend.id,
-                                    friend.name.clone(),
-                                ))));
-                            }
+                        if self.toggle_selected_tag() {
+                            return Ok(None);
+                        }
+                        if let Some(friend) = self.selected_friend() {
+                            return Ok(Some(Action::ToChat(ToChat::User(
+                                friend.id,
+                                friend.name.clone(),
+                            ))));
                         }
                     }
                     _ => {}
@@ -271,12 +716,14 @@ impl Component for Contact {
                         if let Some(idx) = self.search_list_state.selected() {
                             let friend =
                                 self.search_result.lock().unwrap().get(idx).unwrap().clone();
-                            let uid = friend.id;
-                            let name = friend.name;
-                            return Ok(Some(Action::Alert(
-                                format!("要添加{name}为好友么？"),
-                                Some(ConfirmEvent::AddFriend(uid)),
-                            )));
+                            if friend.is_friend {
+                                return Ok(Some(Action::Alert(
+                                    format!("{}已经是您的好友", friend.name),
+                                    None,
+                                )));
+                            }
+                            self.pending_friend = Some(friend);
+                            self.change_state(State::Greeting);
                         }
                     }
                     KeyCode::Esc => {
@@ -285,6 +732,73 @@ impl Component for Contact {
                     }
                     _ => {}
                 },
+                State::CreateGroup => match key.code {
+                    KeyCode::Up => self.friend_list_state.select_previous(),
+                    KeyCode::Down => self.friend_list_state.select_next(),
+                    KeyCode::Char(' ') => self.toggle_group_member(),
+                    KeyCode::Enter => {
+                        if self.group_members.is_empty() {
+                            return Ok(Some(Action::Alert("请至少选择一位好友".to_string(), None)));
+                        }
+                        self.change_state(State::GroupName)
+                    }
+                    KeyCode::Esc => {
+                        self.clean_create_group();
+                        self.change_state(State::Friends)
+                    }
+                    _ => {}
+                },
+                State::Greeting => match key.code {
+                    KeyCode::Enter => {
+                        self.greeting_input.submit_message();
+                        let reason = self
+                            .greeting_input
+                            .data()
+                            .filter(|reason| !reason.trim().is_empty());
+                        if let Some(friend) = &self.pending_friend {
+                            return Ok(Some(Action::Alert(
+                                format!("要添加{}为好友么？", friend.name),
+                                Some(ConfirmEvent::AddFriend(friend.id, reason)),
+                            )));
+                        }
+                    }
+                    KeyCode::Char(to_insert) => self.greeting_input.enter_char(to_insert),
+                    KeyCode::Backspace => self.greeting_input.delete_char(),
+                    KeyCode::Left => self.greeting_input.move_cursor_left(),
+                    KeyCode::Right => self.greeting_input.move_cursor_right(),
+                    KeyCode::Esc => {
+                        self.close_greeting();
+                        self.change_state(State::AddFriend)
+                    }
+                    _ => {}
+                },
+                State::Remark => match key.code {
+                    KeyCode::Enter => return Ok(self.save_remark()),
+                    KeyCode::Char(to_insert) => self.remark_input.enter_char(to_insert),
+                    KeyCode::Backspace => self.remark_input.delete_char(),
+                    KeyCode::Left => self.remark_input.move_cursor_left(),
+                    KeyCode::Right => self.remark_input.move_cursor_right(),
+                    KeyCode::Esc => self.close_edit(),
+                    _ => {}
+                },
+                State::Tags => match key.code {
+                    KeyCode::Enter => return Ok(self.save_remark()),
+                    KeyCode::Char(to_insert) => self.tags_input.enter_char(to_insert),
+                    KeyCode::Backspace => self.tags_input.delete_char(),
+                    KeyCode::Left => self.tags_input.move_cursor_left(),
+                    KeyCode::Right => self.tags_input.move_cursor_right(),
+                    KeyCode::Esc => self.close_edit(),
+                    _ => {}
+                },
+                State::GroupName => match key.code {
+                    KeyCode::Enter => return Ok(self.create_group()),
+                    KeyCode::Char(to_insert) => self.group_name_input.enter_char(to_insert),
+                    KeyCode::Backspace => self.group_name_input.delete_char(),
+                    KeyCode::Left => self.group_name_input.move_cursor_left(),
+                    KeyCode::Right => self.group_name_input.move_cursor_right(),
+                    KeyCode::Esc => self.change_state(State::CreateGroup),
+                    _ => {}
+                },
                 State::FriendReq => match key.code {
                     KeyCode::Char('e') => {
                         self.change_state(State::Search);
@@ -294,18 +808,21 @@ impl Component for Contact {
                     KeyCode::Down => self.friend_req_list_state.select_next(),
                     KeyCode::Left => {
                         self.friend_req_list_state.select(None);
+                        self.selected_reqs.clear();
                         self.change_state(State::Friends)
                     }
+                    KeyCode::Char(' ') => self.toggle_selected_req(),
+                    KeyCode::Char('a') => return Ok(self.review_selected_reqs(true)),
+                    KeyCode::Char('r') => return Ok(self.review_selected_reqs(false)),
                     KeyCode::Enter => {
-                        if let Some(idx) = self.friend_req_list_state.selected() {
-                            if let Some(friend_req) =
+                        if let Some(idx) = self.friend_req_list_state.selected()
+                            && let Some(friend_req) =
                                 self.friend_req_holder.friend_reqs.lock().unwrap().get(idx)
-                            {
-                                return Ok(Some(Action::Alert(
-                                    format!("接受{}的好友请求么？", friend_req.request_name),
-                                    Some(ConfirmEvent::ConfirmFriendReq(None)),
-                                )));
-                            }
+                        {
+                            return Ok(Some(Action::Alert(
+                                format!("接受{}的好友请求么？", friend_req.request_name),
+                                Some(ConfirmEvent::ConfirmFriendReq(None)),
+                            )));
                         }
                     }
                     _ => {}
@@ -316,10 +833,22 @@ impl Component for Contact {
     }
 
     fn update(&mut self, action: Action) -> color_eyre::Result<Option<Action>> {
+        if action == Action::Logout {
+            *self = Contact::new(self.mode_holder.clone());
+            return Ok(None);
+        }
         if self.mode_holder.get_mode() == Mode::Contact && self.friends_holder.need_fetch {
             self.friends_holder.need_fetch = false;
             match friend::friends() {
                 Ok(friends) => {
+                    friends
+                        .iter()
+                        .for_each(|f| USER_DIRECTORY.seed(f.id, f.name.clone()));
+                    REMARKS.sync(&friends);
+                    let uids = friends.iter().map(|f| f.id).collect();
+                    if let Err(err) = presence::fetch(uids) {
+                        error!("Failed to get presence of friends: {}", err);
+                    }
                     self.friends_holder.friends = Arc::new(Mutex::new(friends));
                 }
                 Err(err) => {
@@ -328,45 +857,43 @@ impl Component for Contact {
             };
         }
         if self.mode_holder.get_mode() == Mode::Contact && self.friend_req_holder.need_fetch {
-            self.friend_req_holder.need_fetch = false;
-            match friend::friend_reqs() {
-                Ok(mut friend_reqs) => {
-                    friend_reqs.sort_by_key(|f| f.create_time);
-                    self.friend_req_holder.friend_reqs = Arc::new(Mutex::new(friend_reqs));
-                }
-                Err(err) => {
-                    error!("Failed to get friend reqs: {}", err);
-                }
-            };
+            self.friend_req_holder.fetch();
         }
         match action {
-            Action::Confirm(ConfirmEvent::AddFriend(friend_uid)) => {
+            // 登录后获取好友请求，更新导航栏角标
+            Action::LoginSuccess => {
+                // 切换账号后重新获取好友列表
+                self.friends_holder.need_fetch = true;
+                self.friend_req_holder.fetch();
+            }
+            Action::FriendRequest(_) => self.friend_req_holder.need_fetch = true,
+            Action::Confirm(ConfirmEvent::AddFriend(friend_uid, reason)) => {
                 let uid = CURRENT_USER.get_user().user.unwrap().id;
-                if let Err(e) = friend::add_friend(uid, friend_uid) {
+                if let Err(e) = friend::add_friend(uid, friend_uid, reason) {
                     return Ok(Some(Action::Alert(e.to_string(), None)));
                 }
+                self.close_greeting();
+                self.friend_req_holder.need_fetch = true;
                 self.clean_search();
                 self.change_state(State::Friends);
                 self.search_list_state.select(None);
             }
             Action::Confirm(ConfirmEvent::ConfirmFriendReq(opt)) => {
-                if let Some(b) = opt {
-                    if let Some(idx) = self.friend_req_list_state.selected() {
-                        if let Some(friend_req) =
-                            self.friend_req_holder.friend_reqs.lock().unwrap().get(idx)
-                        {
-                            match friend::review_friend_req(
-                                friend_req.id,
-                                if b {
-                                    FriendRequestStatus::APPROVE
-                                } else {
-                                    FriendRequestStatus::REJECT
-                                },
-                            ) {
-                                Ok(_) => {}
-                                Err(e) => error!("Failed to review friend req: {}", e),
-                            }
-                        }
+                if let Some(b) = opt
+                    && let Some(idx) = self.friend_req_list_state.selected()
+                    && let Some(friend_req) =
+                        self.friend_req_holder.friend_reqs.lock().unwrap().get(idx)
+                {
+                    match friend::review_friend_req(
+                        friend_req.id,
+                        if b {
+                            FriendRequestStatus::APPROVE
+                        } else {
+                            FriendRequestStatus::REJECT
+                        },
+                    ) {
+                        Ok(_) => {}
+                        Err(e) => error!("Failed to review friend req: {}", e),
                     }
                 }
                 self.friend_req_list_state.select(None);
@@ -374,6 +901,57 @@ impl Component for Contact {
                 self.friends_holder.need_fetch = true;
                 self.change_state(State::Friends)
             }
+            Action::Confirm(ConfirmEvent::RemoveFriend(friend_uid)) => {
+                if let Err(e) = friend::delete_friend(friend_uid) {
+                    error!("Failed to delete friend: {}", e);
+                    return Ok(Some(Action::Alert(e.to_string(), None)));
+                }
+                self.friend_list_state.select(None);
+                self.friends_holder.need_fetch = true;
+            }
+            Action::Confirm(ConfirmEvent::BlockUser(uid)) => {
+                if let Err(e) = block::block(uid) {
+                    error!("Failed to block user: {}", e);
+                    return Ok(Some(Action::Alert(e.to_string(), None)));
+                }
+                self.friend_list_state.select(None);
+                self.friend_req_holder.need_fetch = true;
+                return Ok(Some(Action::BlockListChanged));
+            }
+            Action::Confirm(ConfirmEvent::TrustPeerKey(uid)) => {
+                if let Err(e) = E2E.trust_changed_key(uid) {
+                    return Ok(Some(Action::Alert(e.to_string(), None)));
+                }
+                return Ok(Some(Action::Alert("已信任对方的新公钥".to_string(), None)));
+            }
+            Action::Confirm(ConfirmEvent::ReviewFriendReqs(ids, approve)) => {
+                let status = if approve {
+                    FriendRequestStatus::APPROVE
+                } else {
+                    FriendRequestStatus::REJECT
+                };
+                let failures = ids
+                    .into_iter()
+                    .filter_map(|id| {
+                        friend::review_friend_req(id, status.clone())
+                            .err()
+                            .map(|e| {
+                                error!("Failed to review friend req: {}", e);
+                                id
+                            })
+                    })
+                    .collect::<Vec<_>>();
+                self.selected_reqs.clear();
+                self.friend_req_list_state.select(None);
+                self.friend_req_holder.need_fetch = true;
+                self.friends_holder.need_fetch = true;
+                if !failures.is_empty() {
+                    return Ok(Some(Action::Alert(
+                        format!("{}个好友请求处理失败，请稍后重试", failures.len()),
+                        None,
+                    )));
+                }
+            }
             _ => {}
         }
         Ok(None)
@@ -384,7 +962,7 @@ impl Component for Contact {
             let area = area_util::contact_area(area);
             let [search_area, remain_area] =
                 Layout::vertical([Constraint::Length(3), Constraint::Min(0)]).areas(area);
-            let [friend_area, friend_req_area] = if self.friend_req_holder.has_new_friend_reqs() {
+            let [friend_area, friend_req_area] = if self.friend_req_holder.should_show() {
                 Layout::horizontal([Constraint::Percentage(50), Constraint::Percentage(50)])
                     .areas(remain_area)
             } else {
@@ -393,19 +971,32 @@ impl Component for Contact {
             };
 
             let list_block = Block::new()
-                .title("↑↓ To Switch, Enter to select friend.")
+                .title(match self.state {
+                    State::CreateGroup | State::GroupName => {
+                        "↑↓ To Switch, Space To Select Member, Enter To Name The Group."
+                    }
+                    _ => {
+                        "↑↓ To Switch, Enter: Chat/Fold, i: Profile, g: Create Group, n: Remark, t: Tags, x: Remove, b: Block"
+                    }
+                })
                 .title_alignment(Alignment::Center)
                 .borders(Borders::ALL)
                 .border_set(symbols::border::ROUNDED);
             let search_block = Block::new()
-                .title(self.user_input.input_data.label())
                 .title_alignment(Alignment::Center)
                 .borders(Borders::ALL)
                 .border_set(symbols::border::ROUNDED);
-            let user_input =
-                Paragraph::new(self.user_input.input.clone().unwrap_or("".to_string()))
-                    .style(self.user_input.select_style())
-                    .block(search_block);
+            let input = match self.state {
+                State::CreateGroup | State::GroupName => &self.group_name_input,
+                State::Greeting => &self.greeting_input,
+                State::Remark => &self.remark_input,
+                State::Tags => &self.tags_input,
+                _ => &self.user_input,
+            };
+            let search_block = search_block.title(input.input_data.label());
+            let user_input = Paragraph::new(input.input.clone().unwrap_or("".to_string()))
+                .style(input.select_style())
+                .block(search_block);
             frame.render_widget(user_input, search_area);
             match self.state {
                 State::Friends => {
@@ -416,10 +1007,31 @@ impl Component for Contact {
                     self.user_input.set_cursor_position(search_area);
                     self.render_friend_search_res(frame, remain_area, list_block);
                 }
+                State::Greeting => {
+                    self.greeting_input.set_cursor_position(search_area);
+                    self.render_friend_search_res(frame, remain_area, list_block);
+                }
                 State::FriendReq => {
                     self.render_friends(frame, friend_area, list_block.clone());
                     self.render_friend_reqs(frame, friend_req_area, list_block);
                 }
+                State::Remark => {
+                    self.remark_input.set_cursor_position(search_area);
+                    self.render_friends(frame, friend_area, list_block.clone());
+                    self.render_friend_reqs(frame, friend_req_area, list_block);
+                }
+                State::Tags => {
+                    self.tags_input.set_cursor_position(search_area);
+                    self.render_friends(frame, friend_area, list_block.clone());
+                    self.render_friend_reqs(frame, friend_req_area, list_block);
+                }
+                State::CreateGroup => {
+                    self.render_friends(frame, remain_area, list_block);
+                }
+                State::GroupName => {
+                    self.group_name_input.set_cursor_position(search_area);
+                    self.render_friends(frame, remain_area, list_block);
+                }
             }
         }
         Ok(())
@@ -428,13 +1040,58 @@ impl Component for Contact {
 
 impl From<&Friend> for Text<'_> {
     fn from(friend: &Friend) -> Self {
-        Line::from(Span::styled(
-            format!("好友: {}", friend.name),
-            Style::default().fg(Color::White),
-        ))
-        .into()
+        friend_line(friend).into()
+    }
+}
+
+/// 好友资料，包含双方的公钥指纹，可与对方当面核对
+fn friend_profile(friend: &Friend) -> String {
+    let mut lines = vec![format!("用户名：{}", friend.name)];
+    if let Some(remark) = &friend.remark {
+        lines.push(format!("备注：{remark}"));
+    }
+    if !friend.tags.is_empty() {
+        lines.push(format!("标签：{}", friend.tags.join("，")));
+    }
+    lines.push(format!("状态：{}", PRESENCES.get(friend.id).describe()));
+    let encryption = if E2E.is_enabled(friend.id) {
+        "已开启"
+    } else {
+        "未开启"
+    };
+    lines.push(format!("端到端加密：{encryption}"));
+    lines.push(format!(
+        "对方指纹：{}",
+        E2E.peer_fingerprint(friend.id)
+            .unwrap_or_else(|| "未发布".to_string())
+    ));
+    if let Some(changed) = E2E.changed_fingerprint(friend.id) {
+        lines.push(format!("对方新指纹：{changed}"));
+        lines.push("⚠ 对方的公钥与首次记录的不一致，可能被冒充，已停止加密".to_string());
+        lines.push("与对方核对新指纹无误后，按 Enter 信任新公钥".to_string());
+    }
+    if let Some(own) = E2E.own_fingerprint() {
+        lines.push(format!("我的指纹：{own}"));
     }
+    lines.join("\n")
 }
+
+fn friend_line(friend: &Friend) -> Line<'static> {
+    let status = PRESENCES.get(friend.id);
+    let name = match &friend.remark {
+        Some(remark) => format!("好友: {remark} ({})", friend.name),
+        None => format!("好友: {}", friend.name),
+    };
+    Line::from(vec![
+        status.dot(),
+        Span::styled(name, Style::default().fg(Color::White)),
+        Span::styled(
+            format!(" [{}]", status.describe()),
+            Style::default().fg(status.presence.color()),
+        ),
+    ])
+}
+
 impl From<&FriendReq> for Text<'_> {
     fn from(friend_req: &FriendReq) -> Self {
         let line = Line::from(Span::styled(
@@ -453,6 +1110,86 @@ impl From<&FriendReq> for Text<'_> {
             format!("时间：{}", friend_req.create_time),
             Style::default().fg(Color::White),
         ));
-        Text::from(vec![line, line1, line2])
+        let mut text = Text::from(vec![line, line1, line2]);
+        if let Some(reason) = &friend_req.reason {
+            text.push_line(Line::from(Span::styled(
+                format!("验证消息：{reason}"),
+                Style::default().fg(Color::Cyan),
+            )));
+        }
+        text
+    }
+}
+
+impl From<&SentFriendReq> for Text<'_> {
+    fn from(sent_req: &SentFriendReq) -> Self {
+        Line::from(vec![
+            Span::styled(
+                format!("请求添加{}为好友 ", sent_req.target_name),
+                Style::default().fg(Color::White),
+            ),
+            Span::styled(
+                format!("{}", sent_req.status),
+                match sent_req.status {
+                    FriendRequestStatus::WAIT => Style::default().fg(Color::Yellow),
+                    FriendRequestStatus::APPROVE => Style::default().fg(Color::Green),
+                    FriendRequestStatus::REJECT => Style::default().fg(Color::Red),
+                },
+            ),
+        ])
+        .into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn friend(id: i32, tags: &[&str]) -> Friend {
+        Friend {
+            id,
+            name: format!("user{id}"),
+            remark: None,
+            tags: tags.iter().map(|t| t.to_string()).collect(),
+        }
+    }
+
+    fn ids(rows: &[FriendRow]) -> Vec<String> {
+        rows.iter()
+            .map(|row| match row {
+                FriendRow::Tag { name, count, .. } => format!("{name}:{count}"),
+                FriendRow::Friend(f) => f.id.to_string(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn friend_rows_without_tags_is_flat() {
+        let friends = vec![friend(1, &[]), friend(2, &[])];
+        assert_eq!(ids(&friend_rows(&friends, &HashSet::new())), ["1", "2"]);
+    }
+
+    #[test]
+    fn friend_rows_group_by_tags() {
+        let friends = vec![
+            friend(1, &["同事"]),
+            friend(2, &[]),
+            friend(3, &["家人", "同事"]),
+        ];
+        assert_eq!(
+            ids(&friend_rows(&friends, &HashSet::new())),
+            ["同事:2", "1", "3", "家人:1", "3", "未分组:1", "2"]
+        );
+        let collapsed = HashSet::from(["同事".to_string()]);
+        assert_eq!(
+            ids(&friend_rows(&friends, &collapsed)),
+            ["同事:2", "家人:1", "3", "未分组:1", "2"]
+        );
+    }
+
+    #[test]
+    fn parse_tags_trims_and_dedups() {
+        assert_eq!(parse_tags(" 同事, 家人，同事,, "), ["同事", "家人"]);
+        assert!(parse_tags("").is_empty());
     }
 }
@@ -0,0 +1,219 @@
+use crate::components::recent_chat::SELECTED_STYLE;
+use crate::config::get_data_dir;
+use crossterm::event::{KeyCode, KeyEvent};
+use emojis::{Emoji, Group};
+use ratatui::layout::{Alignment, Constraint, Layout, Rect};
+use ratatui::style::{Color, Style, Stylize};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{
+    Block, Borders, Clear, HighlightSpacing, List, ListItem, ListState, Paragraph, Tabs,
+};
+use ratatui::{Frame, symbols};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{LazyLock, Mutex};
+use tracing::warn;
+
+// 最近使用的表情，保存在数据目录
+pub(crate) static RECENT_EMOJI: LazyLock<RecentEmoji> =
+    LazyLock::new(|| RecentEmoji(Mutex::new(load_recent())));
+
+/// 最近使用的表情最多保存的个数
+const RECENT_LIMIT: usize = 24;
+
+pub(crate) struct RecentEmoji(Mutex<Vec<String>>);
+
+impl RecentEmoji {
+    pub(crate) fn list(&self) -> Vec<&'static Emoji> {
+        self.0
+            .lock()
+            .unwrap()
+            .iter()
+            .filter_map(|emoji| emojis::get(emoji))
+            .collect()
+    }
+
+    /// 记录使用的表情，最近使用的排在最前
+    pub(crate) fn record(&self, emoji: &str) {
+        let mut recent = self.0.lock().unwrap();
+        recent.retain(|e| e != emoji);
+        recent.insert(0, emoji.to_string());
+        recent.truncate(RECENT_LIMIT);
+        save_recent(&recent);
+    }
+}
+
+fn recent_path() -> PathBuf {
+    get_data_dir().join("emoji-recent.json")
+}
+
+fn load_recent() -> Vec<String> {
+    fs::read_to_string(recent_path())
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn save_recent(recent: &[String]) {
+    let path = recent_path();
+    let content = serde_json::to_string(recent).unwrap();
+    if let Err(err) = fs::create_dir_all(get_data_dir()).and_then(|_| fs::write(&path, content)) {
+        warn!("fail to save recent emoji {}: {err}", path.display());
+    }
+}
+
+fn group_label(group: Group) -> &'static str {
+    match group {
+        Group::SmileysAndEmotion => "笑脸",
+        Group::PeopleAndBody => "人物",
+        Group::AnimalsAndNature => "自然",
+        Group::FoodAndDrink => "食物",
+        Group::TravelAndPlaces => "旅行",
+        Group::Activities => "活动",
+        Group::Objects => "物品",
+        Group::Symbols => "符号",
+        Group::Flags => "旗帜",
+    }
+}
+
+/// 按键处理结果
+pub(crate) enum EmojiPicked {
+    None,
+    Emoji(&'static str),
+    Cancel,
+}
+
+/// 表情选择弹窗，第一页为最近使用，输入文字按名称或短码搜索所有表情
+pub(crate) struct EmojiPicker {
+    /// 0 为最近使用，其余为表情分类
+    tab: usize,
+    query: String,
+    list_state: ListState,
+}
+
+impl EmojiPicker {
+    pub(crate) fn new() -> Self {
+        // 没有最近使用的表情时从第一个分类开始
+        let tab = if RECENT_EMOJI.list().is_empty() { 1 } else { 0 };
+        Self {
+            tab,
+            query: String::new(),
+            list_state: ListState::default().with_selected(Some(0)),
+        }
+    }
+
+    fn tabs() -> Vec<&'static str> {
+        std::iter::once("最近")
+            .chain(Group::iter().map(group_label))
+            .collect()
+    }
+
+    fn emojis(&self) -> Vec<&'static Emoji> {
+        if !self.query.is_empty() {
+            let query = self.query.to_lowercase();
+            return emojis::iter()
+                .filter(|emoji| {
+                    emoji.name().contains(&query)
+                        || emoji.shortcodes().any(|code| code.contains(&query))
+                })
+                .collect();
+        }
+        match self.tab {
+            0 => RECENT_EMOJI.list(),
+            tab => Group::iter()
+                .nth(tab - 1)
+                .map(|group| group.emojis().collect())
+                .unwrap_or_default(),
+        }
+    }
+
+    fn switch_tab(&mut self, next: bool) {
+        let len = Self::tabs().len();
+        self.tab = if next {
+            (self.tab + 1) % len
+        } else {
+            (self.tab + len - 1) % len
+        };
+        self.list_state.select(Some(0));
+    }
+
+    pub(crate) fn handle_key_event(&mut self, key: KeyEvent) -> EmojiPicked {
+        match key.code {
+            KeyCode::Up => self.list_state.select_previous(),
+            KeyCode::Down => self.list_state.select_next(),
+            KeyCode::Left => self.switch_tab(false),
+            KeyCode::Right => self.switch_tab(true),
+            KeyCode::Enter => {
+                let emojis = self.emojis();
+                if let Some(emoji) = self.list_state.selected().and_then(|i| emojis.get(i)) {
+                    return EmojiPicked::Emoji(emoji.as_str());
+                }
+            }
+            KeyCode::Char(c) => {
+                self.query.push(c);
+                self.list_state.select(Some(0));
+            }
+            KeyCode::Backspace => {
+                self.query.pop();
+                self.list_state.select(Some(0));
+            }
+            KeyCode::Esc => return EmojiPicked::Cancel,
+            _ => {}
+        }
+        EmojiPicked::None
+    }
+
+    pub(crate) fn draw(&mut self, frame: &mut Frame, area: Rect) {
+        frame.render_widget(Clear, area);
+        let block = Block::new()
+            .title("表情")
+            .title_bottom(
+                Line::from("←→: Category, ↑↓: Select, Type To Search, Enter: Insert, Esc: Close")
+                    .centered(),
+            )
+            .title_alignment(Alignment::Center)
+            .borders(Borders::ALL)
+            .border_set(symbols::border::ROUNDED);
+        let inner = block.inner(area);
+        frame.render_widget(block, area);
+        let [tabs_area, search_area, list_area] = Layout::vertical([
+            Constraint::Length(1),
+            Constraint::Length(1),
+            Constraint::Fill(1),
+        ])
+        .areas(inner);
+
+        let tabs = Tabs::new(Self::tabs())
+            .select(self.tab)
+            .highlight_style(Style::default().fg(Color::Yellow).bold());
+        frame.render_widget(tabs, tabs_area);
+        let search = if self.query.is_empty() {
+            Line::from("搜索: ".fg(Color::DarkGray))
+        } else {
+            Line::from(vec![
+                "搜索: ".fg(Color::DarkGray),
+                Span::raw(self.query.clone()),
+            ])
+        };
+        frame.render_widget(Paragraph::new(search), search_area);
+
+        let items = self
+            .emojis()
+            .into_iter()
+            .map(|emoji| {
+                let mut spans = vec![
+                    Span::raw(format!("{}  ", emoji.as_str())),
+                    Span::raw(emoji.name()),
+                ];
+                if let Some(code) = emoji.shortcode() {
+                    spans.push(format!("  :{code}:").fg(Color::DarkGray));
+                }
+                ListItem::new(Line::from(spans))
+            })
+            .collect::<Vec<_>>();
+        let list = List::new(items)
+            .highlight_style(SELECTED_STYLE)
+            .highlight_spacing(HighlightSpacing::Always);
+        frame.render_stateful_widget(list, list_area, &mut self.list_state);
+    }
+}
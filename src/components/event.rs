@@ -1,34 +1,66 @@
 use crate::action::Action;
 use crate::components::Component;
 use crate::datetime::datetime_format;
+use crate::e2e;
+use crate::e2e::E2E;
+use crate::proxy;
 use crate::proxy::HOST;
+use crate::proxy::block::BLOCKED;
+use crate::proxy::friend::PENDING_FRIEND_REQS;
+use crate::proxy::presence::{PRESENCES, PresenceMessage};
 use crate::token::CURRENT_USER;
 use chrono::{DateTime, Local};
 use futures::StreamExt;
 use ratatui::Frame;
 use ratatui::layout::Rect;
-use reqwest::{Client, StatusCode};
+use reqwest::StatusCode;
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::atomic::Ordering;
 use std::sync::{Arc, Mutex};
 use std::time::Duration;
+use tokio::sync::Notify;
 use tokio::sync::broadcast::Sender;
+use tokio::sync::mpsc::UnboundedSender;
 use tokio::time::sleep;
 use tracing::{error, warn};
 
 pub(crate) struct Event {
     chat_tx: Sender<ChatMessage>,
     fetch: Arc<Mutex<Fetch>>,
+    // 非聊天消息通过 action 分发给各组件
+    action_tx: Arc<Mutex<Option<UnboundedSender<Action>>>>,
+    // 切换账号时通知正在读取的事件流结束
+    restart: Arc<Notify>,
 }
 
 #[derive(Default)]
 struct Fetch {
     need: bool,
+    /// 每次登录或切换账号时递增，用于结束上一个账号的事件流
+    generation: u64,
 }
 
 impl Component for Event {
+    fn register_action_handler(&mut self, tx: UnboundedSender<Action>) -> color_eyre::Result<()> {
+        *self.action_tx.lock().unwrap() = Some(tx);
+        Ok(())
+    }
+
     fn update(&mut self, action: Action) -> color_eyre::Result<Option<Action>> {
         if action == Action::LoginSuccess {
-            self.fetch.lock().unwrap().need = true;
+            let mut fetch = self.fetch.lock().unwrap();
+            fetch.need = true;
+            fetch.generation += 1;
+            self.restart.notify_one();
+        }
+        if action == Action::Logout {
+            // 结束当前事件流，等待下次登录
+            let mut fetch = self.fetch.lock().unwrap();
+            fetch.need = false;
+            fetch.generation += 1;
+            self.restart.notify_one();
         }
         Ok(None)
     }
@@ -43,55 +75,131 @@ impl Event {
         Self {
             chat_tx,
             fetch: Arc::new(Mutex::new(Fetch::default())),
+            action_tx: Arc::new(Mutex::new(None)),
+            restart: Arc::new(Notify::new()),
         }
     }
 
     pub(crate) async fn run(&self) {
         let arc = self.fetch.clone();
         let sender = self.chat_tx.clone();
+        let action_tx = self.action_tx.clone();
+        let restart = self.restart.clone();
         tokio::task::spawn(async move {
-            // 检查是否可以开始fetch消息
-            check_need_fetch(arc).await;
-            let url = format!("{}/event/stream", HOST.as_str());
-            let token = CURRENT_USER.get_user().token.clone().unwrap();
-            let res = Client::new()
-                .get(url)
-                .header("Authorization", format!("Bearer {token}"))
-                .header("User-Agent", "Chat-Tui")
-                .header("Accept", "application/event-stream")
-                .send()
-                .await;
-            match res {
-                Ok(res) => match res.status() {
-                    StatusCode::OK => {
-                        let mut stream = res.bytes_stream();
-                        while let Some(item) = stream.next().await {
-                            let bytes = item.unwrap();
-                            let cow = String::from_utf8_lossy(&bytes);
-                            if let Some(msg) = parse(cow.to_string()) {
-                                match msg {
-                                    Message::ChatMessage(chat_msg) => {
-                                        let _ = sender.send(chat_msg);
+            loop {
+                // 检查是否可以开始fetch消息
+                check_need_fetch(arc.clone()).await;
+                let generation = {
+                    let mut fetch = arc.lock().unwrap();
+                    fetch.need = false;
+                    fetch.generation
+                };
+                let url = format!("{}/event/stream", HOST.url());
+                let token = CURRENT_USER.get_user().token.clone().unwrap();
+                let res = proxy::async_client_for(HOST.profile().as_ref())
+                    .get(url)
+                    .header("Authorization", format!("Bearer {token}"))
+                    .header("User-Agent", "Chat-Tui")
+                    .header("Accept", "application/event-stream")
+                    .send()
+                    .await;
+                match res {
+                    Ok(res) => match res.status() {
+                        StatusCode::OK => {
+                            let mut stream = res.bytes_stream();
+                            loop {
+                                let bytes = tokio::select! {
+                                    item = stream.next() => match item {
+                                        Some(Ok(bytes)) => bytes,
+                                        _ => break,
+                                    },
+                                    _ = restart.notified() => {
+                                        // 已切换账号，结束当前事件流
+                                        if arc.lock().unwrap().generation != generation {
+                                            break;
+                                        }
+                                        continue;
+                                    }
+                                };
+                                let cow = String::from_utf8_lossy(&bytes);
+                                if let Some(msg) = parse(cow.to_string()) {
+                                    match msg {
+                                        // 屏蔽用户的消息、输入状态、表情回应和好友请求不展示
+                                        Message::ChatMessage(chat_msg)
+                                            if BLOCKED.contains(chat_msg.payload.from_uid) => {}
+                                        Message::Typing(typing)
+                                            if BLOCKED.contains(typing.from_uid) => {}
+                                        Message::Reaction(reaction)
+                                            if BLOCKED.contains(reaction.from_uid) => {}
+                                        Message::FriendRequest(req)
+                                            if BLOCKED.contains(req.from_uid) => {}
+                                        Message::ChatMessage(mut chat_msg) => {
+                                            decrypt(&mut chat_msg).await;
+                                            let _ = sender.send(chat_msg);
+                                        }
+                                        Message::Typing(typing) => {
+                                            dispatch(&action_tx, Action::Typing(typing));
+                                        }
+                                        Message::Reaction(reaction) => {
+                                            dispatch(&action_tx, Action::Reaction(reaction));
+                                        }
+                                        Message::Presence(PresenceMessage { uid, status }) => {
+                                            PRESENCES.set(uid, status);
+                                        }
+                                        Message::FriendRequest(req) => {
+                                            PENDING_FRIEND_REQS.fetch_add(1, Ordering::SeqCst);
+                                            dispatch(&action_tx, Action::FriendRequest(req));
+                                        }
+                                        Message::Heartbeat(_) => {}
                                     }
-                                    Message::Heartbeat(_) => {}
                                 }
                             }
                         }
+                        _ => {
+                            let text = res.text().await.unwrap();
+                            warn!("fail to fetch event stream: {text}");
+                        }
+                    },
+                    Err(e) => {
+                        error!("fail to get event stream: {}", e)
                     }
-                    _ => {
-                        let text = res.text().await.unwrap();
-                        warn!("fail to fetch event stream: {text}");
-                    }
-                },
-                Err(e) => {
-                    error!("fail to get event stream: {}", e)
                 }
             }
         });
     }
 }
 
-fn parse(sse: String) -> Option<Message> {
+/// 解密私聊消息，对方为消息的另一方
+async fn decrypt(chat_msg: &mut ChatMessage) {
+    let payload = &mut chat_msg.payload;
+    let MessageTarget::User(MessageTargetUser { uid }) = payload.target else {
+        return;
+    };
+    let Some(user) = CURRENT_USER.get_user().user else {
+        return;
+    };
+    let peer = if payload.from_uid == user.id {
+        uid
+    } else {
+        payload.from_uid
+    };
+    let content = payload.detail.get_content();
+    if !e2e::is_encrypted(&content) {
+        return;
+    }
+    E2E.prefetch_peer_key(peer).await;
+    payload
+        .detail
+        .set_content(E2E.decrypt_cached(peer, &content));
+}
+
+fn dispatch(action_tx: &Arc<Mutex<Option<UnboundedSender<Action>>>>, action: Action) {
+    if let Some(tx) = action_tx.lock().unwrap().as_ref() {
+        let _ = tx.send(action);
+    }
+}
+
+pub(crate) fn parse(sse: String) -> Option<Message> {
     sse.lines().find_map(|line| {
         if line.starts_with("data:") {
             let data_json = line.trim_start_matches("data:").trim();
@@ -126,6 +234,10 @@ pub struct ChatMessage {
 pub enum Message {
     ChatMessage(ChatMessage),
     Heartbeat(HeartbeatMessage),
+    Typing(TypingMessage),
+    Reaction(ReactionMessage),
+    Presence(PresenceMessage),
+    FriendRequest(FriendRequestMessage),
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -134,6 +246,42 @@ pub struct HeartbeatMessage {
     time: DateTime<Local>,
 }
 
+/// Typing message, sent while someone is composing a message
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TypingMessage {
+    /// Who is typing
+    pub from_uid: i32,
+    /// Where the message is being typed
+    pub target: MessageTarget,
+    #[serde(with = "datetime_format")]
+    pub time: DateTime<Local>,
+}
+
+/// Reaction message, sent when someone adds or removes a reaction to a message
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReactionMessage {
+    /// Message id
+    pub mid: i64,
+    pub from_uid: i32,
+    pub emoji: String,
+    /// Whether the reaction is added or removed
+    pub added: bool,
+    #[serde(with = "datetime_format")]
+    pub time: DateTime<Local>,
+}
+
+/// Friend request message, sent when someone requests to add the current user
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FriendRequestMessage {
+    /// Friend request id
+    pub id: i32,
+    pub from_uid: i32,
+    pub from_name: String,
+    pub reason: Option<String>,
+    #[serde(with = "datetime_format")]
+    pub time: DateTime<Local>,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct ChatMessagePayload {
     /// Sender id
@@ -190,6 +338,20 @@ impl MessageDetail {
             MessageDetail::Replay(msg) => msg.content.content.clone(),
         }
     }
+
+    pub fn content_type(&self) -> Option<&str> {
+        match self {
+            MessageDetail::Normal(msg) => msg.content.content_type.as_deref(),
+            MessageDetail::Replay(msg) => msg.content.content_type.as_deref(),
+        }
+    }
+
+    pub fn set_content(&mut self, content: String) {
+        match self {
+            MessageDetail::Normal(msg) => msg.content.content = content,
+            MessageDetail::Replay(msg) => msg.content.content = content,
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -206,9 +368,11 @@ pub struct MessageReplay {
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct MessageContent {
     /// Extended attributes
-    // pub properties: Option<HashMap<String, Value>>,
-    /// Content type
-    // pub content_type: String,
+    #[serde(default)]
+    pub properties: Option<HashMap<String, Value>>,
+    /// Content type, 文本消息为空
+    #[serde(default)]
+    pub content_type: Option<String>,
     /// Content
     pub(crate) content: String,
 }
@@ -0,0 +1,131 @@
+use crate::components::recent_chat::SELECTED_STYLE;
+use crate::proxy::file::format_size;
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::layout::{Alignment, Rect};
+use ratatui::style::{Color, Stylize};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Clear, HighlightSpacing, List, ListItem, ListState};
+use ratatui::{Frame, symbols};
+use std::fs;
+use std::path::PathBuf;
+
+/// 选择要发送的文件，Enter 进入目录或选中文件，Backspace 返回上级目录
+pub(crate) struct FilePicker {
+    dir: PathBuf,
+    entries: Vec<Entry>,
+    list_state: ListState,
+}
+
+struct Entry {
+    name: String,
+    is_dir: bool,
+    size: u64,
+}
+
+/// 按键处理结果
+pub(crate) enum Picked {
+    None,
+    File(PathBuf),
+    Cancel,
+}
+
+impl FilePicker {
+    /// 从用户主目录开始浏览
+    pub(crate) fn new() -> Self {
+        let dir = directories::UserDirs::new()
+            .map(|dirs| dirs.home_dir().to_path_buf())
+            .or_else(|| std::env::current_dir().ok())
+            .unwrap_or_else(|| PathBuf::from("."));
+        let mut picker = Self {
+            dir,
+            entries: Vec::new(),
+            list_state: ListState::default(),
+        };
+        picker.load();
+        picker
+    }
+
+    /// 读取当前目录，目录在前，隐藏文件不显示
+    fn load(&mut self) {
+        self.entries = fs::read_dir(&self.dir)
+            .map(|read_dir| {
+                read_dir
+                    .flatten()
+                    .filter_map(|entry| {
+                        let name = entry.file_name().to_string_lossy().to_string();
+                        let meta = entry.metadata().ok()?;
+                        (!name.starts_with('.')).then(|| Entry {
+                            name,
+                            is_dir: meta.is_dir(),
+                            size: meta.len(),
+                        })
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        self.entries
+            .sort_by(|a, b| b.is_dir.cmp(&a.is_dir).then(a.name.cmp(&b.name)));
+        self.list_state
+            .select((!self.entries.is_empty()).then_some(0));
+    }
+
+    pub(crate) fn handle_key_event(&mut self, key: KeyEvent) -> Picked {
+        match key.code {
+            KeyCode::Up => self.list_state.select_previous(),
+            KeyCode::Down => self.list_state.select_next(),
+            KeyCode::Backspace => {
+                if let Some(parent) = self.dir.parent() {
+                    self.dir = parent.to_path_buf();
+                    self.load();
+                }
+            }
+            KeyCode::Enter => {
+                let Some(entry) = self.list_state.selected().and_then(|i| self.entries.get(i))
+                else {
+                    return Picked::None;
+                };
+                let path = self.dir.join(&entry.name);
+                if !entry.is_dir {
+                    return Picked::File(path);
+                }
+                self.dir = path;
+                self.load();
+            }
+            KeyCode::Esc => return Picked::Cancel,
+            _ => {}
+        }
+        Picked::None
+    }
+
+    pub(crate) fn draw(&mut self, frame: &mut Frame, area: Rect) {
+        let items = self
+            .entries
+            .iter()
+            .map(|entry| {
+                if entry.is_dir {
+                    ListItem::new(format!("📁 {}/", entry.name).fg(Color::Cyan))
+                } else {
+                    ListItem::new(Line::from(vec![
+                        Span::raw(format!("📄 {}", entry.name)),
+                        format!("  {}", format_size(entry.size)).fg(Color::DarkGray),
+                    ]))
+                }
+            })
+            .collect::<Vec<_>>();
+        frame.render_widget(Clear, area);
+        let block = Block::new()
+            .title(self.dir.display().to_string())
+            .title_bottom(
+                Line::from("↑↓: Select, Enter: Open/Send, Backspace: Parent, Esc: Cancel")
+                    .centered(),
+            )
+            .title_alignment(Alignment::Center)
+            .borders(Borders::ALL)
+            .border_set(symbols::border::ROUNDED);
+        let list = List::new(items)
+            .block(block)
+            .highlight_style(SELECTED_STYLE)
+            .highlight_spacing(HighlightSpacing::Always);
+        frame.render_stateful_widget(list, area, &mut self.list_state);
+    }
+}
@@ -0,0 +1,152 @@
+use crate::components::event::{MessageTarget, MessageTargetGroup, MessageTargetUser};
+use crate::components::recent_chat::{ChatVo, SELECTED_STYLE, fetch_recent_chats};
+use crate::proxy;
+use crate::proxy::friend;
+use crate::proxy::remark::REMARKS;
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::layout::{Alignment, Constraint, Layout, Rect};
+use ratatui::style::{Color, Stylize};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{
+    Block, Borders, Clear, HighlightSpacing, List, ListItem, ListState, Paragraph,
+};
+use ratatui::{Frame, symbols};
+
+/// 按键处理结果
+pub(crate) enum ForwardPicked {
+    None,
+    Target(MessageTarget, String),
+    Cancel,
+}
+
+struct Entry {
+    target: MessageTarget,
+    name: String,
+    /// 来自最近聊天，否则来自好友列表
+    recent: bool,
+}
+
+/// 选择转发目标，最近聊天在前，其后为不在最近聊天中的好友，输入文字按名称过滤
+pub(crate) struct ForwardPicker {
+    /// 转发的消息条数
+    count: usize,
+    entries: Vec<Entry>,
+    query: String,
+    list_state: ListState,
+}
+
+impl ForwardPicker {
+    pub(crate) fn new(count: usize) -> color_eyre::Result<Self> {
+        let mut entries = proxy::send_request(fetch_recent_chats)??
+            .into_iter()
+            .map(|chat_vo| match chat_vo {
+                ChatVo::User { uid, user_name, .. } => Entry {
+                    target: MessageTarget::User(MessageTargetUser { uid }),
+                    name: REMARKS.display_name(uid, &user_name),
+                    recent: true,
+                },
+                ChatVo::Group {
+                    gid, group_name, ..
+                } => Entry {
+                    target: MessageTarget::Group(MessageTargetGroup { gid }),
+                    name: group_name,
+                    recent: true,
+                },
+            })
+            .collect::<Vec<_>>();
+        for friend in friend::friends()? {
+            let target = MessageTarget::User(MessageTargetUser { uid: friend.id });
+            if entries.iter().all(|entry| entry.target != target) {
+                entries.push(Entry {
+                    target,
+                    name: REMARKS.display_name(friend.id, &friend.name),
+                    recent: false,
+                });
+            }
+        }
+        Ok(Self {
+            count,
+            entries,
+            query: String::new(),
+            list_state: ListState::default().with_selected(Some(0)),
+        })
+    }
+
+    fn filtered(&self) -> Vec<&Entry> {
+        let query = self.query.to_lowercase();
+        self.entries
+            .iter()
+            .filter(|entry| entry.name.to_lowercase().contains(&query))
+            .collect()
+    }
+
+    pub(crate) fn handle_key_event(&mut self, key: KeyEvent) -> ForwardPicked {
+        match key.code {
+            KeyCode::Up => self.list_state.select_previous(),
+            KeyCode::Down => self.list_state.select_next(),
+            KeyCode::Enter => {
+                if let Some(entry) = self
+                    .list_state
+                    .selected()
+                    .and_then(|i| self.filtered().get(i).copied())
+                {
+                    return ForwardPicked::Target(entry.target, entry.name.clone());
+                }
+            }
+            KeyCode::Char(c) => {
+                self.query.push(c);
+                self.list_state.select(Some(0));
+            }
+            KeyCode::Backspace => {
+                self.query.pop();
+                self.list_state.select(Some(0));
+            }
+            KeyCode::Esc => return ForwardPicked::Cancel,
+            _ => {}
+        }
+        ForwardPicked::None
+    }
+
+    pub(crate) fn draw(&mut self, frame: &mut Frame, area: Rect) {
+        frame.render_widget(Clear, area);
+        let block = Block::new()
+            .title(format!("转发 {} 条消息", self.count))
+            .title_bottom(
+                Line::from("↑↓: Select, Type To Search, Enter: Forward, Esc: Cancel").centered(),
+            )
+            .title_alignment(Alignment::Center)
+            .borders(Borders::ALL)
+            .border_set(symbols::border::ROUNDED);
+        let inner = block.inner(area);
+        frame.render_widget(block, area);
+        let [search_area, list_area] =
+            Layout::vertical([Constraint::Length(1), Constraint::Fill(1)]).areas(inner);
+        frame.render_widget(
+            Paragraph::new(Line::from(vec![
+                "搜索: ".fg(Color::DarkGray),
+                Span::raw(self.query.clone()),
+            ])),
+            search_area,
+        );
+
+        let items = self
+            .filtered()
+            .into_iter()
+            .map(|entry| {
+                let icon = match entry.target {
+                    MessageTarget::User(_) => "👤",
+                    MessageTarget::Group(_) => "👥",
+                };
+                let source = if entry.recent { "最近" } else { "好友" };
+                ListItem::new(Line::from(vec![
+                    Span::raw(format!("{icon} {}", entry.name)),
+                    format!("  {source}").fg(Color::DarkGray),
+                ]))
+            })
+            .collect::<Vec<_>>();
+        let list = List::new(items)
+            .highlight_style(SELECTED_STYLE)
+            .highlight_spacing(HighlightSpacing::Always);
+        frame.render_stateful_widget(list, list_area, &mut self.list_state);
+    }
+}
@@ -1,20 +1,32 @@
 use crate::action::{Action, ConfirmEvent};
 use crate::app::{Mode, ModeHolderLock};
-use crate::components::recent_chat::SELECTED_STYLE;
+use crate::components::chat::CHAT_VO;
+use crate::components::contact::ToChat;
+use crate::components::recent_chat::{ChatVo, SELECTED_STYLE};
 use crate::components::user_input::{InputData, UserInput};
 use crate::components::{Component, area_util};
+use crate::proxy::directory::USER_DIRECTORY;
 use crate::proxy::friend::Friend;
 use crate::proxy::group::{DetailRes, GroupUser};
-use crate::proxy::{friend, group};
+use crate::proxy::presence::PRESENCES;
+use crate::proxy::remark::REMARKS;
+use crate::proxy::user::UserDetail;
+use crate::proxy::{friend, group, presence, user};
 use crate::token::CURRENT_USER;
+use chrono::{Local, TimeDelta};
+use color_eyre::eyre::format_err;
 use crossterm::event::{KeyCode, KeyEvent};
 use ratatui::layout::{Alignment, Constraint, Layout, Rect};
 use ratatui::prelude::{Color, Line, Span, Style, Text};
-use ratatui::widgets::{Block, Borders, HighlightSpacing, List, ListItem, ListState, Paragraph};
+use ratatui::widgets::{
+    Block, Borders, Clear, HighlightSpacing, List, ListItem, ListState, Paragraph, Wrap,
+};
 use ratatui::{Frame, symbols};
 use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
 use std::sync::{Arc, Mutex};
-use strum::{Display, EnumIter, FromRepr};
+use std::time::{Duration, Instant};
+use strum::{Display, EnumIter, IntoEnumIterator};
 use tracing::error;
 
 pub(crate) struct GroupManager {
@@ -26,17 +38,189 @@ pub(crate) struct GroupManager {
     state: State,
     group_members_list_state: ListState,
     friends_list_state: ListState,
+    /// 群名称输入框
+    rename_input: UserInput,
+    /// 群公告输入框
+    announcement_input: UserInput,
+    /// 多选的群成员
+    selected_members: HashSet<i32>,
+    /// 等待选择禁言时长的成员
+    mute_targets: Vec<i32>,
+    mute_duration_state: ListState,
+    /// 自定义禁言时长输入框
+    custom_mute_input: UserInput,
+    /// 群成员过滤输入框
+    member_filter: UserInput,
+    member_sort: MemberSort,
+    /// 群成员详情缓存
+    member_profiles: Arc<Mutex<HashMap<i32, UserDetail>>>,
+    /// 选中成员变化的时间，停止移动一段时间后再加载成员详情
+    profile_at: Option<Instant>,
+    /// 邀请时按名称搜索用户，在后台请求
+    user_search: Arc<Mutex<UserSearch>>,
+    /// 输入变化的时间，停止输入一段时间后再搜索
+    search_at: Option<Instant>,
+    /// 多选的邀请对象
+    invite_selected: HashSet<i32>,
+}
+
+/// 停止输入或移动选择多久后再发起请求
+const DEBOUNCE: Duration = Duration::from_millis(300);
+
+#[derive(Default)]
+struct UserSearch {
+    /// 最近一次搜索的关键字
+    keyword: Option<String>,
+    /// 按关键字搜索到的用户
+    results: Vec<UserDetail>,
+}
+
+/// 邀请入群的候选用户，包括好友与搜索结果
+struct InviteCandidate {
+    id: i32,
+    name: String,
+    is_friend: bool,
+    /// 已经是群成员，不可选择
+    is_member: bool,
 }
 
 #[derive(Eq, PartialEq)]
 enum State {
     GroupDetail,
     InviteFriend,
+    Rename,
+    Announcement,
+    MuteDuration,
+    CustomMute,
+    FilterMember,
+}
+
+/// 群成员排序方式
+#[derive(Eq, PartialEq, Clone, Copy, Debug, Default, Display)]
+enum MemberSort {
+    #[default]
+    #[strum(to_string = "角色")]
+    Role,
+    #[strum(to_string = "名称")]
+    Name,
+    #[strum(to_string = "禁言")]
+    Mute,
+}
+
+impl MemberSort {
+    fn next(&self) -> Self {
+        match self {
+            MemberSort::Role => MemberSort::Name,
+            MemberSort::Name => MemberSort::Mute,
+            MemberSort::Mute => MemberSort::Role,
+        }
+    }
+}
+
+fn role_rank(gu: &GroupUser) -> u8 {
+    match GroupRole::from(gu) {
+        GroupRole::Owner => 0,
+        GroupRole::Admin => 1,
+        GroupRole::Member => 2,
+    }
+}
+
+/// 名称是否包含过滤关键字，不区分大小写
+fn name_matches(name: &str, filter: &str) -> bool {
+    name.to_lowercase().contains(&filter.trim().to_lowercase())
+}
+
+/// 按显示名称（好友备注优先）过滤群成员，并按指定方式排序，名称不区分大小写
+fn filter_members<F>(
+    members: &[GroupUser],
+    filter: &str,
+    sort: MemberSort,
+    display_name: F,
+) -> Vec<GroupUser>
+where
+    F: Fn(&GroupUser) -> String,
+{
+    let mut members = members
+        .iter()
+        .map(|gu| (display_name(gu), gu))
+        .filter(|(name, _)| name_matches(name, filter))
+        .map(|(name, gu)| (name.to_lowercase(), gu))
+        .collect::<Vec<_>>();
+    match sort {
+        MemberSort::Role => members.sort_by(|(a_name, a), (b_name, b)| {
+            role_rank(a)
+                .cmp(&role_rank(b))
+                .then_with(|| a_name.cmp(b_name))
+        }),
+        MemberSort::Name => members.sort_by(|(a_name, _), (b_name, _)| a_name.cmp(b_name)),
+        MemberSort::Mute => members.sort_by(|(a_name, a), (b_name, b)| {
+            b.forbid
+                .cmp(&a.forbid)
+                .then_with(|| role_rank(a).cmp(&role_rank(b)))
+                .then_with(|| a_name.cmp(b_name))
+        }),
+    }
+    members.into_iter().map(|(_, gu)| gu.clone()).collect()
 }
 
-#[derive(
-    Eq, PartialEq, Clone, Copy, Debug, Display, FromRepr, EnumIter, Serialize, Deserialize,
-)]
+/// 禁言时长
+#[derive(Eq, PartialEq, Clone, Copy, Debug, Display, EnumIter)]
+enum MuteDuration {
+    #[strum(to_string = "10分钟")]
+    TenMinutes,
+    #[strum(to_string = "1小时")]
+    OneHour,
+    #[strum(to_string = "1天")]
+    OneDay,
+    #[strum(to_string = "永久")]
+    Forever,
+    #[strum(to_string = "自定义")]
+    Custom,
+}
+
+impl MuteDuration {
+    fn duration(&self) -> Option<Duration> {
+        match self {
+            MuteDuration::TenMinutes => Some(Duration::from_secs(10 * 60)),
+            MuteDuration::OneHour => Some(Duration::from_secs(60 * 60)),
+            MuteDuration::OneDay => Some(Duration::from_secs(24 * 60 * 60)),
+            MuteDuration::Forever | MuteDuration::Custom => None,
+        }
+    }
+}
+
+/// 解析自定义禁言时长，如 30、30m、2h、1d，不带单位时按分钟计算
+fn parse_duration(input: &str) -> Option<Duration> {
+    let input = input.trim();
+    let (num, unit) = match input.char_indices().last()? {
+        (idx, c) if c.is_ascii_alphabetic() => (&input[..idx], c.to_ascii_lowercase()),
+        _ => (input, 'm'),
+    };
+    let num = num.trim().parse::<u64>().ok().filter(|num| *num > 0)?;
+    let secs = match unit {
+        'm' => 60,
+        'h' => 60 * 60,
+        'd' => 24 * 60 * 60,
+        _ => return None,
+    };
+    num.checked_mul(secs).map(Duration::from_secs)
+}
+
+/// 禁言剩余时间
+fn format_remaining(remaining: TimeDelta) -> String {
+    let minutes = remaining.num_minutes();
+    if minutes < 1 {
+        "不足1分钟".to_string()
+    } else if minutes < 60 {
+        format!("{minutes}分钟")
+    } else if minutes < 24 * 60 {
+        format!("{}小时{}分钟", minutes / 60, minutes % 60)
+    } else {
+        format!("{}天{}小时", minutes / (24 * 60), minutes % (24 * 60) / 60)
+    }
+}
+
+#[derive(Eq, PartialEq, Clone, Copy, Debug, Display, EnumIter, Serialize, Deserialize)]
 #[repr(u8)]
 pub(crate) enum ManageAction {
     #[strum(to_string = "移出群聊")]
@@ -47,19 +231,76 @@ pub(crate) enum ManageAction {
     UnForbid = 2,
     #[strum(to_string = "设为管理员")]
     SetManager = 3,
+    #[strum(to_string = "取消管理员")]
+    RevokeManager = 4,
+    #[strum(to_string = "转让群主👑")]
+    TransferOwner = 5,
 }
 
 impl ManageAction {
     pub(crate) fn handle(&self, gid: i32, uid: i32) -> color_eyre::Result<()> {
         match self {
             ManageAction::Evict => group::evict(gid, uid),
-            ManageAction::Forbid => group::forbid(gid, uid),
+            ManageAction::Forbid => group::forbid(gid, uid, None),
             ManageAction::UnForbid => group::un_forbid(gid, uid),
             ManageAction::SetManager => group::set_manager(gid, uid),
+            ManageAction::RevokeManager => group::revoke_manager(gid, uid),
+            ManageAction::TransferOwner => group::transfer_owner(gid, uid),
+        }
+    }
+}
+
+/// 群成员角色
+#[derive(Eq, PartialEq, Clone, Copy, Debug, EnumIter)]
+pub(crate) enum GroupRole {
+    Owner,
+    Admin,
+    Member,
+}
+
+impl From<&GroupUser> for GroupRole {
+    fn from(gu: &GroupUser) -> Self {
+        if gu.owner {
+            GroupRole::Owner
+        } else if gu.admin {
+            GroupRole::Admin
+        } else {
+            GroupRole::Member
         }
     }
 }
 
+/// 根据操作者与目标成员的角色，以及目标当前的禁言状态，计算可执行的管理操作
+///
+/// 群主可以管理所有成员，管理员只能管理普通成员，任何人都不能管理群主
+pub(crate) fn manage_actions(
+    actor: GroupRole,
+    target: GroupRole,
+    target_forbid: bool,
+) -> Vec<ManageAction> {
+    let forbid = if target_forbid {
+        ManageAction::UnForbid
+    } else {
+        ManageAction::Forbid
+    };
+    match (actor, target) {
+        (GroupRole::Owner, GroupRole::Member) => vec![
+            ManageAction::Evict,
+            forbid,
+            ManageAction::SetManager,
+            ManageAction::TransferOwner,
+        ],
+        (GroupRole::Owner, GroupRole::Admin) => vec![
+            ManageAction::Evict,
+            forbid,
+            ManageAction::RevokeManager,
+            ManageAction::TransferOwner,
+        ],
+        (GroupRole::Admin, GroupRole::Member) => vec![ManageAction::Evict, forbid],
+        _ => vec![],
+    }
+}
+
 impl From<ManageAction> for Text<'_> {
     fn from(action: ManageAction) -> Self {
         let span = Span::styled(format!(">: {action}"), Style::default().fg(Color::White));
@@ -81,33 +322,176 @@ impl Component for GroupManager {
                         self.fetch_friends();
                         self.group_members_list_state.select(None);
                     }
-                    KeyCode::Up => self.group_members_list_state.select_previous(),
-                    KeyCode::Down => self.group_members_list_state.select_next(),
-                    KeyCode::Enter => {
-                        // 非管理员，无效
+                    KeyCode::Up => {
+                        self.group_members_list_state.select_previous();
+                        self.profile_at = Some(Instant::now());
+                    }
+                    KeyCode::Down => {
+                        self.group_members_list_state.select_next();
+                        self.profile_at = Some(Instant::now());
+                    }
+                    KeyCode::Char(' ') => self.toggle_selected_member(),
+                    KeyCode::Char('/') => {
+                        self.state = State::FilterMember;
+                        self.member_filter.is_editing = true;
+                    }
+                    KeyCode::Char('o') => {
+                        self.member_sort = self.member_sort.next();
+                        self.group_members_list_state.select(None);
+                    }
+                    KeyCode::Char('c') => {
                         let current_uid = CURRENT_USER.get_user().user.unwrap().id;
-                        if self
-                            .detail
-                            .lock()
-                            .unwrap()
-                            .users
-                            .iter()
-                            .any(move |gu| gu.admin && gu.id == current_uid)
+                        if let Some(gu) = self.selected_member()
+                            && gu.id != current_uid
                         {
-                            if let Some(idx) = self.group_members_list_state.selected() {
-                                if let Some(user) = self.detail.lock().unwrap().users.get(idx) {
-                                    return Ok(Some(Action::Alert(
-                                        format!("你希望将{}:", user.name),
-                                        Some(ConfirmEvent::GroupManage(None)),
-                                    )));
-                                }
+                            self.group_members_list_state.select(None);
+                            return Ok(Some(Action::ToChat(ToChat::User(gu.id, gu.name))));
+                        }
+                    }
+                    KeyCode::Char('A') => {
+                        let current_uid = CURRENT_USER.get_user().user.unwrap().id;
+                        if let Some(gu) = self.selected_member()
+                            && gu.id != current_uid
+                        {
+                            if self
+                                .member_profiles
+                                .lock()
+                                .unwrap()
+                                .get(&gu.id)
+                                .is_some_and(|profile| profile.is_friend)
+                            {
+                                return Ok(Some(Action::Alert(
+                                    format!("{}已经是您的好友", gu.name),
+                                    None,
+                                )));
                             }
+                            return Ok(Some(Action::Alert(
+                                format!("确定添加{}为好友么？", gu.name),
+                                Some(ConfirmEvent::AddFriend(gu.id, None)),
+                            )));
+                        }
+                    }
+                    KeyCode::Char('x') => {
+                        if self.selected_members.is_empty() {
+                            return Ok(Some(Action::Alert(
+                                "请先使用空格选择成员".to_string(),
+                                None,
+                            )));
+                        }
+                        let uids = self.selected_members.iter().copied().collect::<Vec<_>>();
+                        return Ok(Some(Action::Alert(
+                            format!("确定将选中的{}名成员移出群聊么？", uids.len()),
+                            Some(ConfirmEvent::EvictMembers(uids)),
+                        )));
+                    }
+                    KeyCode::Char('f') => {
+                        if self.selected_members.is_empty() {
+                            return Ok(Some(Action::Alert(
+                                "请先使用空格选择成员".to_string(),
+                                None,
+                            )));
+                        }
+                        let uids = self.selected_members.iter().copied().collect();
+                        self.choose_mute_duration(uids);
+                    }
+                    KeyCode::Char('F') => {
+                        if !self.current_member().is_some_and(|gu| gu.admin || gu.owner) {
+                            return Ok(Some(Action::Alert(
+                                "您不是管理员，无法操作".to_string(),
+                                None,
+                            )));
+                        }
+                        let detail = self.detail.lock().unwrap();
+                        let msg = if detail.forbid_all {
+                            "确定关闭全员禁言么？"
                         } else {
+                            "确定开启全员禁言么？管理员不受影响"
+                        };
+                        return Ok(Some(Action::Alert(
+                            msg.to_string(),
+                            Some(ConfirmEvent::ForbidAll(detail.group_id, !detail.forbid_all)),
+                        )));
+                    }
+                    KeyCode::Char('l') => {
+                        let detail = self.detail.lock().unwrap();
+                        return Ok(Some(Action::Alert(
+                            format!("确定退出群聊{}么？", detail.name),
+                            Some(ConfirmEvent::LeaveGroup(detail.group_id)),
+                        )));
+                    }
+                    KeyCode::Char('d') => {
+                        if !self.current_member().is_some_and(|gu| gu.owner) {
+                            return Ok(Some(Action::Alert(
+                                "只有群主可以解散群聊".to_string(),
+                                None,
+                            )));
+                        }
+                        let detail = self.detail.lock().unwrap();
+                        return Ok(Some(Action::Alert(
+                            format!("确定解散群聊{}么？解散后无法恢复", detail.name),
+                            Some(ConfirmEvent::DissolveGroup(detail.group_id)),
+                        )));
+                    }
+                    KeyCode::Char('r') => {
+                        if !self.current_member().is_some_and(|gu| gu.admin || gu.owner) {
+                            return Ok(Some(Action::Alert(
+                                "您不是管理员，无法操作".to_string(),
+                                None,
+                            )));
+                        }
+                        self.group_members_list_state.select(None);
+                        self.state = State::Rename;
+                        self.rename_input.is_editing = true;
+                    }
+                    KeyCode::Char('a') => {
+                        if !self.current_member().is_some_and(|gu| gu.admin || gu.owner) {
+                            return Ok(Some(Action::Alert(
+                                "您不是管理员，无法操作".to_string(),
+                                None,
+                            )));
+                        }
+                        match group::announcement(self.gid.unwrap()) {
+                            Ok(Some(announcement)) => {
+                                self.announcement_input.set_input(announcement.content)
+                            }
+                            Ok(None) => {}
+                            Err(e) => error!("fail to fetch group announcement: {e}"),
+                        }
+                        self.group_members_list_state.select(None);
+                        self.state = State::Announcement;
+                        self.announcement_input.is_editing = true;
+                    }
+                    KeyCode::Enter => {
+                        let Some(actor) = self.current_member() else {
+                            return Ok(None);
+                        };
+                        if GroupRole::from(&actor) == GroupRole::Member {
                             return Ok(Some(Action::Alert(
                                 "您不是管理员，无法操作".to_string(),
                                 None,
                             )));
                         }
+                        if let Some(user) = self.selected_member() {
+                            let actions = if user.id == actor.id {
+                                vec![]
+                            } else {
+                                manage_actions(
+                                    GroupRole::from(&actor),
+                                    GroupRole::from(&user),
+                                    user.forbid,
+                                )
+                            };
+                            if actions.is_empty() {
+                                return Ok(Some(Action::Alert(
+                                    format!("您无权对{}进行操作", user.name),
+                                    None,
+                                )));
+                            }
+                            return Ok(Some(Action::Alert(
+                                format!("你希望将{}:", user.name),
+                                Some(ConfirmEvent::GroupManage(actions, None)),
+                            )));
+                        }
                     }
                     _ => {}
                 },
@@ -115,22 +499,137 @@ impl Component for GroupManager {
                     KeyCode::Esc => {
                         self.friends_list_state.select(None);
                         self.next_state();
-                        self.user_input.reset();
+                        self.close_invite();
+                    }
+                    KeyCode::Char(to_insert) => {
+                        self.user_input.enter_char(to_insert);
+                        self.search_at = Some(Instant::now());
+                    }
+                    KeyCode::Backspace => {
+                        self.user_input.delete_char();
+                        self.search_at = Some(Instant::now());
                     }
-                    KeyCode::Char(to_insert) => self.user_input.enter_char(to_insert),
-                    KeyCode::Backspace => self.user_input.delete_char(),
                     KeyCode::Left => self.user_input.move_cursor_left(),
                     KeyCode::Right => self.user_input.move_cursor_right(),
                     KeyCode::Up => self.friends_list_state.select_previous(),
                     KeyCode::Down => self.friends_list_state.select_next(),
+                    KeyCode::Tab => self.toggle_invite_selected(),
                     KeyCode::Enter => {
-                        if let Some(idx) = self.friends_list_state.selected() {
-                            let name = self.friends.lock().unwrap().get(idx).unwrap().name.clone();
-                            return Ok(Some(Action::Alert(
-                                format!("确定邀请{name}入群么？"),
-                                Some(ConfirmEvent::InviteFriend),
-                            )));
+                        // 未多选时，邀请当前选中的用户
+                        if self.invite_selected.is_empty() {
+                            self.toggle_invite_selected();
+                        }
+                        if self.invite_selected.is_empty() {
+                            return Ok(None);
+                        }
+                        let uids = self.invite_selected.iter().copied().collect::<Vec<_>>();
+                        let names = uids
+                            .iter()
+                            .map(|uid| USER_DIRECTORY.name(*uid))
+                            .collect::<Vec<_>>()
+                            .join("、");
+                        return Ok(Some(Action::Alert(
+                            format!("确定邀请{names}入群么？"),
+                            Some(ConfirmEvent::InviteMembers(uids)),
+                        )));
+                    }
+                    _ => {}
+                },
+                State::Announcement => match key.code {
+                    KeyCode::Esc => self.close_announcement(),
+                    KeyCode::Char(to_insert) => self.announcement_input.enter_char(to_insert),
+                    KeyCode::Backspace => self.announcement_input.delete_char(),
+                    KeyCode::Left => self.announcement_input.move_cursor_left(),
+                    KeyCode::Right => self.announcement_input.move_cursor_right(),
+                    KeyCode::Enter => {
+                        self.announcement_input.submit_message();
+                        let content = self.announcement_input.data().unwrap_or_default();
+                        let gid = self.gid.unwrap();
+                        self.close_announcement();
+                        if let Err(e) = group::set_announcement(gid, content) {
+                            error!("fail to set group announcement: {e}");
+                            return Ok(Some(Action::Alert(e.to_string(), None)));
+                        }
+                        return Ok(Some(Action::AnnouncementChanged(gid)));
+                    }
+                    _ => {}
+                },
+                State::MuteDuration => match key.code {
+                    KeyCode::Esc => self.close_mute(),
+                    KeyCode::Up => self.mute_duration_state.select_previous(),
+                    KeyCode::Down => self.mute_duration_state.select_next(),
+                    KeyCode::Enter => {
+                        let Some(mute_duration) = self
+                            .mute_duration_state
+                            .selected()
+                            .and_then(|idx| MuteDuration::iter().nth(idx))
+                        else {
+                            return Ok(None);
+                        };
+                        if mute_duration == MuteDuration::Custom {
+                            self.state = State::CustomMute;
+                            self.custom_mute_input.is_editing = true;
+                            return Ok(None);
                         }
+                        return Ok(self.mute(mute_duration.duration()));
+                    }
+                    _ => {}
+                },
+                State::CustomMute => match key.code {
+                    KeyCode::Esc => self.close_mute(),
+                    KeyCode::Char(to_insert) => self.custom_mute_input.enter_char(to_insert),
+                    KeyCode::Backspace => self.custom_mute_input.delete_char(),
+                    KeyCode::Left => self.custom_mute_input.move_cursor_left(),
+                    KeyCode::Right => self.custom_mute_input.move_cursor_right(),
+                    KeyCode::Enter => {
+                        self.custom_mute_input.submit_message();
+                        let input = self.custom_mute_input.data().unwrap_or_default();
+                        self.custom_mute_input.reset();
+                        return match parse_duration(&input) {
+                            Some(duration) => Ok(self.mute(Some(duration))),
+                            None => Ok(Some(Action::Alert(
+                                "禁言时长格式错误，如：30m、2h、1d".to_string(),
+                                None,
+                            ))),
+                        };
+                    }
+                    _ => {}
+                },
+                State::FilterMember => {
+                    match key.code {
+                        KeyCode::Esc => {
+                            self.member_filter.reset();
+                            self.member_filter.is_editing = false;
+                            self.state = State::GroupDetail;
+                        }
+                        KeyCode::Enter => {
+                            self.member_filter.is_editing = false;
+                            self.state = State::GroupDetail;
+                        }
+                        KeyCode::Char(to_insert) => self.member_filter.enter_char(to_insert),
+                        KeyCode::Backspace => self.member_filter.delete_char(),
+                        KeyCode::Left => self.member_filter.move_cursor_left(),
+                        KeyCode::Right => self.member_filter.move_cursor_right(),
+                        _ => {}
+                    }
+                    self.group_members_list_state.select(None);
+                }
+                State::Rename => match key.code {
+                    KeyCode::Esc => self.close_rename(),
+                    KeyCode::Char(to_insert) => self.rename_input.enter_char(to_insert),
+                    KeyCode::Backspace => self.rename_input.delete_char(),
+                    KeyCode::Left => self.rename_input.move_cursor_left(),
+                    KeyCode::Right => self.rename_input.move_cursor_right(),
+                    KeyCode::Enter => {
+                        self.rename_input.submit_message();
+                        let name = self.rename_input.data().unwrap_or_default();
+                        if name.trim().is_empty() {
+                            return Ok(Some(Action::Alert("请输入群名称".to_string(), None)));
+                        }
+                        return Ok(Some(Action::Alert(
+                            format!("确定将群名称修改为{name}么？"),
+                            Some(ConfirmEvent::RenameGroup(self.gid.unwrap(), name)),
+                        )));
                     }
                     _ => {}
                 },
@@ -141,15 +640,46 @@ impl Component for GroupManager {
 
     fn update(&mut self, action: Action) -> color_eyre::Result<Option<Action>> {
         match action {
-            Action::Confirm(ConfirmEvent::InviteFriend) => {
+            Action::Logout => *self = GroupManager::new(self.mode_holder.clone()),
+            Action::Tick if self.search_at.is_some_and(|at| at.elapsed() >= DEBOUNCE) => {
+                self.search_at = None;
+                self.search_users();
+            }
+            Action::Tick if self.profile_at.is_some_and(|at| at.elapsed() >= DEBOUNCE) => {
+                self.profile_at = None;
+                self.load_member_profile();
+            }
+            Action::Confirm(ConfirmEvent::InviteMembers(uids)) => {
                 self.mode_holder.set_mode(Mode::GroupManager);
-                self.invite_group_member();
+                let result = self.invite_group_members(uids);
+                self.close_invite();
                 self.next_state();
                 self.group_detail(self.gid.unwrap());
+                return Ok(result);
             }
-            Action::Confirm(ConfirmEvent::GroupManage(Some(action))) => {
-                if let Some(idx) = self.group_members_list_state.selected() {
-                    let uid = self.detail.lock().unwrap().users.get(idx).unwrap().id;
+            Action::Confirm(ConfirmEvent::GroupManage(_, Some(action))) => {
+                if let Some(user) = self.selected_member() {
+                    let uid = user.id;
+                    // 转让群主和取消管理员需要再次确认，禁言需要选择时长
+                    match action {
+                        ManageAction::Forbid => {
+                            self.choose_mute_duration(vec![uid]);
+                            return Ok(None);
+                        }
+                        ManageAction::TransferOwner => {
+                            return Ok(Some(Action::Alert(
+                                format!("确定将群主转让给{}么？转让后您将成为普通成员", user.name),
+                                Some(ConfirmEvent::TransferOwner(uid)),
+                            )));
+                        }
+                        ManageAction::RevokeManager => {
+                            return Ok(Some(Action::Alert(
+                                format!("确定取消{}的管理员身份么？", user.name),
+                                Some(ConfirmEvent::RevokeManager(uid)),
+                            )));
+                        }
+                        _ => {}
+                    }
                     match action.handle(self.gid.unwrap(), uid) {
                         Ok(_) => {}
                         Err(e) => {
@@ -159,6 +689,56 @@ impl Component for GroupManager {
                 }
                 self.group_detail(self.gid.unwrap());
             }
+            Action::Confirm(ConfirmEvent::TransferOwner(uid)) => {
+                let gid = self.gid.unwrap();
+                if let Err(e) = ManageAction::TransferOwner.handle(gid, uid) {
+                    error!("fail to transfer owner, err: {e}");
+                    return Ok(Some(Action::Alert(e.to_string(), None)));
+                }
+                self.group_detail(gid);
+                return Ok(Some(Action::RefreshRecentChat));
+            }
+            Action::Confirm(ConfirmEvent::RevokeManager(uid)) => {
+                let gid = self.gid.unwrap();
+                if let Err(e) = ManageAction::RevokeManager.handle(gid, uid) {
+                    error!("fail to revoke manager, err: {e}");
+                    return Ok(Some(Action::Alert(e.to_string(), None)));
+                }
+                self.group_detail(gid);
+            }
+            Action::Confirm(ConfirmEvent::EvictMembers(uids)) => {
+                return Ok(self.moderate(ManageAction::Evict, uids, None));
+            }
+            Action::Confirm(ConfirmEvent::ForbidAll(gid, enable)) => {
+                if let Err(e) = group::forbid_all(gid, enable) {
+                    error!("fail to set forbid all, err: {e}");
+                    return Ok(Some(Action::Alert(e.to_string(), None)));
+                }
+                self.group_detail(gid);
+            }
+            Action::Confirm(ConfirmEvent::RenameGroup(gid, name)) => {
+                self.close_rename();
+                if let Err(e) = group::rename(gid, name) {
+                    error!("fail to rename group, err: {e}");
+                    return Ok(Some(Action::Alert(e.to_string(), None)));
+                }
+                self.group_detail(gid);
+                return Ok(Some(Action::RefreshRecentChat));
+            }
+            Action::Confirm(ConfirmEvent::LeaveGroup(gid)) => {
+                if let Err(e) = group::leave(gid) {
+                    error!("fail to leave group, err: {e}");
+                    return Ok(Some(Action::Alert(e.to_string(), None)));
+                }
+                return Ok(Some(self.exit_group(gid)));
+            }
+            Action::Confirm(ConfirmEvent::DissolveGroup(gid)) => {
+                if let Err(e) = group::dissolve(gid) {
+                    error!("fail to dissolve group, err: {e}");
+                    return Ok(Some(Action::Alert(e.to_string(), None)));
+                }
+                return Ok(Some(self.exit_group(gid)));
+            }
             Action::Group(gid) => {
                 self.gid = Some(gid);
                 self.mode_holder.set_mode(Mode::GroupManager);
@@ -174,43 +754,87 @@ impl Component for GroupManager {
             let area = area_util::group_manager_area(area);
             let [search_area, group_member_area] =
                 Layout::vertical([Constraint::Length(3), Constraint::Min(0)]).areas(area);
-            let [group_member_area, friend_area] = match self.state {
-                State::GroupDetail => {
-                    Layout::horizontal([Constraint::Percentage(100), Constraint::Percentage(0)])
-                        .areas(group_member_area)
-                }
+            let selected_member = self.selected_member();
+            let [group_member_area, side_area] = match self.state {
                 State::InviteFriend => {
                     Layout::horizontal([Constraint::Percentage(50), Constraint::Percentage(50)])
                         .areas(group_member_area)
                 }
+                _ if selected_member.is_some() => {
+                    Layout::horizontal([Constraint::Percentage(60), Constraint::Percentage(40)])
+                        .areas(group_member_area)
+                }
+                _ => Layout::horizontal([Constraint::Percentage(100), Constraint::Percentage(0)])
+                    .areas(group_member_area),
             };
-            let list_block = Block::new()
-                .title("Group Members(↑↓ Or Enter)")
+            let mut title = format!(
+                "Group Members(↑↓ Or Enter, Space To Select, o: Sort By {})",
+                self.member_sort
+            );
+            if !self.selected_members.is_empty() {
+                title.push_str(&format!(" 已选{}人", self.selected_members.len()));
+            }
+            let mut list_block = Block::new()
+                .title(title)
+                .title_bottom(
+                    Line::from(
+                        "/: Filter, l: Leave, d: Dissolve, r: Rename, a: Announcement, x: Evict, f: Mute, F: Mute All",
+                    )
+                    .centered(),
+                )
                 .title_alignment(Alignment::Center)
                 .borders(Borders::ALL)
                 .border_set(symbols::border::ROUNDED);
+            if self.detail.lock().unwrap().forbid_all {
+                list_block = list_block.title(
+                    Line::from(Span::styled(
+                        "全员禁言中🤐",
+                        Style::default().fg(Color::Red),
+                    ))
+                    .right_aligned(),
+                );
+            }
 
+            let input = match self.state {
+                State::Rename => &self.rename_input,
+                State::CustomMute => &self.custom_mute_input,
+                State::Announcement => &self.announcement_input,
+                State::InviteFriend => &self.user_input,
+                _ => &self.member_filter,
+            };
             let search_block = Block::new()
-                .title(self.user_input.input_data.label())
+                .title(input.input_data.label())
                 .title_alignment(Alignment::Center)
                 .borders(Borders::ALL)
                 .border_set(symbols::border::ROUNDED);
-            let user_input =
-                Paragraph::new(self.user_input.input.clone().unwrap_or("".to_string()))
-                    .style(self.user_input.select_style())
-                    .block(search_block);
+            let user_input = Paragraph::new(input.input.clone().unwrap_or("".to_string()))
+                .style(input.select_style())
+                .block(search_block);
             frame.render_widget(user_input, search_area);
             self.render_group_members(frame, group_member_area, list_block);
+            if self.state != State::InviteFriend
+                && let Some(gu) = &selected_member
+            {
+                self.render_member_profile(frame, side_area, gu);
+            }
             match self.state {
                 State::GroupDetail => {}
+                State::FilterMember => self.member_filter.set_cursor_position(search_area),
+                State::MuteDuration => self.render_mute_duration(frame, group_member_area),
+                State::CustomMute => self.custom_mute_input.set_cursor_position(search_area),
+                State::Rename => self.rename_input.set_cursor_position(search_area),
+                State::Announcement => self.announcement_input.set_cursor_position(search_area),
                 State::InviteFriend => {
                     let list_block = Block::new()
-                        .title("Friends(↑↓ Or Enter)")
+                        .title(format!(
+                            "Friends & Users(↑↓, Tab To Select, Enter To Invite) 已选{}人",
+                            self.invite_selected.len()
+                        ))
                         .title_alignment(Alignment::Center)
                         .borders(Borders::ALL)
                         .border_set(symbols::border::ROUNDED);
                     self.user_input.set_cursor_position(search_area);
-                    self.render_friends(frame, friend_area, list_block);
+                    self.render_friends(frame, side_area, list_block);
                 }
             }
         }
@@ -223,7 +847,7 @@ impl GroupManager {
         Self {
             mode_holder,
             user_input: UserInput::new(InputData::Search {
-                label: Some("Press e To Invite Friend To Join Group".to_string()),
+                label: Some("Search Friends Or Users To Invite, Esc To Back.".to_string()),
                 data: None,
             }),
             gid: None,
@@ -231,23 +855,286 @@ impl GroupManager {
                 group_id: 0,
                 name: "".to_string(),
                 users: vec![],
+                forbid_all: false,
             })),
             friends: Arc::new(Mutex::new(vec![])),
             state: State::GroupDetail,
             group_members_list_state: Default::default(),
             friends_list_state: Default::default(),
+            rename_input: UserInput::new(InputData::Search {
+                label: Some("Input New Group Name, Enter To Submit, Esc To Back.".to_string()),
+                data: None,
+            }),
+            announcement_input: UserInput::new(InputData::Search {
+                label: Some("Input Group Announcement, Enter To Submit, Esc To Back.".to_string()),
+                data: None,
+            }),
+            selected_members: HashSet::new(),
+            mute_targets: vec![],
+            mute_duration_state: Default::default(),
+            custom_mute_input: UserInput::new(InputData::Search {
+                label: Some("Input Mute Duration, e.g. 30m, 2h, 1d. Esc To Back.".to_string()),
+                data: None,
+            }),
+            member_filter: UserInput::new(InputData::Search {
+                label: Some("Press / To Filter Members, e To Invite Friend".to_string()),
+                data: None,
+            }),
+            member_sort: MemberSort::default(),
+            member_profiles: Arc::new(Mutex::new(HashMap::new())),
+            profile_at: None,
+            user_search: Arc::new(Mutex::new(UserSearch::default())),
+            search_at: None,
+            invite_selected: HashSet::new(),
         }
     }
 
-    fn render_group_members(&mut self, frame: &mut Frame, friend_area: Rect, block: Block) {
-        // Iterate through all elements in the `items` and stylize them.
-        let items: Vec<ListItem> = self
-            .detail
+    fn current_member(&self) -> Option<GroupUser> {
+        let current_uid = CURRENT_USER.get_user().user.unwrap().id;
+        self.detail
             .lock()
             .unwrap()
             .users
             .iter()
-            .map(|gu| ListItem::new(Text::from(gu)))
+            .find(|gu| gu.id == current_uid)
+            .cloned()
+    }
+
+    /// 过滤、排序后的群成员
+    fn visible_members(&self) -> Vec<GroupUser> {
+        let filter = self.member_filter.input.clone().unwrap_or_default();
+        filter_members(
+            &self.detail.lock().unwrap().users,
+            &filter,
+            self.member_sort,
+            |gu| REMARKS.display_name(gu.id, &gu.name),
+        )
+    }
+
+    fn selected_member(&self) -> Option<GroupUser> {
+        let idx = self.group_members_list_state.selected()?;
+        self.visible_members().into_iter().nth(idx)
+    }
+
+    /// 在后台加载选中成员的详情，已加载的不再请求
+    fn load_member_profile(&mut self) {
+        let Some(gu) = self.selected_member() else {
+            return;
+        };
+        if self.member_profiles.lock().unwrap().contains_key(&gu.id) {
+            return;
+        }
+        let member_profiles = Arc::clone(&self.member_profiles);
+        tokio::task::spawn_blocking(move || match user::detail_by_id(gu.id) {
+            Ok(profile) => {
+                member_profiles.lock().unwrap().insert(gu.id, profile);
+            }
+            Err(e) => error!("fail to fetch member profile: {e}"),
+        });
+    }
+
+    fn render_member_profile(&self, frame: &mut Frame, area: Rect, gu: &GroupUser) {
+        let current_uid = CURRENT_USER.get_user().user.unwrap().id;
+        let block = Block::new()
+            .title("Member Profile")
+            .title_bottom(Line::from("c: Chat, A: Add Friend, Enter: Manage").centered())
+            .title_alignment(Alignment::Center)
+            .borders(Borders::ALL)
+            .border_set(symbols::border::ROUNDED);
+        let field = |label: &str, value: String| {
+            Line::from(vec![
+                Span::styled(format!("{label}: "), Style::default().fg(Color::DarkGray)),
+                Span::styled(value, Style::default().fg(Color::White)),
+            ])
+        };
+        let status = PRESENCES.get(gu.id);
+        let role = match GroupRole::from(gu) {
+            GroupRole::Owner => "群主",
+            GroupRole::Admin => "管理员",
+            GroupRole::Member => "成员",
+        };
+        let mut lines = vec![
+            field("名称", gu.name.clone()),
+            Line::from(vec![
+                Span::styled("状态: ", Style::default().fg(Color::DarkGray)),
+                status.dot(),
+                Span::styled(
+                    status.describe(),
+                    Style::default().fg(status.presence.color()),
+                ),
+            ]),
+            field("角色", role.to_string()),
+            field(
+                "入群时间",
+                gu.join_time
+                    .map(|time| time.format("%Y-%m-%d %H:%M").to_string())
+                    .unwrap_or("-".to_string()),
+            ),
+        ];
+        if let Some(profile) = self.member_profiles.lock().unwrap().get(&gu.id) {
+            let friend = if gu.id == current_uid {
+                "自己"
+            } else if profile.is_friend {
+                "是"
+            } else {
+                "否"
+            };
+            lines.push(field(
+                "邮箱",
+                profile.email.clone().unwrap_or("-".to_string()),
+            ));
+            lines.push(field(
+                "手机",
+                profile.phone.clone().unwrap_or("-".to_string()),
+            ));
+            lines.push(field(
+                "注册时间",
+                profile.create_time.format("%Y-%m-%d %H:%M").to_string(),
+            ));
+            lines.push(field("好友", friend.to_string()));
+        }
+        if gu.forbid {
+            let forbid = match gu.forbid_until {
+                Some(until) => format!("剩余{}", format_remaining(until - Local::now())),
+                None => "永久".to_string(),
+            };
+            lines.push(field("禁言", forbid));
+        }
+        let profile = Paragraph::new(lines)
+            .wrap(Wrap { trim: false })
+            .block(block);
+        frame.render_widget(profile, area);
+    }
+
+    fn toggle_selected_member(&mut self) {
+        let current_uid = CURRENT_USER.get_user().user.unwrap().id;
+        if let Some(gu) = self.selected_member()
+            && gu.id != current_uid
+            && !self.selected_members.remove(&gu.id)
+        {
+            self.selected_members.insert(gu.id);
+        }
+    }
+
+    fn choose_mute_duration(&mut self, uids: Vec<i32>) {
+        self.mute_targets = uids;
+        self.mute_duration_state.select(Some(0));
+        self.state = State::MuteDuration;
+    }
+
+    fn close_mute(&mut self) {
+        self.mute_targets.clear();
+        self.custom_mute_input.reset();
+        self.custom_mute_input.is_editing = false;
+        self.state = State::GroupDetail;
+    }
+
+    fn mute(&mut self, duration: Option<Duration>) -> Option<Action> {
+        let uids = std::mem::take(&mut self.mute_targets);
+        self.close_mute();
+        self.moderate(ManageAction::Forbid, uids, duration)
+    }
+
+    /// 对多个成员执行管理操作，跳过无权操作的成员，并汇总失败结果
+    fn moderate(
+        &mut self,
+        action: ManageAction,
+        uids: Vec<i32>,
+        duration: Option<Duration>,
+    ) -> Option<Action> {
+        let gid = self.gid.unwrap();
+        let actor = self.current_member().map(|gu| GroupRole::from(&gu))?;
+        let members = self.detail.lock().unwrap().users.clone();
+        let mut failures = vec![];
+        for uid in uids {
+            let Some(gu) = members.iter().find(|gu| gu.id == uid) else {
+                continue;
+            };
+            // 已禁言的成员允许重新禁言，以更新禁言时长
+            let allowed = manage_actions(actor, GroupRole::from(gu), false).contains(&action);
+            let res = match action {
+                _ if !allowed => Err(format_err!("无权操作")),
+                ManageAction::Forbid => group::forbid(gid, uid, duration),
+                _ => action.handle(gid, uid),
+            };
+            if let Err(e) = res {
+                error!("fail to handle action: {action}, uid: {uid}, err: {e}");
+                failures.push(format!("{}: {e}", gu.name));
+            }
+        }
+        self.selected_members.clear();
+        self.group_detail(gid);
+        if failures.is_empty() {
+            None
+        } else {
+            Some(Action::Alert(
+                format!("以下成员{action}失败：{}", failures.join("；")),
+                None,
+            ))
+        }
+    }
+
+    fn render_mute_duration(&mut self, frame: &mut Frame, area: Rect) {
+        let area = area_util::centered_rect(40, 50, area);
+        let block = Block::new()
+            .title(format!("禁言时长({}人)", self.mute_targets.len()))
+            .title_alignment(Alignment::Center)
+            .borders(Borders::ALL)
+            .border_set(symbols::border::ROUNDED);
+        let items: Vec<ListItem> = MuteDuration::iter()
+            .map(|duration| ListItem::new(duration.to_string()))
+            .collect();
+        let list = List::new(items)
+            .block(block)
+            .highlight_style(SELECTED_STYLE)
+            .highlight_spacing(HighlightSpacing::Always);
+        frame.render_widget(Clear, area);
+        frame.render_stateful_widget(list, area, &mut self.mute_duration_state);
+    }
+
+    fn close_announcement(&mut self) {
+        self.announcement_input.reset();
+        self.announcement_input.is_editing = false;
+        self.state = State::GroupDetail;
+    }
+
+    fn close_rename(&mut self) {
+        self.rename_input.reset();
+        self.rename_input.is_editing = false;
+        self.state = State::GroupDetail;
+    }
+
+    /// 退出或解散群聊后，关闭当前会话并返回最近聊天
+    fn exit_group(&mut self, gid: i32) -> Action {
+        let mut chat_vo_holder = CHAT_VO.lock().unwrap();
+        if let Some(ChatVo::Group { gid: current, .. }) = chat_vo_holder.chat_vo()
+            && current == gid
+        {
+            chat_vo_holder.clear();
+        }
+        self.gid = None;
+        self.group_members_list_state.select(None);
+        self.mode_holder.set_mode(Mode::RecentChat);
+        Action::RefreshRecentChat
+    }
+
+    fn render_group_members(&mut self, frame: &mut Frame, friend_area: Rect, block: Block) {
+        // Iterate through all elements in the `items` and stylize them.
+        let items: Vec<ListItem> = self
+            .visible_members()
+            .iter()
+            .map(|gu| {
+                let mut line = member_line(gu);
+                if !self.selected_members.is_empty() {
+                    let mark = if self.selected_members.contains(&gu.id) {
+                        "[x] "
+                    } else {
+                        "[ ] "
+                    };
+                    line.spans.insert(0, Span::raw(mark));
+                }
+                ListItem::new(line)
+            })
             .collect();
 
         // Create a List from all list items and highlight the currently selected one
@@ -259,19 +1146,33 @@ impl GroupManager {
     }
     fn render_friends(&mut self, frame: &mut Frame, friend_area: Rect, block: Block) {
         let items: Vec<ListItem> = self
-            .friends
-            .lock()
-            .unwrap()
+            .invite_candidates()
             .iter()
-            .filter_map(|f| match self.user_input.input.clone() {
-                None => Some(ListItem::new(Text::from(f))),
-                Some(x) => {
-                    if f.name.contains(&x) {
-                        Some(ListItem::new(Text::from(f)))
-                    } else {
-                        None
-                    }
+            .map(|candidate| {
+                if candidate.is_member {
+                    return ListItem::new(Line::from(Span::styled(
+                        format!("    {} (已在群中)", candidate.name),
+                        Style::default().fg(Color::DarkGray),
+                    )));
                 }
+                let mark = if self.invite_selected.contains(&candidate.id) {
+                    "[x] "
+                } else {
+                    "[ ] "
+                };
+                let kind = if candidate.is_friend {
+                    "好友"
+                } else {
+                    "用户"
+                };
+                ListItem::new(Line::from(vec![
+                    Span::raw(mark),
+                    PRESENCES.get(candidate.id).dot(),
+                    Span::styled(
+                        format!("{kind}: {}", candidate.name),
+                        Style::default().fg(Color::White),
+                    ),
+                ]))
             })
             .collect();
 
@@ -283,13 +1184,114 @@ impl GroupManager {
         frame.render_stateful_widget(list, friend_area, &mut self.friends_list_state);
     }
 
+    /// 按输入过滤的好友，以及搜索到的非好友用户
+    fn invite_candidates(&self) -> Vec<InviteCandidate> {
+        let current_uid = CURRENT_USER.get_user().user.unwrap().id;
+        let filter = self.user_input.input.clone().unwrap_or_default();
+        let members = self
+            .detail
+            .lock()
+            .unwrap()
+            .users
+            .iter()
+            .map(|gu| gu.id)
+            .collect::<HashSet<_>>();
+        let friends = self.friends.lock().unwrap();
+        let mut candidates = friends
+            .iter()
+            .map(|f| (f.id, REMARKS.display_name(f.id, &f.name)))
+            .filter(|(_, name)| name_matches(name, &filter))
+            .map(|(id, name)| InviteCandidate {
+                id,
+                name,
+                is_friend: true,
+                is_member: members.contains(&id),
+            })
+            .collect::<Vec<_>>();
+        let friend_ids = friends.iter().map(|f| f.id).collect::<HashSet<_>>();
+        candidates.extend(
+            self.user_search
+                .lock()
+                .unwrap()
+                .results
+                .iter()
+                .filter(|u| u.id != current_uid && !friend_ids.contains(&u.id))
+                .map(|u| InviteCandidate {
+                    id: u.id,
+                    name: u.name.clone(),
+                    is_friend: false,
+                    is_member: members.contains(&u.id),
+                }),
+        );
+        candidates
+    }
+
+    /// 停止输入后，在后台按名称搜索用户
+    fn search_users(&mut self) {
+        let keyword = self
+            .user_input
+            .input
+            .clone()
+            .filter(|keyword| !keyword.trim().is_empty());
+        let mut user_search = self.user_search.lock().unwrap();
+        if keyword == user_search.keyword {
+            return;
+        }
+        user_search.keyword = keyword.clone();
+        user_search.results.clear();
+        self.friends_list_state.select(None);
+        let Some(keyword) = keyword else {
+            return;
+        };
+        let user_search = Arc::clone(&self.user_search);
+        tokio::task::spawn_blocking(move || {
+            let users = match user::search(keyword.clone()) {
+                Ok(users) => users,
+                Err(err) => {
+                    error!("fail to search users: {err}");
+                    return;
+                }
+            };
+            users
+                .iter()
+                .for_each(|u| USER_DIRECTORY.seed(u.id, u.name.clone()));
+            let mut user_search = user_search.lock().unwrap();
+            // 请求期间关键字已变化时丢弃结果
+            if user_search.keyword.as_ref() == Some(&keyword) {
+                user_search.results = users;
+            }
+        });
+    }
+
+    fn toggle_invite_selected(&mut self) {
+        if let Some(idx) = self.friends_list_state.selected()
+            && let Some(candidate) = self.invite_candidates().into_iter().nth(idx)
+            && !candidate.is_member
+            && !self.invite_selected.remove(&candidate.id)
+        {
+            self.invite_selected.insert(candidate.id);
+        }
+    }
+
+    fn close_invite(&mut self) {
+        self.user_input.reset();
+        self.invite_selected.clear();
+        self.search_at = None;
+        *self.user_search.lock().unwrap() = UserSearch::default();
+    }
+
     fn next_state(&mut self) {
         match self.state {
             State::GroupDetail => {
                 self.state = State::InviteFriend;
                 self.user_input.is_editing = true;
             }
-            State::InviteFriend => {
+            State::InviteFriend
+            | State::Rename
+            | State::Announcement
+            | State::MuteDuration
+            | State::CustomMute
+            | State::FilterMember => {
                 self.state = State::GroupDetail;
                 self.user_input.is_editing = false;
             }
@@ -299,6 +1301,9 @@ impl GroupManager {
     fn fetch_friends(&mut self) {
         match friend::friends() {
             Ok(friends) => {
+                friends
+                    .iter()
+                    .for_each(|f| USER_DIRECTORY.seed(f.id, f.name.clone()));
                 self.friends = Arc::new(Mutex::new(friends));
             }
             Err(err) => {
@@ -307,21 +1312,45 @@ impl GroupManager {
         };
     }
 
-    fn invite_group_member(&mut self) {
-        if let Some(idx) = self.friends_list_state.selected() {
-            if let Some(friend) = self.friends.lock().unwrap().get(idx) {
-                let uid = friend.id;
-                let gid = self.gid.unwrap();
-                if let Err(e) = group::invite(uid, gid) {
+    /// 逐个邀请，汇总每个用户的邀请结果
+    fn invite_group_members(&mut self, uids: Vec<i32>) -> Option<Action> {
+        let gid = self.gid.unwrap();
+        let mut invited = 0;
+        let mut failures = vec![];
+        for uid in uids {
+            match group::invite(uid, gid) {
+                Ok(_) => invited += 1,
+                Err(e) => {
                     error!("Failed to invite group :{e}");
-                };
+                    failures.push(format!("{}: {e}", USER_DIRECTORY.name(uid)));
+                }
             }
         }
+        if failures.is_empty() {
+            None
+        } else {
+            Some(Action::Alert(
+                format!(
+                    "成功邀请{invited}人，以下用户邀请失败：{}",
+                    failures.join("；")
+                ),
+                None,
+            ))
+        }
     }
 
     fn group_detail(&mut self, gid: i32) {
         match group::detail(gid) {
             Ok(detail) => {
+                detail
+                    .users
+                    .iter()
+                    .for_each(|gu| USER_DIRECTORY.seed(gu.id, gu.name.clone()));
+                let uids = detail.users.iter().map(|gu| gu.id).collect();
+                if let Err(err) = presence::fetch(uids) {
+                    error!("fail to fetch presence of group members: {}", err);
+                }
+                self.member_profiles.lock().unwrap().clear();
                 self.detail = Arc::new(Mutex::new(detail));
             }
             Err(err) => error!("fail to fetch group detail: {}", err),
@@ -329,20 +1358,228 @@ impl GroupManager {
     }
 }
 
-impl From<&GroupUser> for Text<'_> {
-    fn from(gu: &GroupUser) -> Self {
-        let mut spans = vec![Span::styled(
-            format!("好友: {}", gu.name),
+fn member_line(gu: &GroupUser) -> Line<'static> {
+    let mut spans = vec![
+        PRESENCES.get(gu.id).dot(),
+        Span::styled(
+            format!("好友: {}", REMARKS.display_name(gu.id, &gu.name)),
             Style::default().fg(Color::White),
-        )];
-        if gu.admin {
-            spans.push(Span::styled(", ", Style::default().fg(Color::White)));
-            spans.push(Span::styled("管理员", Style::default().fg(Color::Blue)));
+        ),
+    ];
+    if gu.owner {
+        spans.push(Span::styled(", ", Style::default().fg(Color::White)));
+        spans.push(Span::styled("群主", Style::default().fg(Color::Magenta)));
+    } else if gu.admin {
+        spans.push(Span::styled(", ", Style::default().fg(Color::White)));
+        spans.push(Span::styled("管理员", Style::default().fg(Color::Blue)));
+    };
+    if gu.forbid {
+        let forbid = match gu.forbid_until {
+            Some(until) => format!("已禁言🤐 剩余{}", format_remaining(until - Local::now())),
+            None => "已禁言🤐".to_string(),
         };
-        if gu.forbid {
-            spans.push(Span::styled(", ", Style::default().fg(Color::White)));
-            spans.push(Span::styled("已禁言🤐", Style::default().fg(Color::Red)));
+        spans.push(Span::styled(", ", Style::default().fg(Color::White)));
+        spans.push(Span::styled(forbid, Style::default().fg(Color::Red)));
+    };
+    Line::from(spans)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use strum::IntoEnumIterator;
+
+    #[test]
+    fn test_owner_manage_member() {
+        assert_eq!(
+            manage_actions(GroupRole::Owner, GroupRole::Member, false),
+            vec![
+                ManageAction::Evict,
+                ManageAction::Forbid,
+                ManageAction::SetManager,
+                ManageAction::TransferOwner,
+            ]
+        );
+        assert_eq!(
+            manage_actions(GroupRole::Owner, GroupRole::Member, true),
+            vec![
+                ManageAction::Evict,
+                ManageAction::UnForbid,
+                ManageAction::SetManager,
+                ManageAction::TransferOwner,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_owner_manage_admin() {
+        assert_eq!(
+            manage_actions(GroupRole::Owner, GroupRole::Admin, false),
+            vec![
+                ManageAction::Evict,
+                ManageAction::Forbid,
+                ManageAction::RevokeManager,
+                ManageAction::TransferOwner,
+            ]
+        );
+        assert_eq!(
+            manage_actions(GroupRole::Owner, GroupRole::Admin, true),
+            vec![
+                ManageAction::Evict,
+                ManageAction::UnForbid,
+                ManageAction::RevokeManager,
+                ManageAction::TransferOwner,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_admin_manage_member() {
+        assert_eq!(
+            manage_actions(GroupRole::Admin, GroupRole::Member, false),
+            vec![ManageAction::Evict, ManageAction::Forbid]
+        );
+        assert_eq!(
+            manage_actions(GroupRole::Admin, GroupRole::Member, true),
+            vec![ManageAction::Evict, ManageAction::UnForbid]
+        );
+    }
+
+    #[test]
+    fn test_admin_cannot_manage_admin_or_owner() {
+        for forbid in [false, true] {
+            assert!(manage_actions(GroupRole::Admin, GroupRole::Admin, forbid).is_empty());
+            assert!(manage_actions(GroupRole::Admin, GroupRole::Owner, forbid).is_empty());
+        }
+    }
+
+    #[test]
+    fn test_member_cannot_manage_anyone() {
+        for target in GroupRole::iter() {
+            for forbid in [false, true] {
+                assert!(manage_actions(GroupRole::Member, target, forbid).is_empty());
+            }
+        }
+    }
+
+    #[test]
+    fn test_nobody_can_manage_owner() {
+        for actor in GroupRole::iter() {
+            for forbid in [false, true] {
+                assert!(manage_actions(actor, GroupRole::Owner, forbid).is_empty());
+            }
+        }
+    }
+
+    #[test]
+    fn test_forbid_actions_match_forbid_state() {
+        for actor in GroupRole::iter() {
+            for target in GroupRole::iter() {
+                let actions = manage_actions(actor, target, false);
+                assert!(!actions.contains(&ManageAction::UnForbid));
+                let actions = manage_actions(actor, target, true);
+                assert!(!actions.contains(&ManageAction::Forbid));
+            }
+        }
+    }
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("30"), Some(Duration::from_secs(30 * 60)));
+        assert_eq!(parse_duration("30m"), Some(Duration::from_secs(30 * 60)));
+        assert_eq!(
+            parse_duration(" 2H "),
+            Some(Duration::from_secs(2 * 60 * 60))
+        );
+        assert_eq!(
+            parse_duration("1d"),
+            Some(Duration::from_secs(24 * 60 * 60))
+        );
+        assert_eq!(parse_duration(""), None);
+        assert_eq!(parse_duration("0m"), None);
+        assert_eq!(parse_duration("1w"), None);
+        assert_eq!(parse_duration("abc"), None);
+    }
+
+    #[test]
+    fn test_format_remaining() {
+        assert_eq!(format_remaining(TimeDelta::seconds(30)), "不足1分钟");
+        assert_eq!(format_remaining(TimeDelta::minutes(10)), "10分钟");
+        assert_eq!(format_remaining(TimeDelta::minutes(61)), "1小时1分钟");
+        assert_eq!(format_remaining(TimeDelta::hours(49)), "2天1小时");
+    }
+
+    fn member(id: i32, name: &str, role: GroupRole, forbid: bool) -> GroupUser {
+        GroupUser {
+            id,
+            name: name.to_string(),
+            admin: role == GroupRole::Admin,
+            forbid,
+            owner: role == GroupRole::Owner,
+            forbid_until: None,
+            join_time: None,
+        }
+    }
+
+    #[test]
+    fn test_filter_members() {
+        let members = vec![
+            member(1, "carol", GroupRole::Member, true),
+            member(2, "Bob", GroupRole::Admin, false),
+            member(3, "alice", GroupRole::Member, false),
+            member(4, "dave", GroupRole::Owner, false),
+        ];
+        let ids = |members: Vec<GroupUser>| members.iter().map(|gu| gu.id).collect::<Vec<_>>();
+        let name = |gu: &GroupUser| gu.name.clone();
+        assert_eq!(
+            ids(filter_members(&members, "", MemberSort::Role, name)),
+            [4, 2, 3, 1]
+        );
+        assert_eq!(
+            ids(filter_members(&members, "", MemberSort::Name, name)),
+            [3, 2, 1, 4]
+        );
+        assert_eq!(
+            ids(filter_members(&members, "", MemberSort::Mute, name)),
+            [1, 4, 2, 3]
+        );
+        assert_eq!(
+            ids(filter_members(&members, "b", MemberSort::Role, name)),
+            [2]
+        );
+        assert!(filter_members(&members, "zed", MemberSort::Role, name).is_empty());
+
+        // 按好友备注过滤与排序
+        let remark = |gu: &GroupUser| match gu.id {
+            1 => "Amy".to_string(),
+            _ => gu.name.clone(),
+        };
+        assert_eq!(
+            ids(filter_members(&members, "", MemberSort::Name, remark)),
+            [3, 1, 2, 4]
+        );
+        assert_eq!(
+            ids(filter_members(&members, "amy", MemberSort::Role, remark)),
+            [1]
+        );
+        assert!(filter_members(&members, "carol", MemberSort::Role, remark).is_empty());
+    }
+
+    #[test]
+    fn test_role_from_group_user() {
+        let mut gu = GroupUser {
+            id: 1,
+            name: "alice".to_string(),
+            admin: false,
+            forbid: false,
+            owner: false,
+            forbid_until: None,
+            join_time: None,
         };
-        Line::from(spans).into()
+        assert_eq!(GroupRole::from(&gu), GroupRole::Member);
+        gu.admin = true;
+        assert_eq!(GroupRole::from(&gu), GroupRole::Admin);
+        gu.owner = true;
+        assert_eq!(GroupRole::from(&gu), GroupRole::Owner);
     }
 }
@@ -0,0 +1,130 @@
+use crate::graphics::{GRAPHICS, halfblocks};
+use crate::proxy::file;
+use crate::proxy::file::Attachment;
+use image::DynamicImage;
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use ratatui::text::Line;
+use std::collections::HashMap;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::sync::{Arc, Mutex};
+use tracing::warn;
+
+/// 缩略图的最大列数
+const MAX_COLS: u16 = 48;
+/// 缩略图的最大行数
+const MAX_ROWS: u16 = 12;
+/// 解码后保留的最大边长，避免大图占用过多内存
+const MAX_PIXELS: u32 = 1024;
+
+/// 图片附件的缩略图，图片在后台下载并解码
+#[derive(Default)]
+pub(crate) struct ImagePreviews {
+    images: Arc<Mutex<HashMap<String, Loaded>>>,
+    /// 按 (附件id, 列数, 行数) 缓存绘制结果
+    rendered: HashMap<(String, u16, u16), Rendered>,
+}
+
+enum Loaded {
+    Loading,
+    Ready(Arc<DynamicImage>),
+    Failed,
+}
+
+#[derive(Clone)]
+enum Rendered {
+    Lines(Vec<Line<'static>>),
+    Graphics(u32, Arc<String>),
+}
+
+impl ImagePreviews {
+    /// 缩略图占用的列数和行数，图片未加载时在后台加载并返回 None
+    pub(crate) fn size(
+        &self,
+        attachment: &Attachment,
+        max_cols: u16,
+        max_rows: u16,
+    ) -> Option<(u16, u16)> {
+        if !attachment.mime.starts_with("image/") || max_cols == 0 || max_rows == 0 {
+            return None;
+        }
+        let mut images = self.images.lock().unwrap();
+        match images.get(&attachment.id) {
+            Some(Loaded::Ready(image)) => Some(GRAPHICS.fit(
+                image.width(),
+                image.height(),
+                max_cols.min(MAX_COLS),
+                max_rows.min(MAX_ROWS),
+            )),
+            Some(Loaded::Loading | Loaded::Failed) => None,
+            None => {
+                images.insert(attachment.id.clone(), Loaded::Loading);
+                self.load(attachment.clone());
+                None
+            }
+        }
+    }
+
+    fn load(&self, attachment: Attachment) {
+        let images = Arc::clone(&self.images);
+        tokio::task::spawn_blocking(move || {
+            let loaded = file::fetch_cached(&attachment)
+                .and_then(|path| Ok(image::open(path)?))
+                .map(|image| image.thumbnail(MAX_PIXELS, MAX_PIXELS));
+            let loaded = match loaded {
+                Ok(image) => Loaded::Ready(Arc::new(image)),
+                Err(err) => {
+                    warn!("fail to load image {}: {err}", attachment.id);
+                    Loaded::Failed
+                }
+            };
+            images.lock().unwrap().insert(attachment.id, loaded);
+        });
+    }
+
+    fn render(&mut self, attachment: &Attachment, cols: u16, rows: u16) -> Option<Rendered> {
+        let key = (attachment.id.clone(), cols, rows);
+        if let Some(rendered) = self.rendered.get(&key) {
+            return Some(rendered.clone());
+        }
+        let image = match self.images.lock().unwrap().get(&attachment.id) {
+            Some(Loaded::Ready(image)) => Arc::clone(image),
+            _ => return None,
+        };
+        let id = image_id(&key);
+        let rendered = match GRAPHICS.encode(id, &image, cols, rows) {
+            Some(seq) => Rendered::Graphics(id, Arc::new(seq)),
+            None => Rendered::Lines(halfblocks(&image, cols, rows)),
+        };
+        self.rendered.insert(key, rendered.clone());
+        Some(rendered)
+    }
+
+    /// 缩略图占用的行，字符画直接返回内容，图形协议返回空行占位
+    pub(crate) fn lines(
+        &mut self,
+        attachment: &Attachment,
+        cols: u16,
+        rows: u16,
+    ) -> Vec<Line<'static>> {
+        match self.render(attachment, cols, rows) {
+            Some(Rendered::Lines(lines)) => lines,
+            _ => vec![Line::default(); rows as usize],
+        }
+    }
+
+    /// 使用图形协议在 area 处绘制缩略图
+    pub(crate) fn place(&mut self, attachment: &Attachment, area: Rect, buf: &mut Buffer) {
+        if let Some(Rendered::Graphics(id, seq)) = self.render(attachment, area.width, area.height)
+        {
+            GRAPHICS.place(buf, area, id, seq);
+        }
+    }
+}
+
+/// kitty 协议的图片id，不能为 0
+fn image_id(key: &(String, u16, u16)) -> u32 {
+    let mut hasher = DefaultHasher::new();
+    key.hash(&mut hasher);
+    (hasher.finish() as u32).max(1)
+}
@@ -1,31 +1,57 @@
+use crate::account::{ACCOUNTS, reset_caches};
 use crate::action::Action;
 use crate::app::{Mode, ModeHolderLock};
+use crate::components::register::RegisterForm;
 use crate::components::user_input::{InputData, UserInput};
 use crate::components::{Component, area_util};
+use crate::config::{Config, ServerProfile};
+use crate::e2e::E2E;
 use crate::proxy::HOST;
-use crate::token::CURRENT_USER;
-use crate::{proxy, token};
+use crate::proxy::remark::REMARKS;
+use crate::proxy::token as token_api;
+use crate::session::{SavedSession, SessionKey, Store};
+use crate::token::{CURRENT_USER, User};
+use crate::{proxy, session, token};
 use color_eyre::eyre::format_err;
 use crossterm::event::{KeyCode, KeyEvent};
 use ratatui::Frame;
-use ratatui::layout::{Constraint, Direction, Layout, Rect};
+use ratatui::layout::{Constraint, Direction, Layout, Rect, Size};
 use ratatui::style::{Color, Modifier, Style, Stylize};
 use ratatui::text::{Line, Text};
 use ratatui::widgets::{Block, Borders, Paragraph};
 use reqwest::StatusCode;
-use reqwest::blocking::Client;
-use serde::{Deserialize, Serialize};
+use serde::Deserialize;
 use std::sync::mpsc;
 use std::sync::mpsc::{Receiver, Sender};
 use std::thread;
 use std::time::Duration;
-use tracing::error;
+use tokio::sync::mpsc::UnboundedSender;
+use tracing::{error, warn};
 
 pub(crate) struct Login {
     mode_holder: ModeHolderLock,
     user_name_input: UserInput,
     password_input: UserInput,
     state: State,
+    /// 注册页面，为 None 时显示登录页面
+    register_form: Option<RegisterForm>,
+    /// 是否记住登录
+    remember: bool,
+    /// 系统密钥环是否可用，开启记住登录时检测
+    keyring_available: Option<bool>,
+    /// 密钥环不可用时，加密会话文件的口令
+    passphrase_input: UserInput,
+    /// 启动时需要输入口令解锁已保存的会话
+    unlocking: bool,
+    /// 使用加密文件保存、等待解锁的会话
+    locked: Vec<SessionKey>,
+    action_tx: Option<UnboundedSender<Action>>,
+    /// 配置文件中的服务器
+    profiles: Vec<ServerProfile>,
+    /// 当前选择的服务器
+    profile_idx: usize,
+    /// 添加账号前使用的服务器，取消添加时恢复
+    previous_profile: Option<Option<ServerProfile>>,
     // 终止程序信号
     quit_tx: Option<Sender<()>>,
 }
@@ -43,10 +69,233 @@ impl Login {
                 data: None,
             }),
             state: State::Normal,
+            register_form: None,
+            remember: false,
+            keyring_available: None,
+            passphrase_input: UserInput::new(InputData::Password {
+                label: Some("会话加密口令".to_string()),
+                data: None,
+            }),
+            unlocking: false,
+            locked: Vec::new(),
+            action_tx: None,
+            profiles: vec![],
+            profile_idx: 0,
+            previous_profile: None,
             quit_tx: None,
         }
     }
 
+    /// 密钥环不可用时，记住登录需要输入口令
+    fn need_passphrase(&self) -> bool {
+        self.unlocking || (self.remember && self.keyring_available == Some(false))
+    }
+
+    fn toggle_remember(&mut self) {
+        self.remember = !self.remember;
+        if self.remember && self.keyring_available.is_none() {
+            self.keyring_available = Some(session::keyring_available());
+        }
+    }
+
+    /// 选择服务器，并填写该服务器配置的用户名
+    fn select_profile(&mut self, idx: usize) {
+        let Some(profile) = self.profiles.get(idx).cloned() else {
+            return;
+        };
+        self.profile_idx = idx;
+        self.user_name_input.reset();
+        if let Some(user_name) = &profile.user_name {
+            self.user_name_input.set_input(user_name.clone());
+            self.user_name_input.submit_message();
+        }
+        HOST.set_profile(Some(profile));
+    }
+
+    /// 使用保存的会话对应的服务器，配置已删除时返回 false
+    fn use_profile(&mut self, name: Option<&str>) -> bool {
+        let Some(name) = name else {
+            HOST.set_profile(None);
+            return true;
+        };
+        match self.profiles.iter().position(|p| p.name == name) {
+            Some(idx) => {
+                self.select_profile(idx);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// 切换到下一个服务器
+    fn next_profile(&mut self) {
+        if !self.profiles.is_empty() {
+            self.select_profile((self.profile_idx + 1) % self.profiles.len());
+        }
+    }
+
+    /// 已登录时添加新账号，显示登录页面
+    fn add_account(&mut self) {
+        self.previous_profile = Some(HOST.profile());
+        self.password_input.reset();
+        self.remember = false;
+        self.select_profile(self.profile_idx);
+        self.mode_holder.set_mode(Mode::Login);
+    }
+
+    /// 取消添加账号，返回当前账号
+    fn cancel_add_account(&mut self) {
+        if let Some(profile) = self.previous_profile.take() {
+            HOST.set_profile(profile);
+            self.mode_holder.set_mode(Mode::RecentChat);
+        }
+    }
+
+    /// 登录成功后设置当前用户，并启动 token 续期
+    fn on_login(&mut self, user: User, token: String) -> Action {
+        // 已登录的账号转入后台
+        ACCOUNTS.suspend_active();
+        CURRENT_USER.set_user(Some(user), Some(token));
+        ACCOUNTS.add_active();
+        REMARKS.load_local();
+        E2E.init();
+        // 续期线程始终续期当前账号，只需启动一次
+        if self.quit_tx.is_none() {
+            let (quit_tx, quit_rx) = mpsc::channel();
+            self.quit_tx = Some(quit_tx);
+            renew(quit_rx);
+        }
+        self.previous_profile = None;
+        self.password_input.reset();
+        self.unlocking = false;
+        self.passphrase_input.reset();
+        self.mode_holder.set_mode(Mode::RecentChat);
+        Action::LoginSuccess
+    }
+
+    /// 退出当前账号：注销 token、删除保存的会话并清空缓存。
+    /// 还有其他已登录的账号时切换到该账号，否则返回登录页面
+    fn logout(&mut self) -> Option<Action> {
+        if let Err(err) = token_api::logout() {
+            warn!("fail to revoke token: {err}");
+        }
+        if let Err(err) = ACCOUNTS.remember(None) {
+            warn!("fail to forget session: {err}");
+        }
+        let has_others = ACCOUNTS.remove_active();
+        CURRENT_USER.set_user(None, None);
+        reset_caches();
+        self.register_form = None;
+        self.previous_profile = None;
+        self.remember = false;
+        self.state = State::Normal;
+        self.password_input.reset();
+        if has_others && ACCOUNTS.switch(0) {
+            self.mode_holder.set_mode(Mode::RecentChat);
+            return Some(Action::LoginSuccess);
+        }
+        // 停止 token 续期
+        if let Some(quit_tx) = self.quit_tx.take() {
+            let _ = quit_tx.send(());
+        }
+        self.select_profile(self.profile_idx);
+        self.mode_holder.set_mode(Mode::Login);
+        None
+    }
+
+    /// 恢复保存的会话，token 仍有效时续期后直接登录，否则删除保存的会话
+    fn resume(&mut self, key: SessionKey, store: Store, saved: SavedSession) -> Option<Action> {
+        if !self.use_profile(key.profile.as_deref()) {
+            warn!("profile of saved session not found: {key:?}");
+            return None;
+        }
+        self.user_name_input.set_input(saved.user_name.clone());
+        self.user_name_input.submit_message();
+        if token::parse_token(&saved.token).is_err() {
+            session::forget(&key);
+            return Some(Action::Alert("登录已过期，请重新登录".to_string(), None));
+        }
+        let token = saved.token.clone();
+        match proxy::send_request(move || renew_token(&token)) {
+            Ok(Ok(token)) => {
+                let token_data = match token::parse_token(&token) {
+                    Ok(token_data) if token_data.claims.id == key.uid => token_data,
+                    Ok(_) => {
+                        session::forget(&key);
+                        return Some(Action::Alert("保存的会话与账号不符".to_string(), None));
+                    }
+                    Err(err) => {
+                        session::forget(&key);
+                        return Some(Action::Alert(format!("登录失败：{err}"), None));
+                    }
+                };
+                let action = self.on_login(token_data.claims, token);
+                // 保存续期后的 token
+                if let Err(err) = ACCOUNTS.remember(Some(store)) {
+                    warn!("fail to save session: {err}");
+                }
+                Some(action)
+            }
+            Ok(Err(err)) | Err(err) => {
+                error!("fail to renew saved session: {err}");
+                session::forget(&key);
+                Some(Action::Alert("登录已过期，请重新登录".to_string(), None))
+            }
+        }
+    }
+
+    /// 使用输入的口令解锁所有能解密的会话文件
+    fn unlock(&mut self) -> Option<Action> {
+        self.passphrase_input.submit_message();
+        let passphrase = self.passphrase_input.data().unwrap_or_default();
+        let mut unlocked = Vec::new();
+        let mut last_err = None;
+        for key in std::mem::take(&mut self.locked) {
+            match session::load_from_file(&key, &passphrase) {
+                Ok(saved) => unlocked.push((key, saved)),
+                Err(err) => {
+                    last_err = Some(err);
+                    self.locked.push(key);
+                }
+            }
+        }
+        if unlocked.is_empty() {
+            return last_err.map(|err| Action::Alert(err.to_string(), None));
+        }
+        let store = Store::File { passphrase };
+        let mut actions = unlocked
+            .into_iter()
+            .filter_map(|(key, saved)| self.resume(key, store.clone(), saved))
+            .collect::<Vec<_>>();
+        self.locked.clear();
+        self.unlocking = false;
+        let last = actions.pop();
+        self.send_actions(actions);
+        last
+    }
+
+    fn send_actions(&self, actions: Vec<Action>) {
+        if let Some(tx) = &self.action_tx {
+            for action in actions {
+                let _ = tx.send(action);
+            }
+        }
+    }
+
+    /// 根据记住登录选项确定会话的保存位置，不记住时为 None
+    fn session_store(&self) -> Option<Store> {
+        if !self.remember {
+            return None;
+        }
+        if self.keyring_available == Some(true) {
+            Some(Store::Keyring)
+        } else {
+            Some(Store::File {
+                passphrase: self.passphrase_input.data().unwrap_or_default(),
+            })
+        }
+    }
+
     fn next_state(&mut self) {
         match self.state {
             State::Normal => {
@@ -58,10 +307,19 @@ impl Login {
                 self.user_name_input.is_editing = false;
                 self.password_input.is_editing = true;
             }
+            State::PasswordEditing if self.need_passphrase() => {
+                self.state = State::PassphraseEditing;
+                self.password_input.is_editing = false;
+                self.passphrase_input.is_editing = true;
+            }
             State::PasswordEditing => {
                 self.state = State::Normal;
                 self.password_input.is_editing = false;
             }
+            State::PassphraseEditing => {
+                self.state = State::Normal;
+                self.passphrase_input.is_editing = false;
+            }
         }
     }
 }
@@ -71,39 +329,7 @@ enum State {
     Normal,
     UserNameEditing,
     PasswordEditing,
-}
-
-/// Register New User
-#[derive(Debug, Serialize, Deserialize)]
-struct UserRegisterReq {
-    /// name
-    name: String,
-    /// email
-    email: Option<String>,
-    /// password
-    password: String,
-    /// phone
-    phone: Option<String>,
-}
-
-fn register(req: UserRegisterReq) -> color_eyre::Result<i32> {
-    let register_url = format!("{}/user/register", HOST.as_str());
-    let client = Client::new();
-    let response = client.post(register_url).json(&req).send();
-
-    match response {
-        Ok(res) => {
-            if res.status().is_success() {
-                match res.text() {
-                    Ok(uid) => Ok(uid.parse::<i32>()?),
-                    Err(e) => Err(format_err!("Failed to parse response: {}", e)),
-                }
-            } else {
-                Err(format_err!("Register failed: HTTP {}", res.status()))
-            }
-        }
-        Err(e) => Err(format_err!("Failed to send register request: {}", e)),
-    }
+    PassphraseEditing,
 }
 
 struct LoginReq {
@@ -117,8 +343,8 @@ struct LoginRes {
 }
 
 fn login(login: LoginReq) -> color_eyre::Result<String> {
-    let url = format!("{}/token/login", HOST.as_str());
-    let client = Client::new();
+    let url = format!("{}/token/login", HOST.url());
+    let client = proxy::client();
     let response = client
         .post(url)
         .json(&serde_json::json!({
@@ -144,6 +370,26 @@ fn login(login: LoginReq) -> color_eyre::Result<String> {
     }
 }
 
+/// 续期 token，返回新的 token
+fn renew_token(token: &str) -> color_eyre::Result<String> {
+    let renew_url = format!("{}/token/renew", HOST.url());
+    let response = proxy::client()
+        .patch(renew_url)
+        .header("Authorization", format!("Bearer {token}"))
+        .send();
+    match response {
+        Ok(res) => {
+            if res.status().is_success() {
+                res.text()
+                    .map_err(|e| format_err!("Failed to parse response: {}", e))
+            } else {
+                Err(format_err!("Token refresh failed: HTTP {}", res.status()))
+            }
+        }
+        Err(err) => Err(format_err!("Failed to send token refresh request: {}", err)),
+    }
+}
+
 fn renew(quit_rx: Receiver<()>) {
     // 启动异步线程，定时刷新token过期时间
     thread::spawn(move || {
@@ -153,38 +399,22 @@ fn renew(quit_rx: Receiver<()>) {
                     break;
                 }
                 Err(_) => {
-                    let token = match CURRENT_USER.get_user().token.clone() {
-                        None => {
-                            break;
-                        }
-                        Some(token) => token,
+                    let Some((key, token)) = ACCOUNTS.active_session() else {
+                        break;
                     };
-                    let token = format!("Bearer {token}");
-                    let renew_url = format!("{}/token/renew", HOST.as_str());
-                    let client = Client::new();
-                    let response = client
-                        .patch(renew_url)
-                        .header("Authorization", token.clone())
-                        .send();
-                    match response {
-                        Ok(res) => {
-                            if res.status().is_success() {
-                                match res.text() {
-                                    Ok(t) => {
-                                        let token_data = token::parse_token(t.as_str()).unwrap();
-                                        CURRENT_USER.set_user(Some(token_data.claims), Some(t));
-                                    }
-                                    Err(e) => {
-                                        error!("Failed to parse response: {}", e);
-                                    }
+                    match renew_token(&token) {
+                        Ok(t) => {
+                            let token_data = match token::parse_token(t.as_str()) {
+                                Ok(token_data) => token_data,
+                                Err(err) => {
+                                    error!("Failed to parse renewed token: {}", err);
+                                    continue;
                                 }
-                            } else {
-                                error!("Token refresh failed: HTTP {}", res.status());
-                            }
-                        }
-                        Err(err) => {
-                            error!("Failed to send token refresh request: {}", err);
+                            };
+                            // 只更新续期的账号，记住登录时同步更新保存的 token
+                            ACCOUNTS.renewed(&key, token_data.claims, t);
                         }
+                        Err(err) => error!("{err}"),
                     }
                 }
             };
@@ -193,14 +423,84 @@ fn renew(quit_rx: Receiver<()>) {
 }
 
 impl Component for Login {
+    fn register_action_handler(&mut self, tx: UnboundedSender<Action>) -> color_eyre::Result<()> {
+        self.action_tx = Some(tx);
+        Ok(())
+    }
+
+    /// 默认使用第一个服务器配置
+    fn register_config_handler(&mut self, config: Config) -> color_eyre::Result<()> {
+        self.profiles = config.profiles;
+        self.select_profile(0);
+        Ok(())
+    }
+
+    /// 启动时恢复所有记住的会话：直接读取系统密钥环中的会话，加密文件中的会话提示输入口令解锁
+    fn init(&mut self, _area: Size) -> color_eyre::Result<()> {
+        let mut actions = Vec::new();
+        for remembered in session::remembered() {
+            if !remembered.keyring {
+                self.locked.push(remembered.key);
+                continue;
+            }
+            self.keyring_available = Some(true);
+            if let Some(saved) = session::load_from_keyring(&remembered.key) {
+                actions.extend(self.resume(remembered.key, Store::Keyring, saved));
+            }
+        }
+        self.send_actions(actions);
+        if !self.locked.is_empty() {
+            // 已恢复其他账号时，可按 Esc 跳过解锁返回该账号
+            if CURRENT_USER.get_user().user.is_some() {
+                self.previous_profile = Some(HOST.profile());
+                self.mode_holder.set_mode(Mode::Login);
+            }
+            self.unlocking = true;
+            self.remember = true;
+            self.keyring_available = Some(false);
+            self.state = State::PassphraseEditing;
+            self.passphrase_input.is_editing = true;
+        }
+        Ok(())
+    }
+
     fn handle_key_event(&mut self, key: KeyEvent) -> color_eyre::Result<Option<Action>> {
         if self.mode_holder.get_mode() == Mode::Login {
+            if let Some(form) = self.register_form.as_mut() {
+                if key.code == KeyCode::Esc {
+                    self.register_form = None;
+                } else {
+                    form.handle_key_event(key);
+                }
+                return Ok(None);
+            }
             match self.state {
-                State::Normal => {
-                    if let KeyCode::Char('e') = key.code {
+                State::Normal => match key.code {
+                    KeyCode::Char('e') => self.next_state(),
+                    KeyCode::Char('m') => self.toggle_remember(),
+                    KeyCode::Char('p') => self.next_profile(),
+                    KeyCode::Esc => self.cancel_add_account(),
+                    _ => {}
+                },
+                State::PassphraseEditing => match key.code {
+                    KeyCode::Enter if self.unlocking => return Ok(self.unlock()),
+                    KeyCode::Enter => {
+                        self.passphrase_input.submit_message();
                         self.next_state();
                     }
-                }
+                    KeyCode::Char(to_insert) => self.passphrase_input.enter_char(to_insert),
+                    KeyCode::Backspace => self.passphrase_input.delete_char(),
+                    KeyCode::Left => self.passphrase_input.move_cursor_left(),
+                    KeyCode::Right => self.passphrase_input.move_cursor_right(),
+                    // 跳过解锁，使用用户名密码登录
+                    KeyCode::Esc if self.unlocking => {
+                        self.unlocking = false;
+                        self.remember = false;
+                        self.passphrase_input.reset();
+                        self.next_state();
+                    }
+                    _ => {}
+                },
                 State::UserNameEditing => match key.code {
                     KeyCode::Enter => {
                         self.user_name_input.submit_message();
@@ -233,8 +533,40 @@ impl Component for Login {
             self.quit_tx.clone().unwrap().send(())?;
             return Ok(None);
         }
+        if self.mode_holder.get_mode() == Mode::Login
+            && action == Action::Submit
+            && let Some(form) = self.register_form.as_mut()
+        {
+            return match form.submit() {
+                // 注册成功后直接登录
+                Ok(Some((user_name, password))) => {
+                    self.register_form = None;
+                    self.user_name_input.set_input(user_name);
+                    self.user_name_input.submit_message();
+                    self.password_input.set_input(password);
+                    self.password_input.submit_message();
+                    Ok(Some(Action::Submit))
+                }
+                Ok(None) => Ok(None),
+                Err(err) => {
+                    error!("register failed, {err}");
+                    Ok(Some(Action::Alert(format!("{err}"), None)))
+                }
+            };
+        }
+        if action == Action::AddAccount {
+            self.add_account();
+            return Ok(None);
+        }
+        if action == Action::Logout && CURRENT_USER.get_user().user.is_some() {
+            return Ok(self.logout());
+        }
         if self.mode_holder.get_mode() == Mode::Login {
             return match action {
+                Action::Submit if !HOST.is_configured() => Ok(Some(Action::Alert(
+                    "未配置服务器，请在配置文件中添加 profiles".to_string(),
+                    None,
+                ))),
                 Action::Submit => {
                     match (self.user_name_input.data(), self.password_input.data()) {
                         (None, None) => {
@@ -246,6 +578,14 @@ impl Component for Login {
                         (Some(_), None) => {
                             return Ok(Some(Action::Alert("请输入密码".to_string(), None)));
                         }
+                        _ if self.need_passphrase()
+                            && self.passphrase_input.data().is_none_or(|p| p.is_empty()) =>
+                        {
+                            return Ok(Some(Action::Alert(
+                                "系统密钥环不可用，请输入会话加密口令".to_string(),
+                                None,
+                            )));
+                        }
                         _ => {
                             let user_name = self.user_name_input.data().unwrap();
                             let password = self.password_input.data().unwrap();
@@ -259,13 +599,26 @@ impl Component for Login {
                             })?;
                             match result {
                                 Ok(token) => {
-                                    let token_data = token::parse_token(token.as_str()).unwrap();
-                                    CURRENT_USER.set_user(Some(token_data.claims), Some(token));
-                                    let (quit_tx, quit_rx) = mpsc::channel();
-                                    self.quit_tx = Some(quit_tx);
-                                    renew(quit_rx);
-                                    self.mode_holder.set_mode(Mode::RecentChat);
-                                    Ok(Some(Action::LoginSuccess))
+                                    let token_data = match token::parse_token(&token) {
+                                        Ok(token_data) => token_data,
+                                        Err(err) => {
+                                            error!("login failed, {err}");
+                                            return Ok(Some(Action::Alert(
+                                                format!("登录失败：{err}"),
+                                                None,
+                                            )));
+                                        }
+                                    };
+                                    let store = self.session_store();
+                                    let action = self.on_login(token_data.claims, token);
+                                    if let Err(err) = ACCOUNTS.remember(store) {
+                                        error!("fail to remember session: {err}");
+                                        self.send_actions(vec![Action::Alert(
+                                            format!("记住登录失败：{err}"),
+                                            None,
+                                        )]);
+                                    }
+                                    Ok(Some(action))
                                 }
                                 Err(err) => {
                                     error!("login failed, {err}");
@@ -276,23 +629,10 @@ impl Component for Login {
                     }
                 }
                 Action::Register => {
-                    let user_name = self.user_name_input.data().unwrap();
-                    let password = self.password_input.data().unwrap();
-                    let result = proxy::send_request(|| {
-                        register(UserRegisterReq {
-                            name: user_name,
-                            email: None,
-                            password,
-                            phone: None,
-                        })
-                    })?;
-                    match result {
-                        Ok(_) => Ok(Some(Action::Submit)),
-                        Err(e) => {
-                            error!("register failed, {e}");
-                            Ok(Some(Action::Alert(format!("{e}"), None)))
-                        }
+                    if self.register_form.is_none() {
+                        self.register_form = Some(RegisterForm::new());
                     }
+                    Ok(None)
                 }
                 _ => Ok(None),
             };
@@ -311,11 +651,17 @@ impl Component for Login {
         let area = area_util::total_area(area);
         frame.render_widget(bg_block, area);
 
-        let [banner_area, help_area, user_name_area, password_area, _] = Layout::default()
+        let [
+            banner_area,
+            help_area,
+            user_name_area,
+            password_area,
+            passphrase_area,
+        ] = Layout::default()
             .direction(Direction::Vertical)
             .constraints([
                 Constraint::Min(1),
-                Constraint::Max(2),
+                Constraint::Max(4),
                 Constraint::Length(3),
                 Constraint::Length(3),
                 Constraint::Length(3),
@@ -328,6 +674,30 @@ impl Component for Login {
             .block(Block::default().borders(Borders::NONE))
             .centered();
 
+        if let Some(form) = self.register_form.as_mut() {
+            let [banner_area, help_area, form_area, _] = Layout::vertical([
+                Constraint::Min(1),
+                Constraint::Max(2),
+                Constraint::Length(15),
+                Constraint::Length(1),
+            ])
+            .areas(area);
+            frame.render_widget(
+                banner_paragraph,
+                area_util::centered_rect(100, 50, banner_area),
+            );
+            let help = Line::from(vec![
+                "↑↓/Enter To Switch, ".bold(),
+                "Ctrl+S To Register, ".bold(),
+                "Esc To Back.".bold(),
+            ]);
+            frame.render_widget(
+                Paragraph::new(help),
+                area_util::centered_rect(50, 100, help_area),
+            );
+            form.draw(frame, area_util::centered_rect(50, 100, form_area));
+            return Ok(());
+        }
         frame.render_widget(
             banner_paragraph,
             area_util::centered_rect(100, 50, banner_area),
@@ -337,17 +707,35 @@ impl Component for Login {
             State::Normal => (
                 vec![
                     "Press e To Start Editing, ".bold(),
+                    "p To Switch Server, ".bold(),
                     "Ctrl+S To Login, ".bold(),
                     "Ctrl+R To Register.".bold(),
                 ],
                 Style::default().add_modifier(Modifier::RAPID_BLINK),
             ),
-            State::UserNameEditing | State::PasswordEditing => (
+            State::PassphraseEditing if self.unlocking => (
+                vec!["Input Passphrase To Unlock Saved Session, Esc To Skip.".bold()],
+                Style::default(),
+            ),
+            State::UserNameEditing | State::PasswordEditing | State::PassphraseEditing => (
                 vec!["Press Enter To Move To Next. ".into()],
                 Style::default(),
             ),
         };
-        let text = Text::from(Line::from(msg)).patch_style(style);
+        let mut text = Text::from(Line::from(msg)).patch_style(style);
+        if !self.unlocking {
+            let mark = if self.remember { "[x]" } else { "[ ]" };
+            text.push_line(Line::from(format!("{mark} 记住登录 (m)")));
+        }
+        if let Some(profile) = HOST.profile() {
+            text.push_line(Line::from(format!(
+                "服务器: {} ({})",
+                profile.name, profile.url
+            )));
+        }
+        if self.previous_profile.is_some() {
+            text.push_line(Line::from("Esc To Cancel Adding Account."));
+        }
         let help_message = Paragraph::new(text).wrap(ratatui::widgets::Wrap { trim: true }); // 添加自动换行
         frame.render_widget(help_message, area_util::centered_rect(50, 100, help_area));
 
@@ -363,6 +751,13 @@ impl Component for Login {
             .style(self.password_input.select_style())
             .block(Block::bordered().title(self.password_input.input_data.label()));
         frame.render_widget(password, password_area);
+        let passphrase_area = area_util::centered_rect(50, 100, passphrase_area);
+        if self.need_passphrase() {
+            let passphrase = Paragraph::new(self.passphrase_input.display_text())
+                .style(self.passphrase_input.select_style())
+                .block(Block::bordered().title(self.passphrase_input.input_data.label()));
+            frame.render_widget(passphrase, passphrase_area);
+        }
         match self.state {
             State::Normal => {}
             State::UserNameEditing => {
@@ -371,6 +766,9 @@ impl Component for Login {
             State::PasswordEditing => {
                 self.password_input.set_cursor_position(password_area);
             }
+            State::PassphraseEditing => {
+                self.passphrase_input.set_cursor_position(passphrase_area);
+            }
         }
         Ok(())
     }
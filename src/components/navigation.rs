@@ -1,18 +1,28 @@
+use crate::account::ACCOUNTS;
 use crate::action::Action;
 use crate::app::{Mode, ModeHolderLock};
+use crate::components::recent_chat::UNREAD;
 use crate::components::{Component, area_util};
+use crate::proxy::friend::PENDING_FRIEND_REQS;
 use ratatui::Frame;
 use ratatui::layout::{Alignment, Rect};
 use ratatui::style::palette::tailwind;
 use ratatui::style::{Color, Style, Stylize};
 use ratatui::text::Line;
 use ratatui::widgets::{Block, Borders, Tabs};
+use std::sync::atomic::Ordering;
+use std::time::{Duration, Instant};
 use strum::{Display, EnumIter, FromRepr, IntoEnumIterator};
 use unicode_width::UnicodeWidthStr;
 
+/// 通知的显示时长
+const TOAST_DURATION: Duration = Duration::from_secs(5);
+
 pub(crate) struct Navigation {
     mode_holder: ModeHolderLock,
     item: NavigationItem,
+    /// 新消息通知，显示在导航栏右侧
+    toast: Option<(String, Instant)>,
 }
 
 impl Navigation {
@@ -20,11 +30,12 @@ impl Navigation {
         Self {
             mode_holder,
             item: NavigationItem::RecentChat,
+            toast: None,
         }
     }
 
     fn next(&mut self) {
-        self.item.circle();
+        self.item = self.item.circle();
         let next_mode = match self.mode_holder.get_mode() {
             Mode::RecentChat => Mode::Contact,
             Mode::Contact => Mode::Setting,
@@ -54,9 +65,21 @@ impl NavigationItem {
         }
     }
 
+    /// Tab's name with badge
+    fn label(self) -> String {
+        let pending = PENDING_FRIEND_REQS.load(Ordering::SeqCst);
+        // 包含后台账号的未读消息
+        let unread = UNREAD.load(Ordering::SeqCst) + ACCOUNTS.background_unread();
+        match self {
+            NavigationItem::RecentChat if unread > 0 => format!("{self}({unread})"),
+            NavigationItem::Contact if pending > 0 => format!("{self}({pending})"),
+            _ => self.to_string(),
+        }
+    }
+
     /// Return tab's name as a styled `Line`
     pub(crate) fn title(self) -> Line<'static> {
-        format!("  {self}  ")
+        format!("  {}  ", self.label())
             .fg(tailwind::SLATE.c200)
             .bg(self.palette().c900)
             .into()
@@ -73,6 +96,22 @@ impl NavigationItem {
 
 impl Component for Navigation {
     fn update(&mut self, action: Action) -> color_eyre::Result<Option<Action>> {
+        if let Action::FriendRequest(req) = &action {
+            self.toast = Some((
+                format!("🔔 {}请求添加您为好友", req.from_name),
+                Instant::now(),
+            ));
+            return Ok(None);
+        }
+        if matches!(action, Action::LoginSuccess | Action::Logout) {
+            self.item = NavigationItem::RecentChat;
+            return Ok(None);
+        }
+        if let Action::ToChat(_) = action {
+            // 跳转到聊天时，同步切换到最近聊天标签
+            self.item = NavigationItem::RecentChat;
+            return Ok(None);
+        }
         match self.mode_holder.get_mode() {
             Mode::RecentChat | Mode::Contact | Mode::Setting => {
                 if let Action::NextTab = action {
@@ -92,15 +131,24 @@ impl Component for Navigation {
                 let highlight_style = (Color::default(), self.item.palette().c700);
                 let selected_tab_index = self.item as usize;
                 let padding = cal_padding(&navigation_area);
+                let title = match ACCOUNTS.active_label() {
+                    Some(label) => format!("Chat-Tui - {label}"),
+                    None => "Chat-Tui".to_string(),
+                };
+                let mut block = Block::default()
+                    .title(title)
+                    .title_style(Style::default().fg(Color::Green))
+                    .borders(Borders::BOTTOM)
+                    .border_style(Style::default().fg(Color::Green))
+                    .title_alignment(Alignment::Center);
+                self.toast
+                    .take_if(|(_, time)| time.elapsed() >= TOAST_DURATION);
+                if let Some((toast, _)) = &self.toast {
+                    block =
+                        block.title(Line::from(toast.clone().fg(Color::Yellow)).right_aligned());
+                }
                 let tabs = Tabs::new(titles)
-                    .block(
-                        Block::default()
-                            .title("Chat-Tui")
-                            .title_style(Style::default().fg(Color::Green))
-                            .borders(Borders::BOTTOM)
-                            .border_style(Style::default().fg(Color::Green))
-                            .title_alignment(Alignment::Center),
-                    )
+                    .block(block)
                     .highlight_style(highlight_style)
                     .select(selected_tab_index)
                     .padding(" ".repeat(padding), " ".repeat(padding))
@@ -116,7 +164,7 @@ impl Component for Navigation {
 fn cal_padding(area: &Rect) -> usize {
     let width = area.width as usize;
     let navi_width = NavigationItem::iter()
-        .map(|item| item.to_string().as_str().width_cjk() + 4)
+        .map(|item| item.label().as_str().width_cjk() + 4)
         .sum::<usize>()
         + 2;
     let len = NavigationItem::iter().len();
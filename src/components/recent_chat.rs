@@ -5,8 +5,15 @@ use crate::components::contact::ToChat;
 use crate::components::event::{ChatMessage, MessageTarget};
 use crate::components::{Component, area_util};
 use crate::datetime::datetime_format;
+use crate::e2e::E2E;
 use crate::proxy;
-use crate::proxy::{HOST, user};
+use crate::proxy::block::BLOCKED;
+use crate::proxy::directory::USER_DIRECTORY;
+use crate::proxy::file::Attachment;
+use crate::proxy::forward::Forwarded;
+use crate::proxy::presence::PRESENCES;
+use crate::proxy::remark::REMARKS;
+use crate::proxy::{HOST, presence};
 use crate::token::CURRENT_USER;
 use chrono::{DateTime, Local};
 use color_eyre::eyre::format_err;
@@ -17,12 +24,15 @@ use ratatui::style::{Color, Modifier, Style};
 use ratatui::text::{Line, Span, Text};
 use ratatui::widgets::{Block, Borders, HighlightSpacing, List, ListItem, ListState};
 use ratatui::{Frame, symbols};
-use reqwest::blocking::Client;
 use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::{Arc, Mutex};
 use tokio::sync::broadcast::Receiver;
 use tracing::{debug, error, info};
 
+// 当前账号的未读消息总数，显示在导航栏
+pub(crate) static UNREAD: AtomicUsize = AtomicUsize::new(0);
+
 pub(crate) struct RecentChat {
     mode_holder: ModeHolderLock,
     chat_vos: Arc<Mutex<Vec<ChatVo>>>,
@@ -72,6 +82,17 @@ pub(crate) enum ChatVo {
 }
 
 impl ChatVo {
+    /// 未读消息数，超出上限（all）时按 1 计算
+    fn unread_count(&self) -> usize {
+        let unread = match self {
+            ChatVo::User { unread, .. } | ChatVo::Group { unread, .. } => unread,
+        };
+        unread
+            .as_ref()
+            .map(|count| count.parse().unwrap_or(1))
+            .unwrap_or(0)
+    }
+
     pub(crate) fn reset_unread(&mut self) -> Option<()> {
         match self {
             ChatVo::User { unread, .. } => {
@@ -94,7 +115,7 @@ impl ChatVo {
                 ..
             } => {
                 *mid = chat_message.mid;
-                *msg = chat_message.payload.detail.get_content();
+                *msg = preview(chat_message);
                 *msg_time = chat_message.payload.created_at;
                 if !is_selected {
                     *unread = update_unread(unread)
@@ -109,9 +130,10 @@ impl ChatVo {
                 unread,
                 ..
             } => {
-                *user_name = user::detail_by_id(*uid).unwrap().name.clone();
+                *uid = chat_message.payload.from_uid;
+                *user_name = USER_DIRECTORY.name(*uid);
                 *mid = chat_message.mid;
-                *msg = chat_message.payload.detail.get_content();
+                *msg = preview(chat_message);
                 *msg_time = chat_message.payload.created_at;
                 if !is_selected {
                     *unread = update_unread(unread)
@@ -120,6 +142,19 @@ impl ChatVo {
         }
     }
 }
+/// 消息预览，附件显示文件名，转发的消息显示原发送者
+fn preview(chat_message: &ChatMessage) -> String {
+    let detail = &chat_message.payload.detail;
+    let content = detail.get_content();
+    if let Some(forwarded) = Forwarded::from_msg(detail.content_type(), &content) {
+        return forwarded.summary();
+    }
+    match Attachment::from_msg(detail.content_type(), &content) {
+        Some(attachment) => attachment.summary(),
+        None => content,
+    }
+}
+
 fn update_unread(unread: &mut Option<String>) -> Option<String> {
     match unread {
         None => Some("1".to_string()),
@@ -131,10 +166,10 @@ fn update_unread(unread: &mut Option<String>) -> Option<String> {
     }
 }
 
-fn fetch_recent_chats() -> color_eyre::Result<Vec<ChatVo>> {
-    let url = format!("{}/user/history", HOST.as_str());
+pub(crate) fn fetch_recent_chats() -> color_eyre::Result<Vec<ChatVo>> {
+    let url = format!("{}/user/history", HOST.url());
     let token = CURRENT_USER.get_user().token.clone().unwrap();
-    let res = Client::new()
+    let res = proxy::client()
         .post(url)
         .json(&serde_json::json!({
             "page": 1,
@@ -158,7 +193,7 @@ impl From<&ChatVo> for Text<'_> {
     fn from(value: &ChatVo) -> Self {
         match value {
             ChatVo::User {
-                uid: _uid,
+                uid,
                 user_name,
                 msg,
                 msg_time,
@@ -166,10 +201,13 @@ impl From<&ChatVo> for Text<'_> {
                 ..
             } => {
                 let mut content = vec![
-                    Line::from(Span::styled(
-                        format!("好友: {}\n", user_name),
-                        Style::default().fg(Color::White),
-                    )),
+                    Line::from(vec![
+                        PRESENCES.get(*uid).dot(),
+                        Span::styled(
+                            format!("好友: {}\n", REMARKS.display_name(*uid, user_name)),
+                            Style::default().fg(Color::White),
+                        ),
+                    ]),
                     Line::from(Span::styled(
                         format!("时间: {}\n", msg_time),
                         Style::default().fg(Color::White),
@@ -190,6 +228,7 @@ impl From<&ChatVo> for Text<'_> {
             ChatVo::Group {
                 gid: _gid,
                 group_name,
+                uid,
                 user_name,
                 msg,
                 msg_time,
@@ -206,7 +245,7 @@ impl From<&ChatVo> for Text<'_> {
                         Style::default().fg(Color::White),
                     )),
                     Line::from(Span::styled(
-                        format!("{}: {}\n", user_name, msg),
+                        format!("{}: {}\n", REMARKS.display_name(*uid, user_name), msg),
                         Style::default().fg(Color::Green),
                     )),
                 ];
@@ -246,24 +285,23 @@ impl RecentChat {
                     MessageTarget::User(target_user) => {
                         let mut guard = chat_vos.lock().unwrap();
                         guard.iter_mut().enumerate().for_each(|(idx, c)| {
-                            if let ChatVo::User { uid, .. } = c {
-                                if *uid == target_user.uid || *uid == chat_message.payload.from_uid
-                                {
-                                    c.update(&chat_message, selected_idx == idx);
-                                }
+                            if let ChatVo::User { uid, .. } = c
+                                && (*uid == target_user.uid
+                                    || *uid == chat_message.payload.from_uid)
+                            {
+                                c.update(&chat_message, selected_idx == idx);
                             }
                         });
                     }
                     MessageTarget::Group(target_group) => {
                         let mut guard = chat_vos.lock().unwrap();
                         guard.iter_mut().enumerate().for_each(|(idx, c)| {
-                            if let ChatVo::Group { gid, .. } = c {
-                                if *gid == target_group.gid
+                            if let ChatVo::Group { gid, .. } = c
+                                && (*gid == target_group.gid
                                     || CURRENT_USER.get_user().user.unwrap().id
-                                        == chat_message.payload.from_uid
-                                {
-                                    c.update(&chat_message, selected_idx == idx);
-                                }
+                                        == chat_message.payload.from_uid)
+                            {
+                                c.update(&chat_message, selected_idx == idx);
                             }
                         });
                     }
@@ -272,6 +310,70 @@ impl RecentChat {
         });
     }
 
+    fn fetch(&mut self) -> color_eyre::Result<()> {
+        let arc = self.chat_vos.clone();
+        proxy::send_request(move || match fetch_recent_chats() {
+            Ok(items) => {
+                items.iter().for_each(|c| info!("chatVo:{:?}", c));
+                items.iter().for_each(|c| match c {
+                    ChatVo::User { uid, user_name, .. } => {
+                        USER_DIRECTORY.seed(*uid, user_name.clone())
+                    }
+                    ChatVo::Group { uid, user_name, .. } => {
+                        USER_DIRECTORY.seed(*uid, user_name.clone())
+                    }
+                });
+                let uids = items
+                    .iter()
+                    .filter_map(|c| match c {
+                        ChatVo::User { uid, .. } => Some(*uid),
+                        ChatVo::Group { .. } => None,
+                    })
+                    .collect();
+                if let Err(err) = presence::fetch(uids) {
+                    error!("fail to fetch presence: {err}");
+                }
+                let mut chat_vos = arc.lock().unwrap();
+                // 不展示与屏蔽用户的会话
+                *chat_vos = items
+                    .into_iter()
+                    .filter(|c| match c {
+                        ChatVo::User { uid, .. } => !BLOCKED.contains(*uid),
+                        ChatVo::Group { .. } => true,
+                    })
+                    .map(|mut c| {
+                        if let ChatVo::User { uid, msg, .. } = &mut c {
+                            *msg = E2E.decrypt(*uid, msg);
+                        }
+                        c
+                    })
+                    .collect();
+            }
+            Err(err) => {
+                error!("fail to fetch recent chat: {err}");
+            }
+        })
+    }
+
+    /// 重新获取列表后，根据当前会话恢复选中项
+    fn reselect(&mut self) {
+        let mut chat_vo_holder = CHAT_VO.lock().unwrap();
+        let current = chat_vo_holder.chat_vo();
+        let chat_vos = self.chat_vos.lock().unwrap();
+        let idx = current.and_then(|current| {
+            chat_vos.iter().position(|c| match (c, &current) {
+                (ChatVo::User { uid, .. }, ChatVo::User { uid: current, .. }) => uid == current,
+                (ChatVo::Group { gid, .. }, ChatVo::Group { gid: current, .. }) => gid == current,
+                _ => false,
+            })
+        });
+        match idx {
+            Some(idx) => chat_vo_holder.set_chat_vo(chat_vos[idx].clone()),
+            None => chat_vo_holder.clear(),
+        }
+        self.list_state.lock().unwrap().select(idx);
+    }
+
     fn send_chat(&mut self) -> color_eyre::Result<Option<Action>> {
         let mut chat_vos = self.chat_vos.lock().unwrap();
         match self.list_state.lock().unwrap().selected() {
@@ -311,17 +413,27 @@ impl Component for RecentChat {
 
     fn update(&mut self, action: Action) -> color_eyre::Result<Option<Action>> {
         if action == Action::LoginSuccess && CURRENT_USER.get_user().user.is_some() {
-            let arc = self.chat_vos.clone();
-            proxy::send_request(move || match fetch_recent_chats() {
-                Ok(items) => {
-                    items.iter().for_each(|c| info!("chatVo:{:?}", c));
-                    let mut chat_vos = arc.lock().unwrap();
-                    *chat_vos = items;
-                }
-                Err(err) => {
-                    error!("fail to fetch recent chat: {err}");
-                }
-            })?;
+            // 切换账号后清除上一个账号的选中项
+            self.list_state.lock().unwrap().select(None);
+            self.fetch()?;
+        }
+        if action == Action::Logout {
+            self.chat_vos.lock().unwrap().clear();
+            self.list_state.lock().unwrap().select(None);
+        }
+        if action == Action::Tick {
+            let unread = self
+                .chat_vos
+                .lock()
+                .unwrap()
+                .iter()
+                .map(ChatVo::unread_count)
+                .sum();
+            UNREAD.store(unread, Ordering::SeqCst);
+        }
+        if action == Action::RefreshRecentChat {
+            self.fetch()?;
+            self.reselect();
         }
         if let Action::ToChat(to_chat) = action {
             self.mode_holder.set_mode(Mode::RecentChat);
@@ -359,7 +471,8 @@ impl Component for RecentChat {
                             msg_time: Default::default(),
                             unread: None,
                         };
-                        self.chat_vos.lock().unwrap().insert(0, chat_vo)
+                        self.chat_vos.lock().unwrap().insert(0, chat_vo);
+                        self.list_state.lock().unwrap().select(Some(0));
                     }
                     ToChat::Group(gid, group_name) => {
                         let vo = ChatVo::Group {
@@ -372,7 +485,8 @@ impl Component for RecentChat {
                             msg_time: Default::default(),
                             unread: None,
                         };
-                        self.chat_vos.lock().unwrap().insert(0, vo)
+                        self.chat_vos.lock().unwrap().insert(0, vo);
+                        self.list_state.lock().unwrap().select(Some(0));
                     }
                 },
                 Some(idx) => self.list_state.lock().unwrap().select(Some(idx)),
@@ -0,0 +1,364 @@
+use crate::components::user_input::{InputData, UserInput};
+use crate::proxy;
+use crate::proxy::HOST;
+use color_eyre::eyre::format_err;
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::Frame;
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Color, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Paragraph};
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use strum::{Display, EnumIter, IntoEnumIterator};
+
+/// 注册表单的字段，顺序即输入框顺序
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, EnumIter)]
+pub(crate) enum Field {
+    Name,
+    Password,
+    Confirm,
+    Email,
+    Phone,
+}
+
+impl Field {
+    fn label(&self) -> &'static str {
+        match self {
+            Field::Name => "用户名",
+            Field::Password => "密码",
+            Field::Confirm => "确认密码",
+            Field::Email => "邮箱(选填)",
+            Field::Phone => "手机(选填)",
+        }
+    }
+
+    /// 服务端返回的字段名
+    fn from_server(name: &str) -> Option<Self> {
+        match name {
+            "name" => Some(Field::Name),
+            "password" => Some(Field::Password),
+            "email" => Some(Field::Email),
+            "phone" => Some(Field::Phone),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Display)]
+pub(crate) enum PasswordStrength {
+    #[strum(to_string = "弱")]
+    Weak,
+    #[strum(to_string = "中")]
+    Medium,
+    #[strum(to_string = "强")]
+    Strong,
+}
+
+impl PasswordStrength {
+    /// 按长度与字符种类（小写、大写、数字、符号）估算密码强度
+    pub(crate) fn of(password: &str) -> Self {
+        let kinds = [
+            password.chars().any(|c| c.is_ascii_lowercase()),
+            password.chars().any(|c| c.is_ascii_uppercase()),
+            password.chars().any(|c| c.is_ascii_digit()),
+            password.chars().any(|c| !c.is_ascii_alphanumeric()),
+        ]
+        .into_iter()
+        .filter(|b| *b)
+        .count();
+        let len = password.chars().count();
+        if len >= 12 && kinds >= 3 {
+            PasswordStrength::Strong
+        } else if len >= MIN_PASSWORD_LEN && kinds >= 2 {
+            PasswordStrength::Medium
+        } else {
+            PasswordStrength::Weak
+        }
+    }
+
+    fn color(&self) -> Color {
+        match self {
+            PasswordStrength::Weak => Color::Red,
+            PasswordStrength::Medium => Color::Yellow,
+            PasswordStrength::Strong => Color::Green,
+        }
+    }
+}
+
+const MIN_PASSWORD_LEN: usize = 8;
+
+/// 客户端校验，返回每个字段的错误信息
+pub(crate) fn validate(
+    name: &str,
+    password: &str,
+    confirm: &str,
+    email: &str,
+    phone: &str,
+) -> HashMap<Field, String> {
+    let mut errors = HashMap::new();
+    let name_len = name.trim().chars().count();
+    if name_len == 0 {
+        errors.insert(Field::Name, "请输入用户名".to_string());
+    } else if !(2..=20).contains(&name_len) {
+        errors.insert(Field::Name, "用户名长度应为2-20个字符".to_string());
+    }
+    if password.chars().count() < MIN_PASSWORD_LEN {
+        errors.insert(Field::Password, format!("密码至少{MIN_PASSWORD_LEN}个字符"));
+    } else if PasswordStrength::of(password) == PasswordStrength::Weak {
+        errors.insert(
+            Field::Password,
+            "密码需包含字母、数字或符号中的两种".to_string(),
+        );
+    }
+    if confirm != password {
+        errors.insert(Field::Confirm, "两次输入的密码不一致".to_string());
+    }
+    let email = email.trim();
+    if !email.is_empty()
+        && !email
+            .split_once('@')
+            .is_some_and(|(user, domain)| !user.is_empty() && domain.contains('.'))
+    {
+        errors.insert(Field::Email, "邮箱格式不正确".to_string());
+    }
+    let phone = phone.trim();
+    let digits = phone.strip_prefix('+').unwrap_or(phone);
+    let valid_phone =
+        (6..=15).contains(&digits.len()) && digits.chars().all(|c| c.is_ascii_digit());
+    if !phone.is_empty() && !valid_phone {
+        errors.insert(Field::Phone, "手机号格式不正确".to_string());
+    }
+    errors
+}
+
+/// Register New User
+#[derive(Debug, Serialize, Deserialize)]
+struct UserRegisterReq {
+    /// name
+    name: String,
+    /// email
+    email: Option<String>,
+    /// password
+    password: String,
+    /// phone
+    phone: Option<String>,
+}
+
+enum RegisterError {
+    /// 服务端返回的字段校验信息
+    Fields(HashMap<String, String>),
+    Other(color_eyre::Report),
+}
+
+fn register(req: UserRegisterReq) -> Result<i32, RegisterError> {
+    let register_url = format!("{}/user/register", HOST.url());
+    let client = proxy::client();
+    let response = client.post(register_url).json(&req).send();
+
+    match response {
+        Ok(res) => match res.status() {
+            status if status.is_success() => match res.text() {
+                Ok(uid) => uid
+                    .parse::<i32>()
+                    .map_err(|e| RegisterError::Other(e.into())),
+                Err(e) => Err(RegisterError::Other(format_err!(
+                    "Failed to parse response: {}",
+                    e
+                ))),
+            },
+            StatusCode::CONFLICT => Err(RegisterError::Fields(HashMap::from([(
+                "name".to_string(),
+                "用户名已存在".to_string(),
+            )]))),
+            StatusCode::BAD_REQUEST => {
+                let text = res.text().unwrap_or_default();
+                match serde_json::from_str::<HashMap<String, String>>(&text) {
+                    Ok(fields) => Err(RegisterError::Fields(fields)),
+                    Err(_) => Err(RegisterError::Other(format_err!("{text}"))),
+                }
+            }
+            status => Err(RegisterError::Other(format_err!(
+                "Register failed: HTTP {}",
+                status
+            ))),
+        },
+        Err(e) => Err(RegisterError::Other(format_err!(
+            "Failed to send register request: {}",
+            e
+        ))),
+    }
+}
+
+/// 注册页面
+pub(crate) struct RegisterForm {
+    inputs: Vec<UserInput>,
+    focused: usize,
+    errors: HashMap<Field, String>,
+}
+
+impl RegisterForm {
+    pub(crate) fn new() -> Self {
+        let inputs = Field::iter()
+            .map(|field| {
+                let label = Some(field.label().to_string());
+                UserInput::new(match field {
+                    Field::Password | Field::Confirm => InputData::Password { label, data: None },
+                    _ => InputData::UserName { label, data: None },
+                })
+            })
+            .collect();
+        let mut form = Self {
+            inputs,
+            focused: 0,
+            errors: HashMap::new(),
+        };
+        form.focus(0);
+        form
+    }
+
+    fn focus(&mut self, idx: usize) {
+        self.focused = idx;
+        for (i, input) in self.inputs.iter_mut().enumerate() {
+            input.is_editing = i == idx;
+        }
+    }
+
+    fn value(&self, field: Field) -> String {
+        self.inputs[field as usize]
+            .input
+            .clone()
+            .unwrap_or_default()
+    }
+
+    pub(crate) fn handle_key_event(&mut self, key: KeyEvent) {
+        let len = self.inputs.len();
+        let input = &mut self.inputs[self.focused];
+        match key.code {
+            KeyCode::Char(to_insert) => input.enter_char(to_insert),
+            KeyCode::Backspace => input.delete_char(),
+            KeyCode::Left => input.move_cursor_left(),
+            KeyCode::Right => input.move_cursor_right(),
+            KeyCode::Down | KeyCode::Enter => self.focus((self.focused + 1) % len),
+            KeyCode::Up => self.focus((self.focused + len - 1) % len),
+            _ => {}
+        }
+        if matches!(key.code, KeyCode::Char(_) | KeyCode::Backspace) {
+            // 修改后清除该字段的错误信息
+            if let Some(field) = Field::iter().nth(self.focused) {
+                self.errors.remove(&field);
+            }
+        }
+    }
+
+    /// 校验并提交注册，成功时返回用户名和密码用于登录；失败时错误信息显示在对应字段，
+    /// 无法对应到字段的错误作为 Err 返回
+    pub(crate) fn submit(&mut self) -> color_eyre::Result<Option<(String, String)>> {
+        let [name, password, confirm, email, phone] = [
+            Field::Name,
+            Field::Password,
+            Field::Confirm,
+            Field::Email,
+            Field::Phone,
+        ]
+        .map(|field| self.value(field));
+        self.errors = validate(&name, &password, &confirm, &email, &phone);
+        if !self.errors.is_empty() {
+            return Ok(None);
+        }
+        let optional = |s: String| Some(s.trim().to_string()).filter(|s| !s.is_empty());
+        let req = UserRegisterReq {
+            name: name.trim().to_string(),
+            email: optional(email),
+            password: password.clone(),
+            phone: optional(phone),
+        };
+        match crate::proxy::send_request(move || register(req))? {
+            Ok(_) => Ok(Some((name.trim().to_string(), password))),
+            Err(RegisterError::Fields(fields)) => {
+                let mut others = vec![];
+                for (field, msg) in fields {
+                    match Field::from_server(&field) {
+                        Some(field) => {
+                            self.errors.insert(field, msg);
+                        }
+                        None => others.push(msg),
+                    }
+                }
+                if others.is_empty() {
+                    Ok(None)
+                } else {
+                    Err(format_err!("{}", others.join("\n")))
+                }
+            }
+            Err(RegisterError::Other(err)) => Err(err),
+        }
+    }
+
+    pub(crate) fn draw(&mut self, frame: &mut Frame, area: Rect) {
+        let areas = Layout::vertical([Constraint::Length(3); 5]).split(area);
+        let password = self.value(Field::Password);
+        for (field, area) in Field::iter().zip(areas.iter()) {
+            let input = &mut self.inputs[field as usize];
+            let mut block = Block::bordered().title(input.input_data.label());
+            if let Some(err) = self.errors.get(&field) {
+                block = block.title_bottom(
+                    Line::from(Span::styled(err.clone(), Style::default().fg(Color::Red)))
+                        .right_aligned(),
+                );
+            } else if field == Field::Password && !password.is_empty() {
+                let strength = PasswordStrength::of(&password);
+                block = block.title_bottom(
+                    Line::from(Span::styled(
+                        format!("强度：{strength}"),
+                        Style::default().fg(strength.color()),
+                    ))
+                    .right_aligned(),
+                );
+            }
+            let paragraph = Paragraph::new(input.display_text())
+                .style(input.select_style())
+                .block(block);
+            frame.render_widget(paragraph, *area);
+            if input.is_editing {
+                input.set_cursor_position(*area);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn password_strength() {
+        assert_eq!(PasswordStrength::of("abc"), PasswordStrength::Weak);
+        assert_eq!(PasswordStrength::of("abcdefgh"), PasswordStrength::Weak);
+        assert_eq!(PasswordStrength::of("abcd1234"), PasswordStrength::Medium);
+        assert_eq!(
+            PasswordStrength::of("Abcd1234!xyz"),
+            PasswordStrength::Strong
+        );
+    }
+
+    #[test]
+    fn validate_fields() {
+        assert!(validate("alice", "abcd1234", "abcd1234", "", "").is_empty());
+        assert!(validate("alice", "abcd1234", "abcd1234", "a@b.com", "+8613800000000").is_empty());
+        let errors = validate(" ", "abc", "abd", "a@b", "12ab");
+        let mut fields = errors.keys().copied().collect::<Vec<_>>();
+        fields.sort_by_key(|f| *f as u8);
+        assert_eq!(
+            fields,
+            [
+                Field::Name,
+                Field::Password,
+                Field::Confirm,
+                Field::Email,
+                Field::Phone
+            ]
+        );
+    }
+}
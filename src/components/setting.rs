@@ -1,49 +1,605 @@
-use crate::action::Action;
+use crate::action::{Action, ConfirmEvent};
 use crate::app::{Mode, ModeHolderLock};
+use crate::components::recent_chat::SELECTED_STYLE;
+use crate::components::user_input::{InputData, UserInput};
 use crate::components::{Component, area_util};
+use crate::proxy::block::BlockedUser;
+use crate::proxy::presence::{PRESENCES, PresenceStatus};
+use crate::proxy::token::LoginSession;
+use crate::proxy::user::{UpdateUserReq, UserDetail};
+use crate::proxy::{block, presence, token, user};
 use crate::token::CURRENT_USER;
-use crossterm::event::KeyEvent;
-use ratatui::layout::{Alignment, Rect};
-use ratatui::widgets::{Block, Borders, Paragraph};
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::layout::{Alignment, Constraint, Layout, Rect};
+use ratatui::prelude::{Color, Line, Span, Style};
+use ratatui::text::Text;
+use ratatui::widgets::{Block, Borders, HighlightSpacing, List, ListItem, ListState, Paragraph};
 use ratatui::{Frame, symbols};
+use tracing::error;
 
 pub(crate) struct Setting {
     mode_holder: ModeHolderLock,
+    state: State,
+    /// 当前用户资料
+    profile: Option<UserDetail>,
+    /// 用户名、邮箱、手机输入框
+    profile_inputs: [UserInput; 3],
+    /// 原密码、新密码、确认密码输入框
+    password_inputs: [UserInput; 3],
+    /// 正在编辑的输入框
+    focused: usize,
+    /// 自定义状态输入框
+    status_input: UserInput,
+    /// 屏蔽的用户
+    blocked_users: Vec<BlockedUser>,
+    blocked_list_state: ListState,
+    /// 已登录的会话
+    sessions: Vec<LoginSession>,
+    session_list_state: ListState,
+    /// 当前操作的列表
+    list_focus: ListFocus,
+}
+
+#[derive(Default, Eq, PartialEq)]
+enum ListFocus {
+    #[default]
+    Blocked,
+    Sessions,
+}
+
+#[derive(Default, Eq, PartialEq)]
+enum State {
+    #[default]
+    Normal,
+    /// 编辑个人资料
+    Profile,
+    /// 修改密码
+    Password,
+}
+
+fn text_input(label: &str) -> UserInput {
+    UserInput::new(InputData::Search {
+        label: Some(label.to_string()),
+        data: None,
+    })
+}
+
+fn password_input(label: &str) -> UserInput {
+    UserInput::new(InputData::Password {
+        label: Some(label.to_string()),
+        data: None,
+    })
+}
+
+/// 空白输入视为未填写
+fn non_blank(input: &UserInput) -> Option<String> {
+    input
+        .data()
+        .map(|data| data.trim().to_string())
+        .filter(|data| !data.is_empty())
 }
 
 impl Setting {
     pub(crate) fn new(mode_holder: ModeHolderLock) -> Self {
-        Self { mode_holder }
+        Self {
+            mode_holder,
+            state: State::default(),
+            profile: None,
+            profile_inputs: [
+                text_input("用户名"),
+                text_input("邮箱(选填)"),
+                text_input("手机(选填)"),
+            ],
+            password_inputs: [
+                password_input("原密码"),
+                password_input("新密码"),
+                password_input("确认新密码"),
+            ],
+            focused: 0,
+            status_input: UserInput::new(InputData::Search {
+                label: Some("Press s To Edit Status Text".to_string()),
+                data: None,
+            }),
+            blocked_users: vec![],
+            blocked_list_state: ListState::default(),
+            sessions: vec![],
+            session_list_state: ListState::default(),
+            list_focus: ListFocus::default(),
+        }
+    }
+
+    fn fetch_sessions(&mut self) {
+        match token::sessions() {
+            Ok(sessions) => self.sessions = sessions,
+            Err(err) => error!("fail to fetch sessions: {err}"),
+        }
+        self.session_list_state.select(None);
+    }
+
+    fn revoke_session(&mut self, id: String) -> Option<Action> {
+        if let Err(err) = token::revoke(id) {
+            return Some(Action::Alert(err.to_string(), None));
+        }
+        self.fetch_sessions();
+        None
+    }
+
+    fn render_sessions(&mut self, frame: &mut Frame, area: Rect) {
+        let title = format!("Sessions({}), r To Revoke", self.sessions.len());
+        let items: Vec<ListItem> = self
+            .sessions
+            .iter()
+            .map(|session| {
+                let mut spans = vec![Span::styled(
+                    session
+                        .device
+                        .clone()
+                        .unwrap_or_else(|| "未知设备".to_string()),
+                    Style::default().fg(Color::White),
+                )];
+                if let Some(ip) = &session.ip {
+                    spans.push(Span::raw(format!(" {ip}")));
+                }
+                spans.push(Span::styled(
+                    format!(" 活跃于{}", session.last_active.format("%Y-%m-%d %H:%M")),
+                    Style::default().fg(Color::DarkGray),
+                ));
+                if session.current {
+                    spans.push(Span::styled(
+                        " (当前设备)",
+                        Style::default().fg(Color::Green),
+                    ));
+                }
+                ListItem::new(Line::from(spans))
+            })
+            .collect();
+        let list = List::new(items)
+            .block(self.list_block(title, ListFocus::Sessions))
+            .highlight_style(SELECTED_STYLE)
+            .highlight_spacing(HighlightSpacing::Always);
+        frame.render_stateful_widget(list, area, &mut self.session_list_state);
+    }
+
+    /// 当前操作的列表使用高亮边框
+    fn list_block(&self, title: String, focus: ListFocus) -> Block<'static> {
+        let border_style = if self.list_focus == focus {
+            Style::default().fg(Color::Green)
+        } else {
+            Style::default()
+        };
+        Block::new()
+            .title(title)
+            .title_alignment(Alignment::Center)
+            .borders(Borders::ALL)
+            .border_style(border_style)
+            .border_set(symbols::border::ROUNDED)
+    }
+
+    fn fetch_blocked_users(&mut self) {
+        match block::blocked_users() {
+            Ok(users) => self.blocked_users = users,
+            Err(err) => error!("fail to fetch blocked users: {err}"),
+        }
+        self.blocked_list_state.select(None);
+    }
+
+    fn fetch_profile(&mut self) {
+        let Some(current) = CURRENT_USER.get_user().user else {
+            return;
+        };
+        match user::detail_by_id(current.id) {
+            Ok(detail) => self.profile = Some(detail),
+            Err(err) => error!("fail to fetch profile: {err}"),
+        }
+    }
+
+    fn inputs(&mut self) -> Option<&mut [UserInput; 3]> {
+        match self.state {
+            State::Normal => None,
+            State::Profile => Some(&mut self.profile_inputs),
+            State::Password => Some(&mut self.password_inputs),
+        }
+    }
+
+    fn focus(&mut self, idx: usize) {
+        self.focused = idx;
+        if let Some(inputs) = self.inputs() {
+            for (i, input) in inputs.iter_mut().enumerate() {
+                input.is_editing = i == idx;
+            }
+        }
+    }
+
+    fn start_edit_profile(&mut self) {
+        let user = CURRENT_USER.get_user().user.unwrap();
+        let values = [Some(user.name), user.email, user.phone];
+        for (input, value) in self.profile_inputs.iter_mut().zip(values) {
+            input.reset();
+            if let Some(value) = value {
+                input.set_input(value);
+            }
+        }
+        self.state = State::Profile;
+        self.focus(0);
+    }
+
+    fn start_change_password(&mut self) {
+        self.password_inputs.iter_mut().for_each(UserInput::reset);
+        self.state = State::Password;
+        self.focus(0);
+    }
+
+    fn stop_edit(&mut self) {
+        if let Some(inputs) = self.inputs() {
+            for input in inputs.iter_mut() {
+                input.reset();
+                input.is_editing = false;
+            }
+        }
+        self.state = State::Normal;
+        self.focused = 0;
+    }
+
+    fn save_profile(&mut self) -> Option<Action> {
+        self.profile_inputs
+            .iter_mut()
+            .for_each(UserInput::submit_message);
+        let [name, email, phone] = &self.profile_inputs;
+        let Some(name) = non_blank(name) else {
+            return Some(Action::Alert("用户名不能为空".to_string(), None));
+        };
+        let req = UpdateUserReq {
+            name: name.clone(),
+            email: non_blank(email),
+            phone: non_blank(phone),
+        };
+        let (email, phone) = (req.email.clone(), req.phone.clone());
+        if let Err(err) = user::update(req) {
+            return Some(Action::Alert(err.to_string(), None));
+        }
+        CURRENT_USER.update_profile(name, email, phone);
+        self.stop_edit();
+        self.fetch_profile();
+        Some(Action::Alert("个人资料已更新".to_string(), None))
+    }
+
+    /// 校验两次输入的新密码，通过后弹出确认框
+    fn submit_password(&mut self) -> Option<Action> {
+        self.password_inputs
+            .iter_mut()
+            .for_each(UserInput::submit_message);
+        let [old, new, confirm] = &self.password_inputs;
+        let (Some(_), Some(new)) = (old.data(), new.data()) else {
+            return Some(Action::Alert("请输入原密码和新密码".to_string(), None));
+        };
+        if confirm.data().as_ref() != Some(&new) {
+            return Some(Action::Alert("两次输入的新密码不一致".to_string(), None));
+        }
+        Some(Action::Alert(
+            "确定修改密码么？".to_string(),
+            Some(ConfirmEvent::ChangePassword),
+        ))
+    }
+
+    fn change_password(&mut self) -> Option<Action> {
+        let [old, new, _] = &self.password_inputs;
+        let (Some(old), Some(new)) = (old.data(), new.data()) else {
+            return None;
+        };
+        if let Err(err) = user::change_password(old, new) {
+            return Some(Action::Alert(err.to_string(), None));
+        }
+        self.stop_edit();
+        Some(Action::Alert("密码已修改".to_string(), None))
+    }
+
+    fn handle_edit_key(&mut self, key: KeyEvent) -> Option<Action> {
+        let focused = self.focused;
+        let input = &mut self.inputs()?[focused];
+        match key.code {
+            KeyCode::Char(to_insert) => input.enter_char(to_insert),
+            KeyCode::Backspace => input.delete_char(),
+            KeyCode::Left => input.move_cursor_left(),
+            KeyCode::Right => input.move_cursor_right(),
+            KeyCode::Down => self.focus((focused + 1) % 3),
+            KeyCode::Up => self.focus((focused + 2) % 3),
+            KeyCode::Enter if focused < 2 => self.focus(focused + 1),
+            KeyCode::Enter => {
+                return match self.state {
+                    State::Profile => self.save_profile(),
+                    _ => self.submit_password(),
+                };
+            }
+            KeyCode::Esc => self.stop_edit(),
+            _ => {}
+        }
+        None
+    }
+
+    fn render_profile(&mut self, frame: &mut Frame, area: Rect) {
+        let title = match self.state {
+            State::Normal => "Profile, e: Edit Profile, w: Change Password, l: Logout",
+            State::Profile => "Edit Profile, ↑↓: Switch, Enter: Save, Esc: Cancel",
+            State::Password => "Change Password, ↑↓: Switch, Enter: Save, Esc: Cancel",
+        };
+        let block = Block::new()
+            .title(title)
+            .title_alignment(Alignment::Center)
+            .borders(Borders::ALL)
+            .border_set(symbols::border::ROUNDED);
+        let inner = block.inner(area);
+        frame.render_widget(block, area);
+        let focused = self.focused;
+        let Some(inputs) = self.inputs() else {
+            let user = CURRENT_USER.get_user().user.unwrap();
+            let none = || "未设置".to_string();
+            let mut lines = vec![
+                Line::from(format!("用户名：{}", user.name)),
+                Line::from(format!("邮箱：{}", user.email.unwrap_or_else(none))),
+                Line::from(format!("手机：{}", user.phone.unwrap_or_else(none))),
+                Line::from(format!("角色：{}", user.role)),
+            ];
+            if let Some(profile) = &self.profile {
+                lines.push(Line::from(format!(
+                    "注册时间：{}",
+                    profile.create_time.format("%Y-%m-%d %H:%M")
+                )));
+            }
+            frame.render_widget(Paragraph::new(lines), inner);
+            return;
+        };
+        let areas = Layout::vertical([Constraint::Length(3); 3]).split(inner);
+        for (i, input) in inputs.iter_mut().enumerate() {
+            let paragraph = Paragraph::new(input.display_text())
+                .style(input.select_style())
+                .block(Block::bordered().title(input.input_data.label()));
+            frame.render_widget(paragraph, areas[i]);
+            if i == focused {
+                input.set_cursor_position(areas[i]);
+            }
+        }
+    }
+
+    fn set_presence(&self, status: PresenceStatus) -> Option<Action> {
+        match presence::set(status) {
+            Ok(_) => None,
+            Err(err) => Some(Action::Alert(err.to_string(), None)),
+        }
     }
 }
 
 impl Component for Setting {
-    fn handle_key_event(&mut self, _key: KeyEvent) -> color_eyre::Result<Option<Action>> {
+    fn handle_key_event(&mut self, key: KeyEvent) -> color_eyre::Result<Option<Action>> {
+        if self.mode_holder.get_mode() != Mode::Setting {
+            return Ok(None);
+        }
+        if self.state != State::Normal {
+            return Ok(self.handle_edit_key(key));
+        }
+        let uid = CURRENT_USER.get_user().user.unwrap().id;
+        let mut status = PRESENCES.get(uid);
+        if self.status_input.is_editing {
+            match key.code {
+                KeyCode::Enter => {
+                    self.status_input.submit_message();
+                    status.status_text = self.status_input.data();
+                    self.status_input.reset();
+                    self.status_input.is_editing = false;
+                    return Ok(self.set_presence(status));
+                }
+                KeyCode::Char(to_insert) => self.status_input.enter_char(to_insert),
+                KeyCode::Backspace => self.status_input.delete_char(),
+                KeyCode::Left => self.status_input.move_cursor_left(),
+                KeyCode::Right => self.status_input.move_cursor_right(),
+                KeyCode::Esc => {
+                    self.status_input.reset();
+                    self.status_input.is_editing = false;
+                }
+                _ => {}
+            }
+            return Ok(None);
+        }
+        match key.code {
+            KeyCode::Char('p') => {
+                status.presence = status.presence.next();
+                return Ok(self.set_presence(status));
+            }
+            KeyCode::Char('s') => self.status_input.is_editing = true,
+            KeyCode::Char('e') => self.start_edit_profile(),
+            KeyCode::Char('w') => self.start_change_password(),
+            KeyCode::Left => self.list_focus = ListFocus::Blocked,
+            KeyCode::Right => self.list_focus = ListFocus::Sessions,
+            KeyCode::Up => match self.list_focus {
+                ListFocus::Blocked => self.blocked_list_state.select_previous(),
+                ListFocus::Sessions => self.session_list_state.select_previous(),
+            },
+            KeyCode::Down => match self.list_focus {
+                ListFocus::Blocked => self.blocked_list_state.select_next(),
+                ListFocus::Sessions => self.session_list_state.select_next(),
+            },
+            KeyCode::Char('l') => {
+                return Ok(Some(Action::Alert(
+                    "确定退出登录么？".to_string(),
+                    Some(ConfirmEvent::Logout),
+                )));
+            }
+            KeyCode::Char('r') if self.list_focus == ListFocus::Sessions => {
+                if let Some(idx) = self.session_list_state.selected()
+                    && let Some(session) = self.sessions.get(idx)
+                {
+                    // 当前会话通过退出登录注销
+                    if session.current {
+                        return Ok(Some(Action::Alert(
+                            "当前设备请使用退出登录(l)".to_string(),
+                            None,
+                        )));
+                    }
+                    return Ok(Some(Action::Alert(
+                        "确定注销该设备的登录么？".to_string(),
+                        Some(ConfirmEvent::RevokeSession(session.id.clone())),
+                    )));
+                }
+            }
+            KeyCode::Char('u') if self.list_focus == ListFocus::Blocked => {
+                if let Some(idx) = self.blocked_list_state.selected()
+                    && let Some(user) = self.blocked_users.get(idx)
+                {
+                    return Ok(Some(Action::Alert(
+                        format!("确定取消屏蔽{}么？", user.name),
+                        Some(ConfirmEvent::UnblockUser(user.id)),
+                    )));
+                }
+            }
+            _ => {}
+        }
         Ok(None)
     }
 
-    fn update(&mut self, _action: Action) -> color_eyre::Result<Option<Action>> {
+    fn update(&mut self, action: Action) -> color_eyre::Result<Option<Action>> {
+        match action {
+            // 登录后加载屏蔽列表，用于过滤消息
+            Action::LoginSuccess => {
+                self.fetch_profile();
+                self.fetch_sessions();
+                self.fetch_blocked_users();
+                if !self.blocked_users.is_empty() {
+                    return Ok(Some(Action::RefreshRecentChat));
+                }
+            }
+            Action::BlockListChanged => {
+                self.fetch_blocked_users();
+                return Ok(Some(Action::RefreshRecentChat));
+            }
+            Action::Confirm(ConfirmEvent::UnblockUser(uid)) => {
+                if let Err(err) = block::unblock(uid) {
+                    return Ok(Some(Action::Alert(err.to_string(), None)));
+                }
+                self.fetch_blocked_users();
+                return Ok(Some(Action::RefreshRecentChat));
+            }
+            Action::Confirm(ConfirmEvent::ChangePassword) => return Ok(self.change_password()),
+            Action::Confirm(ConfirmEvent::Logout) => return Ok(Some(Action::Logout)),
+            Action::Confirm(ConfirmEvent::RevokeSession(id)) => {
+                return Ok(self.revoke_session(id));
+            }
+            Action::Logout => *self = Setting::new(self.mode_holder.clone()),
+            _ => {}
+        }
         Ok(None)
     }
 
     fn draw(&mut self, frame: &mut Frame, area: Rect) -> color_eyre::Result<()> {
         if self.mode_holder.get_mode() == Mode::Setting {
             let area = area_util::setting_area(area);
-            let area = area_util::centered_rect(60, 10, area);
+            let area = area_util::centered_rect(60, 80, area);
+            let profile_height = match self.state {
+                State::Normal => 7,
+                _ => 11,
+            };
+            let [profile_area, presence_area, status_area, lists_area] = Layout::vertical([
+                Constraint::Length(profile_height),
+                Constraint::Length(5),
+                Constraint::Length(3),
+                Constraint::Fill(1),
+            ])
+            .areas(area);
+            self.render_profile(frame, profile_area);
             let block = Block::new()
+                .title("Press p To Switch Presence")
+                .title_alignment(Alignment::Center)
                 .borders(Borders::ALL)
                 .border_set(symbols::border::ROUNDED);
             let user = CURRENT_USER.get_user().user.unwrap();
-            let text = format!(
-                "你好{}，欢迎使用Chat-Tui！\n这里还没有实现哟～～～",
-                user.name
-            );
-            let area = area_util::cal_center_area(area, text.as_str());
-            let user_input = Paragraph::new(text)
+            let status = PRESENCES.get(user.id);
+            let text = Text::from(vec![
+                Line::from(format!("你好{}，欢迎使用Chat-Tui！", user.name)),
+                Line::from(vec![
+                    status.dot(),
+                    Span::styled(
+                        status.describe(),
+                        Style::default().fg(status.presence.color()),
+                    ),
+                ]),
+            ]);
+            let presence = Paragraph::new(text)
                 .alignment(Alignment::Center)
                 .block(block);
-            frame.render_widget(user_input, area);
+            frame.render_widget(presence, presence_area);
+
+            let status_block = Block::new()
+                .title(self.status_input.input_data.label())
+                .title_alignment(Alignment::Center)
+                .borders(Borders::ALL)
+                .border_set(symbols::border::ROUNDED);
+            let status_input =
+                Paragraph::new(self.status_input.input.clone().unwrap_or("".to_string()))
+                    .style(self.status_input.select_style())
+                    .block(status_block);
+            frame.render_widget(status_input, status_area);
+            if self.status_input.is_editing {
+                self.status_input.set_cursor_position(status_area);
+            }
+
+            let [blocked_area, sessions_area] =
+                Layout::horizontal([Constraint::Fill(1); 2]).areas(lists_area);
+            let blocked_block = self.list_block(
+                format!("Blocked Users({}), u To Unblock", self.blocked_users.len()),
+                ListFocus::Blocked,
+            );
+            let items: Vec<ListItem> = self
+                .blocked_users
+                .iter()
+                .map(|user| {
+                    ListItem::new(Line::from(vec![
+                        Span::styled(user.name.clone(), Style::default().fg(Color::White)),
+                        Span::styled(
+                            format!(" 屏蔽于{}", user.create_time.format("%Y-%m-%d %H:%M")),
+                            Style::default().fg(Color::DarkGray),
+                        ),
+                    ]))
+                })
+                .collect();
+            let list = List::new(items)
+                .block(blocked_block)
+                .highlight_style(SELECTED_STYLE)
+                .highlight_spacing(HighlightSpacing::Always);
+            frame.render_stateful_widget(list, blocked_area, &mut self.blocked_list_state);
+            self.render_sessions(frame, sessions_area);
         }
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::ModeHolder;
+    use std::sync::{Arc, Mutex};
+
+    fn submit(passwords: [&str; 3]) -> Option<Action> {
+        let mut setting = Setting::new(ModeHolderLock(Arc::new(Mutex::new(ModeHolder::default()))));
+        for (input, password) in setting.password_inputs.iter_mut().zip(passwords) {
+            password.chars().for_each(|c| input.enter_char(c));
+        }
+        setting.submit_password()
+    }
+
+    #[test]
+    fn test_submit_password() {
+        assert_eq!(
+            submit(["old", "new1", "new2"]),
+            Some(Action::Alert("两次输入的新密码不一致".to_string(), None))
+        );
+        assert_eq!(
+            submit(["", "new", "new"]),
+            Some(Action::Alert("请输入原密码和新密码".to_string(), None))
+        );
+        assert_eq!(
+            submit(["old", "new", "new"]),
+            Some(Action::Alert(
+                "确定修改密码么？".to_string(),
+                Some(ConfirmEvent::ChangePassword),
+            ))
+        );
+    }
+}
@@ -1,10 +1,11 @@
 use crate::action::Action;
 use crate::components::Component;
 use crossterm::event::{KeyCode, KeyEvent};
+use emojis::Emoji;
 use ratatui::Frame;
 use ratatui::buffer::Buffer;
 use ratatui::layout::{Position, Rect};
-use ratatui::prelude::{Color, Style};
+use ratatui::prelude::{Color, Span, Style};
 use ratatui::widgets::Widget;
 
 pub(crate) struct UserInput {
@@ -169,6 +170,86 @@ impl UserInput {
             .unwrap_or(self.current_input().len())
     }
 
+    /// 在光标处插入文本，如选择的表情
+    pub(crate) fn insert_str(&mut self, text: &str) {
+        let index = self.byte_index();
+        let mut input = self.current_input();
+        input.insert_str(index, text);
+        self.set_current_input(input);
+        self.character_index += text.chars().count();
+    }
+
+    /// 光标前正在输入的表情短码，如输入 ":smi" 时返回 "smi"
+    fn shortcode_prefix(&self) -> Option<String> {
+        if !matches!(self.input_data, InputData::ChatMsg { .. }) {
+            return None;
+        }
+        let before = self
+            .current_input()
+            .chars()
+            .take(self.character_index)
+            .collect::<String>();
+        let start = before.rfind(':')?;
+        let code = &before[start + 1..];
+        // 冒号前为空白或行首，避免把 "12:30" 之类的内容当作短码
+        let at_boundary = before[..start]
+            .chars()
+            .last()
+            .is_none_or(char::is_whitespace);
+        let is_code = !code.is_empty()
+            && code
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '+' | '-'));
+        (at_boundary && is_code).then(|| code.to_string())
+    }
+
+    /// 与光标前短码匹配的表情
+    pub(crate) fn emoji_completions(&self, limit: usize) -> Vec<&'static Emoji> {
+        let Some(prefix) = self.shortcode_prefix() else {
+            return Vec::new();
+        };
+        emojis::iter()
+            .filter(|emoji| emoji.shortcodes().any(|code| code.starts_with(&prefix)))
+            .take(limit)
+            .collect()
+    }
+
+    /// 用表情替换光标前正在输入的短码
+    pub(crate) fn complete_emoji(&mut self, emoji: &str) {
+        if let Some(prefix) = self.shortcode_prefix() {
+            self.replace_before_cursor(prefix.chars().count() + 1, emoji);
+        }
+    }
+
+    /// 输入结束的冒号后，将完整的 :shortcode: 替换为表情
+    pub(crate) fn expand_shortcode(&mut self) -> Option<&'static str> {
+        let before = self
+            .current_input()
+            .chars()
+            .nth(self.character_index.checked_sub(1)?);
+        if before != Some(':') {
+            return None;
+        }
+        self.character_index -= 1;
+        let prefix = self.shortcode_prefix();
+        self.character_index += 1;
+        let prefix = prefix?;
+        let emoji = emojis::get_by_shortcode(&prefix)?;
+        self.replace_before_cursor(prefix.chars().count() + 2, emoji.as_str());
+        Some(emoji.as_str())
+    }
+
+    /// 删除光标前的 count 个字符并插入 text
+    fn replace_before_cursor(&mut self, count: usize, text: &str) {
+        let input = self.current_input();
+        let start = self.character_index.saturating_sub(count);
+        let mut replaced = input.chars().take(start).collect::<String>();
+        replaced.push_str(text);
+        replaced.extend(input.chars().skip(self.character_index));
+        self.set_current_input(replaced);
+        self.character_index = start + text.chars().count();
+    }
+
     pub(crate) fn delete_char(&mut self) {
         let is_not_cursor_leftmost = self.character_index != 0;
         if is_not_cursor_leftmost {
@@ -206,6 +287,12 @@ impl UserInput {
         self.input.clone().unwrap_or_default()
     }
 
+    /// 预填输入框内容，光标移动到末尾
+    pub(crate) fn set_input(&mut self, input: String) {
+        self.character_index = input.chars().count();
+        self.set_current_input(input);
+    }
+
     fn set_current_input(&mut self, input: String) {
         self.input = Some(input);
     }
@@ -221,15 +308,30 @@ impl UserInput {
     }
 
     pub(crate) fn set_cursor_position(&mut self, input_area: Rect) {
+        // 按显示宽度计算光标位置，表情和中文占两列
+        let before_cursor = self
+            .display_text()
+            .chars()
+            .take(self.character_index)
+            .collect::<String>();
         self.cursor_position = Some(Position::new(
             // Draw the cursor at the current position in the input field.
             // This position is can be controlled via the left and right arrow key
-            input_area.x + self.character_index as u16 + 1,
+            input_area.x + Span::raw(before_cursor).width() as u16 + 1,
             // Move one line down, from the border to the input line
             input_area.y + 1,
         ))
     }
 
+    /// 输入框显示的内容，密码以*代替
+    pub(crate) fn display_text(&self) -> String {
+        let input = self.current_input();
+        match self.input_data {
+            InputData::Password { .. } => "*".repeat(input.chars().count()),
+            _ => input,
+        }
+    }
+
     pub(crate) fn select_style(&self) -> Style {
         if self.is_editing {
             Style::default().fg(Color::Yellow)
@@ -240,4 +342,38 @@ impl UserInput {
 }
 
 #[cfg(test)]
-mod test {}
+mod test {
+    use super::*;
+
+    fn chat_input(text: &str) -> UserInput {
+        let mut input = UserInput::new(InputData::ChatMsg {
+            label: None,
+            data: None,
+        });
+        input.set_input(text.to_string());
+        input
+    }
+
+    #[test]
+    fn expand_shortcode() {
+        let mut input = chat_input("你好 :smile");
+        input.enter_char(':');
+        assert_eq!(input.expand_shortcode(), Some("😄"));
+        assert_eq!(input.input.as_deref(), Some("你好 😄"));
+
+        // 时间等非短码内容不替换
+        let mut input = chat_input("12:30");
+        input.enter_char(':');
+        assert_eq!(input.expand_shortcode(), None);
+    }
+
+    #[test]
+    fn complete_emoji() {
+        let mut input = chat_input(":thumbs");
+        let completions = input.emoji_completions(5);
+        assert!(completions.iter().any(|emoji| emoji.as_str() == "👍"));
+        input.complete_emoji("👍");
+        assert_eq!(input.input.as_deref(), Some("👍"));
+        assert!(input.emoji_completions(5).is_empty());
+    }
+}
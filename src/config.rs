@@ -4,12 +4,15 @@ use color_eyre::Result;
 use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 use derive_deref::{Deref, DerefMut};
 use directories::ProjectDirs;
+use jsonwebtoken::Algorithm;
 use ratatui::style::{Color, Modifier, Style};
 use serde::{de::Deserializer, Deserialize};
+use sha2::{Digest, Sha256};
 use std::sync::LazyLock;
 use std::{collections::HashMap, env, path::PathBuf};
 use tracing::error;
 
+use crate::graphics::Protocol;
 use crate::{action::Action, app::Mode};
 
 const CONFIG: &str = include_str!("../.config/config.json5");
@@ -30,6 +33,57 @@ pub struct Config {
     pub keybindings: KeyBindings,
     #[serde(default)]
     pub styles: Styles,
+    /// 服务器配置，登录时选择
+    #[serde(default)]
+    pub profiles: Vec<ServerProfile>,
+    /// 附件下载目录，默认为系统的下载目录
+    #[serde(default)]
+    pub download_dir: Option<PathBuf>,
+    /// 图片预览使用的终端图形协议，默认自动检测
+    #[serde(default)]
+    pub image_protocol: Option<Protocol>,
+}
+
+/// 服务器配置
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Eq)]
+pub struct ServerProfile {
+    pub name: String,
+    pub url: String,
+    #[serde(default)]
+    pub tls: TlsOptions,
+    /// 登录时自动填写的用户名
+    #[serde(default)]
+    pub user_name: Option<String>,
+    #[serde(default)]
+    pub jwt: JwtVerification,
+}
+
+/// token 的校验方式
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Eq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum JwtVerification {
+    /// HS256 共享密钥，读取环境变量 JWT_SECRET
+    #[default]
+    Secret,
+    /// 服务端公钥，PEM 格式，支持 RS256/ES256/EdDSA 等算法
+    Pem { algorithm: Algorithm, path: PathBuf },
+    /// 从 JWKS 获取服务端公钥，默认地址为 {url}/.well-known/jwks.json
+    Jwks {
+        #[serde(default)]
+        url: Option<String>,
+    },
+    /// 不校验签名，只解析用户信息和过期时间，由服务端负责鉴权
+    Insecure,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Eq)]
+pub struct TlsOptions {
+    /// 信任无效证书，仅用于测试环境
+    #[serde(default)]
+    pub accept_invalid_certs: bool,
+    /// 自签名证书的 CA 文件，PEM 格式
+    #[serde(default)]
+    pub ca_cert: Option<PathBuf>,
 }
 
 pub static PROJECT_NAME: LazyLock<String> =
@@ -106,6 +160,30 @@ pub fn get_data_dir() -> PathBuf {
     }
 }
 
+/// 数据目录中区分服务器配置的名称，配置名含路径分隔符等字符时使用名称的哈希
+pub(crate) fn profile_key(profile: Option<&str>) -> String {
+    match profile {
+        None => "default".to_string(),
+        Some(name)
+            if !name.starts_with('.')
+                && !name.is_empty()
+                && name
+                    .chars()
+                    .all(|c| c.is_alphanumeric() || matches!(c, '-' | '_' | '.')) =>
+        {
+            name.to_string()
+        }
+        Some(name) => {
+            let digest = Sha256::digest(name.as_bytes());
+            let hash = digest[..8]
+                .iter()
+                .map(|b| format!("{b:02x}"))
+                .collect::<String>();
+            format!("profile-{hash}")
+        }
+    }
+}
+
 pub fn get_config_dir() -> PathBuf {
     if let Some(s) = CONFIG_FOLDER.clone() {
         s
@@ -116,6 +194,13 @@ pub fn get_config_dir() -> PathBuf {
     }
 }
 
+/// 附件的默认下载目录
+pub fn get_download_dir() -> PathBuf {
+    directories::UserDirs::new()
+        .and_then(|dirs| dirs.download_dir().map(|dir| dir.to_path_buf()))
+        .unwrap_or_else(|| get_data_dir().join("downloads"))
+}
+
 fn project_directory() -> Option<ProjectDirs> {
     ProjectDirs::from("com", "kdheepak", env!("CARGO_PKG_NAME"))
 }
@@ -450,6 +535,19 @@ mod tests {
 
     use super::*;
 
+    #[test]
+    fn test_profile_key() {
+        assert_eq!(profile_key(None), "default");
+        assert_eq!(profile_key(Some("work-1")), "work-1");
+        assert_eq!(profile_key(Some("公司")), "公司");
+        for name in ["../etc", "a/b", "..", "", "a\\b"] {
+            let key = profile_key(Some(name));
+            assert!(key.starts_with("profile-"), "{name}");
+            assert_eq!(key.len(), "profile-".len() + 16);
+        }
+        assert_ne!(profile_key(Some("a/b")), profile_key(Some("a\\b")));
+    }
+
     #[test]
     fn test_parse_style_default() {
         let style = parse_style("");
@@ -497,6 +595,39 @@ mod tests {
         assert_eq!(color, None);
     }
 
+    #[test]
+    fn test_profiles() {
+        let c: Config = json5::from_str(
+            r#"{
+                profiles: [
+                    { name: "dev", url: "https://localhost:3000", tls: { accept_invalid_certs: true } },
+                    { name: "prod", url: "https://chat.example.com", user_name: "alice" },
+                    { name: "rsa", url: "https://a.com", jwt: { type: "pem", algorithm: "RS256", path: "/a.pem" } },
+                    { name: "jwks", url: "https://b.com", jwt: { type: "jwks" } },
+                ],
+                download_dir: "/tmp/downloads",
+                image_protocol: "sixel",
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(c.profiles.len(), 4);
+        assert!(c.profiles[0].tls.accept_invalid_certs);
+        assert_eq!(c.profiles[0].user_name, None);
+        assert_eq!(c.profiles[1].user_name.as_deref(), Some("alice"));
+        assert_eq!(c.profiles[1].tls, TlsOptions::default());
+        assert_eq!(c.profiles[1].jwt, JwtVerification::Secret);
+        assert_eq!(
+            c.profiles[2].jwt,
+            JwtVerification::Pem {
+                algorithm: Algorithm::RS256,
+                path: PathBuf::from("/a.pem")
+            }
+        );
+        assert_eq!(c.profiles[3].jwt, JwtVerification::Jwks { url: None });
+        assert_eq!(c.download_dir, Some(PathBuf::from("/tmp/downloads")));
+        assert_eq!(c.image_protocol, Some(Protocol::Sixel));
+    }
+
     #[test]
     fn test_config() -> Result<()> {
         let c = Config::new()?;
@@ -0,0 +1,517 @@
+use crate::config::get_data_dir;
+use crate::proxy;
+use crate::proxy::{HOST, client, send_request};
+use crate::token::CURRENT_USER;
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use color_eyre::eyre::format_err;
+use hkdf::Hkdf;
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::{LazyLock, Mutex};
+use tracing::warn;
+use x25519_dalek::{PublicKey, StaticSecret};
+
+// 私聊端到端加密：每个用户一个 X25519 身份密钥，双方的共享密钥派生出会话密钥
+pub(crate) static E2E: LazyLock<E2e> = LazyLock::new(|| E2e(Mutex::new(Keyring::default())));
+
+/// 加密消息的前缀，不带前缀的消息按明文显示
+const PREFIX: &str = "e2e:v1:";
+const NONCE_LEN: usize = 12;
+const SALT: &[u8] = b"chat-tui-e2e";
+const KEY_CHANGED: &str = "对方的公钥已变更，已停止加密，请与对方核对指纹";
+
+pub(crate) struct E2e(Mutex<Keyring>);
+
+#[derive(Default)]
+pub(crate) struct Keyring {
+    identity: Option<StaticSecret>,
+    /// 对方的公钥，未发布公钥的用户不缓存，以便对方发布后可以开启加密
+    peers: HashMap<i32, PublicKey>,
+    /// 已开启加密的私聊
+    enabled: HashSet<i32>,
+    /// 首次获取到的对方公钥指纹，之后获取的公钥必须与之一致
+    pinned: HashMap<i32, String>,
+    /// 公钥与首次记录的指纹不一致的用户及其新公钥，确认信任前不再与其加密通信
+    changed: HashMap<i32, PublicKey>,
+}
+
+/// 获取到的公钥与记录的指纹比较的结果
+#[derive(Debug, PartialEq, Eq)]
+enum Pin {
+    /// 首次获取，记录指纹
+    New,
+    Match,
+    Mismatch,
+}
+
+fn check_pin(pinned: &mut HashMap<i32, String>, peer: i32, fingerprint: String) -> Pin {
+    match pinned.get(&peer) {
+        Some(pin) if *pin == fingerprint => Pin::Match,
+        Some(_) => Pin::Mismatch,
+        None => {
+            pinned.insert(peer, fingerprint);
+            Pin::New
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct PublicKeyDto {
+    public_key: String,
+}
+
+impl E2e {
+    /// 登录或切换账号后加载当前用户的身份密钥并发布公钥，首次使用时生成
+    pub(crate) fn init(&self) {
+        *self.0.lock().unwrap() = Keyring::default();
+        let Some(user) = CURRENT_USER.get_user().user else {
+            return;
+        };
+        let identity = match load_identity(user.id) {
+            Ok(identity) => identity,
+            Err(err) => {
+                warn!("fail to load e2e identity: {err}");
+                return;
+            }
+        };
+        let public = PublicKey::from(&identity);
+        let mut keyring = self.0.lock().unwrap();
+        keyring.identity = Some(identity);
+        keyring.enabled = load_enabled(user.id);
+        keyring.pinned = load_pinned(user.id);
+        drop(keyring);
+        if let Err(err) = publish(public) {
+            warn!("fail to publish e2e public key: {err}");
+        }
+    }
+
+    /// 切换账号时取出当前账号的密钥
+    pub(crate) fn take(&self) -> Keyring {
+        std::mem::take(&mut self.0.lock().unwrap())
+    }
+
+    pub(crate) fn restore(&self, keyring: Keyring) {
+        *self.0.lock().unwrap() = keyring;
+    }
+
+    pub(crate) fn is_enabled(&self, peer: i32) -> bool {
+        self.0.lock().unwrap().enabled.contains(&peer)
+    }
+
+    /// 对方的公钥是否与首次记录的指纹不一致，可能被服务端替换
+    pub(crate) fn key_changed(&self, peer: i32) -> bool {
+        self.0.lock().unwrap().changed.contains_key(&peer)
+    }
+
+    /// 公钥变更后获取到的新公钥指纹
+    pub(crate) fn changed_fingerprint(&self, peer: i32) -> Option<String> {
+        self.0.lock().unwrap().changed.get(&peer).map(fingerprint)
+    }
+
+    /// 与对方核对新指纹后信任新公钥，替换记录的指纹并恢复加密
+    pub(crate) fn trust_changed_key(&self, peer: i32) -> color_eyre::Result<()> {
+        let uid = CURRENT_USER
+            .get_user()
+            .user
+            .ok_or_else(|| format_err!("未登录"))?
+            .id;
+        let mut keyring = self.0.lock().unwrap();
+        let key = keyring
+            .changed
+            .remove(&peer)
+            .ok_or_else(|| format_err!("对方的公钥未变更"))?;
+        keyring.pinned.insert(peer, fingerprint(&key));
+        keyring.peers.insert(peer, key);
+        save_pinned(uid, &keyring.pinned);
+        Ok(())
+    }
+
+    /// 打开会话时获取对方的公钥，以便及时发现公钥变更
+    pub(crate) fn check_peer_key(&self, peer: i32) {
+        let _ = self.peer_key(peer);
+    }
+
+    /// 开启或关闭与对方的加密，返回开启后的状态；对方未发布公钥时无法开启
+    pub(crate) fn toggle(&self, peer: i32) -> color_eyre::Result<bool> {
+        if self.key_changed(peer) {
+            return Err(format_err!("{KEY_CHANGED}"));
+        }
+        if !self.is_enabled(peer) && self.peer_key(peer).is_none() {
+            return Err(format_err!("对方尚未启用端到端加密"));
+        }
+        let uid = CURRENT_USER.get_user().user.unwrap().id;
+        let mut keyring = self.0.lock().unwrap();
+        let enabled = if keyring.enabled.remove(&peer) {
+            false
+        } else {
+            keyring.enabled.insert(peer)
+        };
+        save_enabled(uid, &keyring.enabled);
+        Ok(enabled)
+    }
+
+    /// 当前用户公钥的指纹
+    pub(crate) fn own_fingerprint(&self) -> Option<String> {
+        let keyring = self.0.lock().unwrap();
+        keyring
+            .identity
+            .as_ref()
+            .map(|identity| fingerprint(&PublicKey::from(identity)))
+    }
+
+    /// 对方公钥的指纹，公钥变更后为首次记录的指纹，新指纹见 [E2e::changed_fingerprint]
+    pub(crate) fn peer_fingerprint(&self, peer: i32) -> Option<String> {
+        self.peer_key(peer)
+            .map(|key| fingerprint(&key))
+            .or_else(|| self.0.lock().unwrap().pinned.get(&peer).cloned())
+    }
+
+    pub(crate) fn encrypt(&self, peer: i32, plain: &str) -> color_eyre::Result<String> {
+        if self.key_changed(peer) {
+            return Err(format_err!("{KEY_CHANGED}"));
+        }
+        let key = self
+            .conversation_key(peer)
+            .ok_or_else(|| format_err!("对方尚未启用端到端加密"))?;
+        seal(&key, plain)
+    }
+
+    /// 解密收到的消息，明文消息原样返回，无法解密时返回提示
+    pub(crate) fn decrypt(&self, peer: i32, msg: &str) -> String {
+        self.decrypt_with(peer, msg, self.conversation_key(peer))
+    }
+
+    /// 只使用已缓存的公钥解密，不发起请求，用于事件流任务
+    pub(crate) fn decrypt_cached(&self, peer: i32, msg: &str) -> String {
+        let key = self
+            .cached_peer_key(peer)
+            .and_then(|peer_key| self.conversation_key_with(peer, &peer_key));
+        self.decrypt_with(peer, msg, key)
+    }
+
+    fn decrypt_with(&self, peer: i32, msg: &str, key: Option<Key<Aes256Gcm>>) -> String {
+        if !is_encrypted(msg) {
+            return msg.to_string();
+        }
+        match key.map(|key| open(&key, msg)) {
+            Some(Ok(plain)) => plain,
+            Some(Err(err)) => {
+                warn!("fail to decrypt message from {peer}: {err}");
+                "[无法解密的消息]".to_string()
+            }
+            None => "[无法解密的消息]".to_string(),
+        }
+    }
+
+    fn conversation_key(&self, peer: i32) -> Option<Key<Aes256Gcm>> {
+        let peer_key = self.peer_key(peer)?;
+        self.conversation_key_with(peer, &peer_key)
+    }
+
+    fn conversation_key_with(&self, peer: i32, peer_key: &PublicKey) -> Option<Key<Aes256Gcm>> {
+        let uid = CURRENT_USER.get_user().user?.id;
+        let keyring = self.0.lock().unwrap();
+        let identity = keyring.identity.as_ref()?;
+        Some(conversation_key(identity, peer_key, uid, peer))
+    }
+
+    fn cached_peer_key(&self, peer: i32) -> Option<PublicKey> {
+        self.0.lock().unwrap().peers.get(&peer).copied()
+    }
+
+    /// 校验并缓存获取到的公钥，与首次记录的指纹不一致时拒绝使用
+    fn accept_peer_key(&self, peer: i32, key: PublicKey) -> Option<PublicKey> {
+        let uid = CURRENT_USER.get_user().user?.id;
+        let mut keyring = self.0.lock().unwrap();
+        match check_pin(&mut keyring.pinned, peer, fingerprint(&key)) {
+            Pin::Mismatch => {
+                warn!("public key of {peer} does not match the pinned fingerprint");
+                keyring.changed.insert(peer, key);
+                return None;
+            }
+            Pin::New => save_pinned(uid, &keyring.pinned),
+            Pin::Match => {}
+        }
+        keyring.peers.insert(peer, key);
+        Some(key)
+    }
+
+    /// 异步获取并缓存对方的公钥，事件流任务解密前调用，避免阻塞运行时的工作线程
+    pub(crate) async fn prefetch_peer_key(&self, peer: i32) {
+        if self.cached_peer_key(peer).is_some() || self.key_changed(peer) {
+            return;
+        }
+        match fetch_peer_key_async(peer).await {
+            Ok(Some(key)) => {
+                self.accept_peer_key(peer, key);
+            }
+            Ok(None) => {}
+            Err(err) => warn!("fail to get public key of {peer}: {err}"),
+        }
+    }
+
+    fn peer_key(&self, peer: i32) -> Option<PublicKey> {
+        if let Some(key) = self.cached_peer_key(peer) {
+            return Some(key);
+        }
+        if self.key_changed(peer) {
+            return None;
+        }
+        match send_request(move || fetch_peer_key(peer)) {
+            Ok(Ok(Some(key))) => self.accept_peer_key(peer, key),
+            Ok(Ok(None)) => None,
+            Ok(Err(err)) | Err(err) => {
+                warn!("fail to get public key of {peer}: {err}");
+                None
+            }
+        }
+    }
+}
+
+/// 是否为加密消息
+pub(crate) fn is_encrypted(msg: &str) -> bool {
+    msg.starts_with(PREFIX)
+}
+
+/// 双方的共享密钥经 HKDF 派生为会话密钥，双方的 uid 按大小排序后作为 info
+fn conversation_key(identity: &StaticSecret, peer: &PublicKey, a: i32, b: i32) -> Key<Aes256Gcm> {
+    let shared = identity.diffie_hellman(peer);
+    let info = format!("{}:{}", a.min(b), a.max(b));
+    let mut key = Key::<Aes256Gcm>::default();
+    Hkdf::<Sha256>::new(Some(SALT), shared.as_bytes())
+        .expand(info.as_bytes(), &mut key)
+        .expect("32 bytes is a valid length for HKDF-SHA256");
+    key
+}
+
+/// 加密结果为 前缀 + base64(nonce + 密文)
+fn seal(key: &Key<Aes256Gcm>, plain: &str) -> color_eyre::Result<String> {
+    let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+    let encrypted = Aes256Gcm::new(key)
+        .encrypt(&nonce, plain.as_bytes())
+        .map_err(|err| format_err!("fail to encrypt message: {err}"))?;
+    let data = [nonce.as_slice(), &encrypted].concat();
+    Ok(format!("{PREFIX}{}", STANDARD.encode(data)))
+}
+
+fn open(key: &Key<Aes256Gcm>, msg: &str) -> color_eyre::Result<String> {
+    let data = STANDARD.decode(msg.trim_start_matches(PREFIX))?;
+    if data.len() < NONCE_LEN {
+        return Err(format_err!("message too short"));
+    }
+    let (nonce, encrypted) = data.split_at(NONCE_LEN);
+    let plain = Aes256Gcm::new(key)
+        .decrypt(Nonce::from_slice(nonce), encrypted)
+        .map_err(|err| format_err!("fail to decrypt message: {err}"))?;
+    Ok(String::from_utf8(plain)?)
+}
+
+/// 公钥指纹：SHA-256 的前 16 字节，每 2 字节一组
+pub(crate) fn fingerprint(key: &PublicKey) -> String {
+    let digest = Sha256::digest(key.as_bytes());
+    digest[..16]
+        .chunks(2)
+        .map(|chunk| format!("{:02X}{:02X}", chunk[0], chunk[1]))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// 按用户区分身份密钥文件
+fn identity_path(uid: i32) -> PathBuf {
+    get_data_dir().join(format!("e2e-{uid}.key"))
+}
+
+fn enabled_path(uid: i32) -> PathBuf {
+    get_data_dir().join(format!("e2e-{uid}.json"))
+}
+
+fn load_identity(uid: i32) -> color_eyre::Result<StaticSecret> {
+    let path = identity_path(uid);
+    if let Ok(content) = fs::read_to_string(&path) {
+        let bytes: [u8; 32] = STANDARD
+            .decode(content.trim())?
+            .try_into()
+            .map_err(|_| format_err!("invalid identity key {}", path.display()))?;
+        return Ok(StaticSecret::from(bytes));
+    }
+    let identity = StaticSecret::random_from_rng(OsRng);
+    fs::create_dir_all(get_data_dir())?;
+    let mut options = OpenOptions::new();
+    options.write(true).create_new(true);
+    // 身份私钥仅当前用户可读，创建时即设置权限
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    options
+        .open(&path)?
+        .write_all(STANDARD.encode(identity.to_bytes()).as_bytes())?;
+    Ok(identity)
+}
+
+fn pinned_path(uid: i32) -> PathBuf {
+    get_data_dir().join(format!("e2e-{uid}-peers.json"))
+}
+
+fn load_pinned(uid: i32) -> HashMap<i32, String> {
+    fs::read_to_string(pinned_path(uid))
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn save_pinned(uid: i32, pinned: &HashMap<i32, String>) {
+    let path = pinned_path(uid);
+    let content = serde_json::to_string(pinned).unwrap();
+    if let Err(err) = fs::create_dir_all(get_data_dir()).and_then(|_| fs::write(&path, content)) {
+        warn!("fail to save pinned fingerprints {}: {err}", path.display());
+    }
+}
+
+fn load_enabled(uid: i32) -> HashSet<i32> {
+    fs::read_to_string(enabled_path(uid))
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn save_enabled(uid: i32, enabled: &HashSet<i32>) {
+    let path = enabled_path(uid);
+    let content = serde_json::to_string(enabled).unwrap();
+    if let Err(err) = fs::create_dir_all(get_data_dir()).and_then(|_| fs::write(&path, content)) {
+        warn!("fail to save e2e settings {}: {err}", path.display());
+    }
+}
+
+/// 发布当前用户的公钥
+fn publish(public: PublicKey) -> color_eyre::Result<()> {
+    let body = PublicKeyDto {
+        public_key: STANDARD.encode(public.as_bytes()),
+    };
+    send_request(move || {
+        let token = CURRENT_USER.get_user().token.clone().unwrap();
+        let res = client()
+            .put(format!("{}/user/key", HOST.url()))
+            .header("Authorization", format!("Bearer {token}"))
+            .json(&body)
+            .send();
+        match res {
+            Ok(res) => match res.status() {
+                StatusCode::OK => Ok(()),
+                _ => Err(format_err!(
+                    "Failed to publish public key, status: {}",
+                    res.status()
+                )),
+            },
+            Err(err) => Err(format_err!("Failed to publish public key, err: {err}")),
+        }
+    })?
+}
+
+/// 获取对方发布的公钥，未发布时返回 None
+fn fetch_peer_key(uid: i32) -> color_eyre::Result<Option<PublicKey>> {
+    let token = CURRENT_USER.get_user().token.clone().unwrap();
+    let res = client()
+        .get(format!("{}/user/{uid}/key", HOST.url()))
+        .header("Authorization", format!("Bearer {token}"))
+        .send();
+    match res {
+        Ok(res) => match res.status() {
+            StatusCode::OK => {
+                let dto = res
+                    .json::<PublicKeyDto>()
+                    .map_err(|err| format_err!("Failed to get public key, err: {err}"))?;
+                Ok(Some(decode_public_key(uid, dto)?))
+            }
+            StatusCode::NOT_FOUND => Ok(None),
+            _ => Err(format_err!(
+                "Failed to get public key, status: {}",
+                res.status()
+            )),
+        },
+        Err(err) => Err(format_err!("Failed to get public key, err: {err}")),
+    }
+}
+
+/// 异步获取对方发布的公钥，未发布时返回 None
+async fn fetch_peer_key_async(uid: i32) -> color_eyre::Result<Option<PublicKey>> {
+    let token = CURRENT_USER.get_user().token.clone().unwrap();
+    let res = proxy::async_client_for(HOST.profile().as_ref())
+        .get(format!("{}/user/{uid}/key", HOST.url()))
+        .header("Authorization", format!("Bearer {token}"))
+        .send()
+        .await;
+    match res {
+        Ok(res) => match res.status() {
+            StatusCode::OK => {
+                let dto = res
+                    .json::<PublicKeyDto>()
+                    .await
+                    .map_err(|err| format_err!("Failed to get public key, err: {err}"))?;
+                Ok(Some(decode_public_key(uid, dto)?))
+            }
+            StatusCode::NOT_FOUND => Ok(None),
+            _ => Err(format_err!(
+                "Failed to get public key, status: {}",
+                res.status()
+            )),
+        },
+        Err(err) => Err(format_err!("Failed to get public key, err: {err}")),
+    }
+}
+
+fn decode_public_key(uid: i32, dto: PublicKeyDto) -> color_eyre::Result<PublicKey> {
+    let bytes: [u8; 32] = STANDARD
+        .decode(dto.public_key)?
+        .try_into()
+        .map_err(|_| format_err!("invalid public key of {uid}"))?;
+    Ok(PublicKey::from(bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn seal_and_open() {
+        let alice = StaticSecret::random_from_rng(OsRng);
+        let bob = StaticSecret::random_from_rng(OsRng);
+        let key = conversation_key(&alice, &PublicKey::from(&bob), 1, 2);
+        assert_eq!(key, conversation_key(&bob, &PublicKey::from(&alice), 2, 1));
+
+        let msg = seal(&key, "你好").unwrap();
+        assert!(msg.starts_with(PREFIX));
+        assert_eq!(open(&key, &msg).unwrap(), "你好");
+
+        let other = conversation_key(&alice, &PublicKey::from(&bob), 1, 3);
+        assert!(open(&other, &msg).is_err());
+    }
+
+    #[test]
+    fn pin_first_seen_key() {
+        let mut pinned = HashMap::new();
+        let key = fingerprint(&PublicKey::from([1u8; 32]));
+        let other = fingerprint(&PublicKey::from([2u8; 32]));
+        assert_eq!(check_pin(&mut pinned, 1, key.clone()), Pin::New);
+        assert_eq!(check_pin(&mut pinned, 1, key.clone()), Pin::Match);
+        assert_eq!(check_pin(&mut pinned, 1, other), Pin::Mismatch);
+        assert_eq!(pinned[&1], key);
+    }
+
+    #[test]
+    fn fingerprint_format() {
+        let key = PublicKey::from([0u8; 32]);
+        let fingerprint = fingerprint(&key);
+        assert_eq!(fingerprint.split(' ').count(), 8);
+        assert!(fingerprint.split(' ').all(|group| group.len() == 4));
+    }
+}
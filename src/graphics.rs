@@ -0,0 +1,309 @@
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use crossterm::cursor::{MoveTo, RestorePosition, SavePosition};
+use crossterm::queue;
+use icy_sixel::{DiffusionMethod, MethodForLargest, MethodForRep, PixelFormat, Quality};
+use image::imageops::FilterType;
+use image::{DynamicImage, ImageFormat};
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use ratatui::style::{Color, Style};
+use ratatui::text::{Line, Span};
+use serde::Deserialize;
+use std::env;
+use std::io::{self, Cursor, Write};
+use std::sync::{Arc, LazyLock, Mutex, RwLock};
+use tracing::{info, warn};
+
+// 终端图形协议，图片通过转义序列直接输出到终端，不经过 ratatui 的缓冲区
+pub(crate) static GRAPHICS: LazyLock<Graphics> = LazyLock::new(|| {
+    let protocol = detect();
+    info!("image protocol: {protocol:?}");
+    Graphics {
+        protocol: RwLock::new(protocol),
+        cell_size: cell_size(),
+        placements: Mutex::new(Placements::default()),
+    }
+});
+
+/// kitty 协议每段数据的最大长度
+const KITTY_CHUNK: usize = 4096;
+
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Protocol {
+    Kitty,
+    Iterm2,
+    Sixel,
+    /// 不支持图形协议时用半块字符显示
+    Halfblocks,
+}
+
+/// 根据环境变量判断终端支持的图形协议
+fn detect() -> Protocol {
+    // tmux 中需要转发转义序列，直接使用字符画
+    if env::var_os("TMUX").is_some() {
+        return Protocol::Halfblocks;
+    }
+    let term = env::var("TERM").unwrap_or_default();
+    let program = env::var("TERM_PROGRAM").unwrap_or_default();
+    if env::var_os("KITTY_WINDOW_ID").is_some()
+        || term.contains("kitty")
+        || term.contains("ghostty")
+        || program == "ghostty"
+    {
+        Protocol::Kitty
+    } else if program == "iTerm.app"
+        || program == "WezTerm"
+        || env::var("LC_TERMINAL").is_ok_and(|t| t == "iTerm2")
+    {
+        Protocol::Iterm2
+    } else if term.contains("foot")
+        || term.contains("mlterm")
+        || term.contains("yaft")
+        || program == "contour"
+        || program == "mintty"
+    {
+        Protocol::Sixel
+    } else {
+        Protocol::Halfblocks
+    }
+}
+
+/// 单元格的像素大小，终端不支持查询时按 8x16 计算
+fn cell_size() -> (u16, u16) {
+    match crossterm::terminal::window_size() {
+        Ok(size) if size.width > 0 && size.height > 0 && size.columns > 0 && size.rows > 0 => (
+            (size.width / size.columns).max(1),
+            (size.height / size.rows).max(1),
+        ),
+        _ => (8, 16),
+    }
+}
+
+pub(crate) struct Graphics {
+    protocol: RwLock<Protocol>,
+    cell_size: (u16, u16),
+    placements: Mutex<Placements>,
+}
+
+#[derive(Default)]
+struct Placements {
+    /// 当前帧要显示的图片
+    pending: Vec<Placement>,
+    /// 已输出到终端的图片
+    shown: Vec<Placement>,
+    /// 上一帧的终端大小，变化后重新输出所有图片
+    area: Rect,
+}
+
+#[derive(Clone)]
+struct Placement {
+    id: u32,
+    area: Rect,
+    seq: Arc<String>,
+}
+
+impl PartialEq for Placement {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id && self.area == other.area && Arc::ptr_eq(&self.seq, &other.seq)
+    }
+}
+
+impl Graphics {
+    pub(crate) fn protocol(&self) -> Protocol {
+        *self.protocol.read().unwrap()
+    }
+
+    /// 使用配置中指定的协议，覆盖自动检测的结果
+    pub(crate) fn set_protocol(&self, protocol: Protocol) {
+        *self.protocol.write().unwrap() = protocol;
+    }
+
+    /// 在 max_cols x max_rows 个单元格内保持宽高比显示图片所需的列数和行数
+    pub(crate) fn fit(&self, width: u32, height: u32, max_cols: u16, max_rows: u16) -> (u16, u16) {
+        fit(width, height, max_cols, max_rows, self.cell_size)
+    }
+
+    /// 将图片编码为图形协议的转义序列，字符画返回 None
+    pub(crate) fn encode(
+        &self,
+        id: u32,
+        image: &DynamicImage,
+        cols: u16,
+        rows: u16,
+    ) -> Option<String> {
+        let (cell_width, cell_height) = self.cell_size;
+        let width = cols as u32 * cell_width as u32;
+        let height = rows as u32 * cell_height as u32;
+        let image = image.resize_exact(width, height, FilterType::Triangle);
+        let res = match self.protocol() {
+            Protocol::Halfblocks => return None,
+            Protocol::Kitty => png(&image).map(|data| kitty(id, &data, cols, rows)),
+            Protocol::Iterm2 => png(&image).map(|data| iterm2(&data, cols, rows)),
+            Protocol::Sixel => sixel(&image),
+        };
+        res.map_err(|err| warn!("fail to encode image: {err}")).ok()
+    }
+
+    /// 在 area 处显示图片，该区域的单元格不再由 ratatui 绘制
+    pub(crate) fn place(&self, buf: &mut Buffer, area: Rect, id: u32, seq: Arc<String>) {
+        let area = area.intersection(buf.area);
+        for y in area.top()..area.bottom() {
+            for x in area.left()..area.right() {
+                if let Some(cell) = buf.cell_mut((x, y)) {
+                    cell.set_skip(true);
+                }
+            }
+        }
+        self.placements
+            .lock()
+            .unwrap()
+            .pending
+            .push(Placement { id, area, seq });
+    }
+
+    /// 绘制结束后调用，被弹窗等其他组件覆盖的图片不再显示
+    pub(crate) fn retain_visible(&self, buf: &Buffer) {
+        let mut placements = self.placements.lock().unwrap();
+        if placements.area != buf.area {
+            placements.area = buf.area;
+            placements.shown.clear();
+        }
+        placements.pending.retain(|placement| {
+            placement
+                .area
+                .positions()
+                .all(|position| buf.cell(position).is_some_and(|cell| cell.skip))
+        });
+    }
+
+    /// 输出新增或移动的图片，kitty 协议需要删除不再显示的图片
+    pub(crate) fn flush(&self, w: &mut impl Write) -> io::Result<()> {
+        let mut placements = self.placements.lock().unwrap();
+        let pending = std::mem::take(&mut placements.pending);
+        let changed = pending.iter().any(|p| !placements.shown.contains(p))
+            || placements.shown.iter().any(|p| !pending.contains(p));
+        if !changed {
+            return Ok(());
+        }
+        queue!(w, SavePosition)?;
+        if self.protocol() == Protocol::Kitty {
+            for placement in placements.shown.iter().filter(|p| !pending.contains(p)) {
+                write!(w, "\x1b_Ga=d,d=i,i={},q=2\x1b\\", placement.id)?;
+            }
+        }
+        for placement in pending.iter().filter(|p| !placements.shown.contains(p)) {
+            queue!(w, MoveTo(placement.area.x, placement.area.y))?;
+            w.write_all(placement.seq.as_bytes())?;
+        }
+        queue!(w, RestorePosition)?;
+        placements.shown = pending;
+        w.flush()
+    }
+
+    /// 清屏后需要重新输出所有图片
+    pub(crate) fn invalidate(&self) {
+        self.placements.lock().unwrap().shown.clear();
+    }
+}
+
+fn fit(width: u32, height: u32, max_cols: u16, max_rows: u16, cell: (u16, u16)) -> (u16, u16) {
+    let cols = width.div_ceil(cell.0 as u32).max(1) as f64;
+    let rows = height.div_ceil(cell.1 as u32).max(1) as f64;
+    let scale = (max_cols as f64 / cols)
+        .min(max_rows as f64 / rows)
+        .min(1.0);
+    (
+        ((cols * scale).round() as u16).clamp(1, max_cols.max(1)),
+        ((rows * scale).round() as u16).clamp(1, max_rows.max(1)),
+    )
+}
+
+fn png(image: &DynamicImage) -> color_eyre::Result<Vec<u8>> {
+    let mut data = Vec::new();
+    image.write_to(&mut Cursor::new(&mut data), ImageFormat::Png)?;
+    Ok(data)
+}
+
+/// kitty 图形协议，数据分段传输，C=1 表示不移动光标
+fn kitty(id: u32, data: &[u8], cols: u16, rows: u16) -> String {
+    let encoded = STANDARD.encode(data);
+    let chunks = encoded.as_bytes().chunks(KITTY_CHUNK).collect::<Vec<_>>();
+    let mut seq = String::new();
+    for (i, chunk) in chunks.iter().enumerate() {
+        let more = (i + 1 < chunks.len()) as u8;
+        let chunk = std::str::from_utf8(chunk).unwrap();
+        if i == 0 {
+            seq.push_str(&format!(
+                "\x1b_Gf=100,a=T,i={id},p=1,c={cols},r={rows},C=1,q=2,m={more};{chunk}\x1b\\"
+            ));
+        } else {
+            seq.push_str(&format!("\x1b_Gm={more};{chunk}\x1b\\"));
+        }
+    }
+    seq
+}
+
+/// iTerm2 内联图片协议
+fn iterm2(data: &[u8], cols: u16, rows: u16) -> String {
+    format!(
+        "\x1b]1337;File=inline=1;size={};width={cols};height={rows};preserveAspectRatio=0:{}\x07",
+        data.len(),
+        STANDARD.encode(data)
+    )
+}
+
+fn sixel(image: &DynamicImage) -> color_eyre::Result<String> {
+    let rgb = image.to_rgb8();
+    icy_sixel::sixel_string(
+        rgb.as_raw(),
+        rgb.width() as i32,
+        rgb.height() as i32,
+        PixelFormat::RGB888,
+        DiffusionMethod::Stucki,
+        MethodForLargest::Auto,
+        MethodForRep::Auto,
+        Quality::HIGH,
+    )
+    .map_err(|err| color_eyre::eyre::format_err!("{err}"))
+}
+
+/// 半块字符画，每个单元格的上半部分为前景色，下半部分为背景色
+pub(crate) fn halfblocks(image: &DynamicImage, cols: u16, rows: u16) -> Vec<Line<'static>> {
+    let image = image
+        .resize_exact(cols as u32, rows as u32 * 2, FilterType::Triangle)
+        .to_rgb8();
+    (0..rows as u32)
+        .map(|y| {
+            let spans = (0..cols as u32)
+                .map(|x| {
+                    let top = image.get_pixel(x, y * 2);
+                    let bottom = image.get_pixel(x, y * 2 + 1);
+                    Span::styled(
+                        "▀",
+                        Style::default()
+                            .fg(Color::Rgb(top[0], top[1], top[2]))
+                            .bg(Color::Rgb(bottom[0], bottom[1], bottom[2])),
+                    )
+                })
+                .collect::<Vec<_>>();
+            Line::from(spans)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fit_keeps_aspect_ratio() {
+        // 小图按原始大小显示
+        assert_eq!(fit(80, 32, 40, 10, (8, 16)), (10, 2));
+        // 大图按比例缩小到可用区域内
+        assert_eq!(fit(800, 800, 40, 10, (8, 16)), (20, 10));
+        assert_eq!(fit(1600, 160, 40, 10, (8, 16)), (40, 2));
+        assert_eq!(fit(1, 1, 0, 0, (8, 16)), (1, 1));
+    }
+}
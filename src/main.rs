@@ -4,15 +4,19 @@ use color_eyre::Result;
 
 use crate::app::App;
 
+mod account;
 mod action;
 mod app;
 mod cli;
 mod components;
 mod config;
 mod datetime;
+mod e2e;
 mod errors;
+mod graphics;
 mod logging;
 mod proxy;
+mod session;
 mod token;
 mod tui;
 
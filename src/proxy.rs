@@ -1,15 +1,106 @@
+pub mod block;
+pub mod directory;
+pub mod file;
+pub mod forward;
 pub mod friend;
 pub mod group;
+pub mod presence;
+pub mod reaction;
+pub mod remark;
+pub mod token;
 pub mod user;
 
+use crate::config::ServerProfile;
 use color_eyre::eyre::format_err;
 use std::env;
-use std::sync::LazyLock;
+use std::fs;
+use std::sync::{LazyLock, RwLock};
 use tokio::task::spawn_blocking;
+use tracing::warn;
 
-pub(crate) static HOST: LazyLock<String> = LazyLock::new(|| {
-    env::var("CHAT_SERVER_HOST").ok().expect("host env not set")
-});
+// 当前账号所在的服务器
+pub(crate) static HOST: LazyLock<Server> = LazyLock::new(|| Server(RwLock::new(None)));
+
+pub(crate) struct Server(RwLock<Option<ServerProfile>>);
+
+impl Server {
+    pub(crate) fn url(&self) -> String {
+        url_of(self.0.read().unwrap().as_ref())
+    }
+
+    pub(crate) fn is_configured(&self) -> bool {
+        !self.url().is_empty()
+    }
+
+    pub(crate) fn profile(&self) -> Option<ServerProfile> {
+        self.0.read().unwrap().clone()
+    }
+
+    pub(crate) fn set_profile(&self, profile: Option<ServerProfile>) {
+        *self.0.write().unwrap() = profile;
+    }
+}
+
+/// 服务器地址，没有服务器配置时使用环境变量 CHAT_SERVER_HOST
+pub(crate) fn url_of(profile: Option<&ServerProfile>) -> String {
+    match profile {
+        Some(profile) => profile.url.trim_end_matches('/').to_string(),
+        None => env::var("CHAT_SERVER_HOST").unwrap_or_default(),
+    }
+}
+
+/// 读取服务器配置中的 CA 证书
+fn ca_cert(profile: &ServerProfile) -> Option<reqwest::Certificate> {
+    let path = profile.tls.ca_cert.as_ref()?;
+    match fs::read(path).map(|pem| reqwest::Certificate::from_pem(&pem)) {
+        Ok(Ok(cert)) => Some(cert),
+        Ok(Err(err)) => {
+            warn!("invalid ca cert {}: {err}", path.display());
+            None
+        }
+        Err(err) => {
+            warn!("fail to read ca cert {}: {err}", path.display());
+            None
+        }
+    }
+}
+
+/// 按服务器的 TLS 配置创建同步客户端
+pub(crate) fn client_for(profile: Option<&ServerProfile>) -> reqwest::blocking::Client {
+    let Some(profile) = profile else {
+        return reqwest::blocking::Client::new();
+    };
+    let mut builder = reqwest::blocking::Client::builder()
+        .danger_accept_invalid_certs(profile.tls.accept_invalid_certs);
+    if let Some(cert) = ca_cert(profile) {
+        builder = builder.add_root_certificate(cert);
+    }
+    builder.build().unwrap_or_else(|err| {
+        warn!("fail to build client for {}: {err}", profile.name);
+        reqwest::blocking::Client::new()
+    })
+}
+
+/// 按服务器的 TLS 配置创建异步客户端，用于事件流
+pub(crate) fn async_client_for(profile: Option<&ServerProfile>) -> reqwest::Client {
+    let Some(profile) = profile else {
+        return reqwest::Client::new();
+    };
+    let mut builder =
+        reqwest::Client::builder().danger_accept_invalid_certs(profile.tls.accept_invalid_certs);
+    if let Some(cert) = ca_cert(profile) {
+        builder = builder.add_root_certificate(cert);
+    }
+    builder.build().unwrap_or_else(|err| {
+        warn!("fail to build client for {}: {err}", profile.name);
+        reqwest::Client::new()
+    })
+}
+
+/// 当前服务器的同步客户端
+pub(crate) fn client() -> reqwest::blocking::Client {
+    client_for(HOST.profile().as_ref())
+}
 
 pub(crate) fn send_request<F, R>(f: F) -> color_eyre::Result<R>
 where
@@ -0,0 +1,122 @@
+use crate::datetime::datetime_format;
+use crate::proxy::{HOST, client, send_request};
+use crate::token::CURRENT_USER;
+use chrono::{DateTime, Local};
+use color_eyre::eyre::format_err;
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::sync::{LazyLock, Mutex};
+
+// 已屏蔽的用户，即使服务端仍推送其消息，客户端也不展示
+pub(crate) static BLOCKED: LazyLock<BlockList> =
+    LazyLock::new(|| BlockList(Mutex::new(HashSet::new())));
+
+pub(crate) struct BlockList(Mutex<HashSet<i32>>);
+
+impl BlockList {
+    pub(crate) fn contains(&self, uid: i32) -> bool {
+        self.0.lock().unwrap().contains(&uid)
+    }
+
+    pub(crate) fn clear(&self) {
+        self.0.lock().unwrap().clear();
+    }
+
+    pub(crate) fn take(&self) -> HashSet<i32> {
+        std::mem::take(&mut self.0.lock().unwrap())
+    }
+
+    pub(crate) fn restore(&self, uids: HashSet<i32>) {
+        *self.0.lock().unwrap() = uids;
+    }
+
+    fn reset(&self, uids: impl IntoIterator<Item = i32>) {
+        let mut guard = self.0.lock().unwrap();
+        guard.clear();
+        guard.extend(uids);
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub(crate) struct BlockedUser {
+    pub(crate) id: i32,
+    pub(crate) name: String,
+    /// 屏蔽时间
+    #[serde(with = "datetime_format")]
+    pub(crate) create_time: DateTime<Local>,
+}
+
+/// 获取屏蔽列表，并写入 BLOCKED
+pub(crate) fn blocked_users() -> color_eyre::Result<Vec<BlockedUser>> {
+    let users = send_request(move || {
+        let token = CURRENT_USER.get_user().token.clone().unwrap();
+        let res = client()
+            .get(format!("{}/user/block", HOST.url()))
+            .header("Authorization", format!("Bearer {token}"))
+            .send();
+        match res {
+            Ok(res) => match res.status() {
+                StatusCode::OK => {
+                    let res = res.json::<Vec<BlockedUser>>();
+                    res.map_err(|err| format_err!("Failed to get blocked users, err: {err}"))
+                }
+                _ => Err(format_err!(
+                    "Failed to get blocked users, status: {}",
+                    res.status()
+                )),
+            },
+            Err(err) => Err(format_err!("Failed to get blocked users, err: {err}")),
+        }
+    })??;
+    BLOCKED.reset(users.iter().map(|u| u.id));
+    Ok(users)
+}
+
+pub(crate) fn block(uid: i32) -> color_eyre::Result<()> {
+    send_request(move || {
+        let token = CURRENT_USER.get_user().token.clone().unwrap();
+        let res = client()
+            .put(format!("{}/user/block/{uid}", HOST.url()))
+            .header("Authorization", format!("Bearer {token}"))
+            .send();
+        match res {
+            Ok(res) => match res.status() {
+                StatusCode::OK => {
+                    BLOCKED.0.lock().unwrap().insert(uid);
+                    Ok(())
+                }
+                _ => Err(format_err!(
+                    "Failed to block user, uid: {uid}, status: {}",
+                    res.status()
+                )),
+            },
+            Err(err) => Err(format_err!("Failed to block user, uid: {uid}, err: {err}")),
+        }
+    })?
+}
+
+pub(crate) fn unblock(uid: i32) -> color_eyre::Result<()> {
+    send_request(move || {
+        let token = CURRENT_USER.get_user().token.clone().unwrap();
+        let res = client()
+            .delete(format!("{}/user/block/{uid}", HOST.url()))
+            .header("Authorization", format!("Bearer {token}"))
+            .send();
+        match res {
+            Ok(res) => match res.status() {
+                StatusCode::OK => {
+                    BLOCKED.0.lock().unwrap().remove(&uid);
+                    Ok(())
+                }
+                _ => Err(format_err!(
+                    "Failed to unblock user, uid: {uid}, status: {}",
+                    res.status()
+                )),
+            },
+            Err(err) => Err(format_err!(
+                "Failed to unblock user, uid: {uid}, err: {err}"
+            )),
+        }
+    })?
+}
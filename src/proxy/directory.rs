@@ -0,0 +1,183 @@
+use crate::proxy::remark::REMARKS;
+use crate::proxy::user;
+use crate::token::CURRENT_USER;
+use std::collections::HashMap;
+use std::sync::{Arc, LazyLock, Mutex};
+use std::time::{Duration, Instant};
+use tracing::warn;
+
+/// 用户名缓存的有效期
+const TTL: Duration = Duration::from_secs(10 * 60);
+/// 查询失败时，使用uid占位的有效期，避免对同一用户反复请求
+const FAILURE_TTL: Duration = Duration::from_secs(30);
+
+// 用户名目录，所有组件共享
+pub(crate) static USER_DIRECTORY: LazyLock<UserDirectory> =
+    LazyLock::new(|| UserDirectory::new(TTL, FAILURE_TTL));
+
+pub(crate) struct UserDirectory {
+    entries: Mutex<HashMap<i32, Entry>>,
+    /// 正在查询的用户，同一用户的并发查询共用一次请求
+    in_flight: Mutex<HashMap<i32, Arc<Mutex<()>>>>,
+    ttl: Duration,
+    failure_ttl: Duration,
+}
+
+pub(crate) struct Entry {
+    name: String,
+    expire_at: Instant,
+}
+
+impl UserDirectory {
+    fn new(ttl: Duration, failure_ttl: Duration) -> Self {
+        Self {
+            entries: Mutex::new(HashMap::new()),
+            in_flight: Mutex::new(HashMap::new()),
+            ttl,
+            failure_ttl,
+        }
+    }
+
+    /// 退出账号时清空，不同服务器的 uid 不通用
+    pub(crate) fn clear(&self) {
+        self.entries.lock().unwrap().clear();
+    }
+
+    /// 切换账号时取出当前账号的缓存
+    pub(crate) fn take(&self) -> HashMap<i32, Entry> {
+        std::mem::take(&mut self.entries.lock().unwrap())
+    }
+
+    /// 切换回该账号时恢复缓存
+    pub(crate) fn restore(&self, entries: HashMap<i32, Entry>) {
+        *self.entries.lock().unwrap() = entries;
+    }
+
+    /// 使用已知的用户名预热缓存，如好友列表、群成员列表
+    pub(crate) fn seed(&self, uid: i32, name: String) {
+        self.insert(uid, name, self.ttl);
+    }
+
+    /// 获取显示名称，优先使用好友备注，查询失败时返回uid
+    pub(crate) fn name(&self, uid: i32) -> String {
+        if let Some(remark) = REMARKS.get(uid).remark {
+            return remark;
+        }
+        if let Some(user) = CURRENT_USER.get_user().user
+            && user.id == uid
+        {
+            return user.name;
+        }
+        self.name_with(uid, |uid| user::detail_by_id(uid).map(|detail| detail.name))
+    }
+
+    fn name_with<F>(&self, uid: i32, fetch: F) -> String
+    where
+        F: FnOnce(i32) -> color_eyre::Result<String>,
+    {
+        if let Some(name) = self.cached(uid) {
+            return name;
+        }
+        let lock = self
+            .in_flight
+            .lock()
+            .unwrap()
+            .entry(uid)
+            .or_default()
+            .clone();
+        let _guard = lock.lock().unwrap();
+        // 等待期间，其他调用者可能已经查询完成
+        if let Some(name) = self.cached(uid) {
+            return name;
+        }
+        let name = match fetch(uid) {
+            Ok(name) => {
+                self.insert(uid, name.clone(), self.ttl);
+                name
+            }
+            Err(err) => {
+                warn!("fail to get user name, uid: {uid}, err: {err}");
+                self.insert(uid, uid.to_string(), self.failure_ttl);
+                uid.to_string()
+            }
+        };
+        self.in_flight.lock().unwrap().remove(&uid);
+        name
+    }
+
+    fn cached(&self, uid: i32) -> Option<String> {
+        self.entries
+            .lock()
+            .unwrap()
+            .get(&uid)
+            .filter(|entry| entry.expire_at > Instant::now())
+            .map(|entry| entry.name.clone())
+    }
+
+    fn insert(&self, uid: i32, name: String, ttl: Duration) {
+        self.entries.lock().unwrap().insert(
+            uid,
+            Entry {
+                name,
+                expire_at: Instant::now() + ttl,
+            },
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use color_eyre::eyre::format_err;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::thread;
+
+    #[test]
+    fn test_seeded_name_is_cached() {
+        let directory = UserDirectory::new(TTL, FAILURE_TTL);
+        directory.seed(1, "alice".to_string());
+        let name = directory.name_with(1, |_| panic!("should not fetch"));
+        assert_eq!(name, "alice");
+    }
+
+    #[test]
+    fn test_expired_name_is_fetched_again() {
+        let directory = UserDirectory::new(Duration::ZERO, FAILURE_TTL);
+        directory.seed(1, "alice".to_string());
+        let name = directory.name_with(1, |_| Ok("bob".to_string()));
+        assert_eq!(name, "bob");
+    }
+
+    #[test]
+    fn test_failed_lookup_falls_back_to_uid() {
+        let directory = UserDirectory::new(TTL, FAILURE_TTL);
+        let name = directory.name_with(42, |uid| Err(format_err!("no user {uid}")));
+        assert_eq!(name, "42");
+        // 失败结果同样被缓存
+        let name = directory.name_with(42, |_| panic!("should not fetch"));
+        assert_eq!(name, "42");
+    }
+
+    #[test]
+    fn test_concurrent_lookups_are_coalesced() {
+        let directory = Arc::new(UserDirectory::new(TTL, FAILURE_TTL));
+        let fetched = Arc::new(AtomicUsize::new(0));
+        let handles = (0..8)
+            .map(|_| {
+                let directory = directory.clone();
+                let fetched = fetched.clone();
+                thread::spawn(move || {
+                    directory.name_with(7, |_| {
+                        fetched.fetch_add(1, Ordering::SeqCst);
+                        thread::sleep(Duration::from_millis(50));
+                        Ok("carol".to_string())
+                    })
+                })
+            })
+            .collect::<Vec<_>>();
+        for handle in handles {
+            assert_eq!(handle.join().unwrap(), "carol");
+        }
+        assert_eq!(fetched.load(Ordering::SeqCst), 1);
+    }
+}
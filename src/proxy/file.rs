@@ -0,0 +1,283 @@
+use crate::config::{get_data_dir, profile_key};
+use crate::proxy::{HOST, client};
+use crate::token::CURRENT_USER;
+use color_eyre::eyre::format_err;
+use reqwest::StatusCode;
+use reqwest::blocking::Body;
+use reqwest::header::{CONTENT_TYPE, RANGE};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// 缓存用于预览的附件大小上限
+const MAX_CACHED: u64 = 20 * 1024 * 1024;
+
+/// 附件消息的 content_type，消息内容为附件信息的 JSON
+pub(crate) const ATTACHMENT: &str = "attachment";
+
+/// 已上传的附件
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub(crate) struct Attachment {
+    /// 服务端文件id
+    pub(crate) id: String,
+    pub(crate) name: String,
+    /// 文件大小，单位字节
+    pub(crate) size: u64,
+    /// MIME 类型
+    pub(crate) mime: String,
+}
+
+impl Attachment {
+    /// 从消息解析附件，非附件消息返回 None
+    pub(crate) fn from_msg(content_type: Option<&str>, msg: &str) -> Option<Self> {
+        if content_type != Some(ATTACHMENT) {
+            return None;
+        }
+        serde_json::from_str(msg).ok()
+    }
+
+    pub(crate) fn to_msg(&self) -> String {
+        serde_json::to_string(self).unwrap()
+    }
+
+    /// 最近聊天中的消息预览
+    pub(crate) fn summary(&self) -> String {
+        format!("[文件] {}", self.name)
+    }
+
+    /// 用于本地路径的文件id，含路径分隔符或 `..` 的id视为非法
+    fn safe_id(&self) -> color_eyre::Result<&str> {
+        match Path::new(&self.id).file_name() {
+            Some(name) if name == self.id.as_str() => Ok(&self.id),
+            _ => Err(format_err!("invalid attachment id: {}", self.id)),
+        }
+    }
+}
+
+/// 可读的文件大小，如 1.5 MB
+pub(crate) fn format_size(size: u64) -> String {
+    const UNITS: [&str; 4] = ["KB", "MB", "GB", "TB"];
+    if size < 1024 {
+        return format!("{size} B");
+    }
+    let mut value = size as f64 / 1024.0;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    format!("{value:.1} {}", UNITS[unit])
+}
+
+/// 读取时累计已读字节数，用于显示上传进度
+struct ProgressReader<R> {
+    inner: R,
+    progress: Arc<AtomicU64>,
+}
+
+impl<R: Read> Read for ProgressReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.progress.fetch_add(n as u64, Ordering::Relaxed);
+        Ok(n)
+    }
+}
+
+/// 上传文件，progress 记录已上传的字节数
+pub(crate) fn upload(path: &Path, progress: Arc<AtomicU64>) -> color_eyre::Result<Attachment> {
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .ok_or_else(|| format_err!("无效的文件路径：{}", path.display()))?;
+    let file = File::open(path)?;
+    let size = file.metadata()?.len();
+    let mime = mime_guess::from_path(path)
+        .first_or_octet_stream()
+        .to_string();
+    let token = CURRENT_USER.get_user().token.clone().unwrap();
+    let body = Body::sized(
+        ProgressReader {
+            inner: file,
+            progress,
+        },
+        size,
+    );
+    let res = client()
+        .post(format!("{}/file", HOST.url()))
+        .query(&[("name", name.as_str())])
+        .header("Authorization", format!("Bearer {token}"))
+        .header(CONTENT_TYPE, mime.as_str())
+        .body(body)
+        .send();
+    match res {
+        Ok(res) => match res.status() {
+            StatusCode::OK => res
+                .json::<Attachment>()
+                .map_err(|err| format_err!("Failed to upload file, err: {err}")),
+            StatusCode::PAYLOAD_TOO_LARGE => Err(format_err!("文件过大：{}", format_size(size))),
+            _ => Err(format_err!(
+                "Failed to upload file, status: {}",
+                res.status()
+            )),
+        },
+        Err(err) => Err(format_err!("Failed to upload file, err: {err}")),
+    }
+}
+
+/// 下载附件到 dir，未下载完的部分保存为临时文件，再次下载时从断点继续
+pub(crate) fn download(
+    attachment: &Attachment,
+    dir: &Path,
+    progress: Arc<AtomicU64>,
+) -> color_eyre::Result<PathBuf> {
+    let id = attachment.safe_id()?;
+    fs::create_dir_all(dir)?;
+    // 只取文件名，避免写到下载目录之外
+    let name = Path::new(&attachment.name)
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| id.to_string());
+    let part = dir.join(format!(".{name}.{id}.part"));
+    let downloaded = fs::metadata(&part).map(|meta| meta.len()).unwrap_or(0);
+    progress.store(downloaded, Ordering::Relaxed);
+
+    if downloaded < attachment.size || !part.exists() {
+        let token = CURRENT_USER.get_user().token.clone().unwrap();
+        let mut req = client()
+            .get(format!("{}/file/{}", HOST.url(), id))
+            .header("Authorization", format!("Bearer {token}"));
+        if downloaded > 0 {
+            req = req.header(RANGE, format!("bytes={downloaded}-"));
+        }
+        let mut res = req
+            .send()
+            .map_err(|err| format_err!("Failed to download file, err: {err}"))?;
+        let mut file = match res.status() {
+            StatusCode::PARTIAL_CONTENT => OpenOptions::new().append(true).open(&part)?,
+            // 服务端不支持断点续传时重新下载
+            StatusCode::OK => {
+                progress.store(0, Ordering::Relaxed);
+                File::create(&part)?
+            }
+            status => return Err(format_err!("Failed to download file, status: {status}")),
+        };
+        let mut buf = [0u8; 64 * 1024];
+        loop {
+            let n = res.read(&mut buf)?;
+            if n == 0 {
+                break;
+            }
+            file.write_all(&buf[..n])?;
+            progress.fetch_add(n as u64, Ordering::Relaxed);
+        }
+        file.flush()?;
+    }
+
+    let target = unique_path(dir, &name);
+    fs::rename(&part, &target)?;
+    Ok(target)
+}
+
+/// 下载附件到缓存目录，已缓存时直接返回，用于图片预览
+pub(crate) fn fetch_cached(attachment: &Attachment) -> color_eyre::Result<PathBuf> {
+    // 不同服务器的文件id可能相同，按服务器配置区分缓存目录
+    let profile = HOST.profile().map(|profile| profile.name);
+    let dir = get_data_dir()
+        .join("attachments")
+        .join(profile_key(profile.as_deref()));
+    let id = attachment.safe_id()?;
+    let path = dir.join(id);
+    if path.exists() {
+        return Ok(path);
+    }
+    if attachment.size > MAX_CACHED {
+        return Err(format_err!(
+            "文件过大，不预览：{}",
+            format_size(attachment.size)
+        ));
+    }
+    fs::create_dir_all(&dir)?;
+    let token = CURRENT_USER.get_user().token.clone().unwrap();
+    let res = client()
+        .get(format!("{}/file/{id}", HOST.url()))
+        .header("Authorization", format!("Bearer {token}"))
+        .send()
+        .map_err(|err| format_err!("Failed to download file, err: {err}"))?;
+    if res.status() != StatusCode::OK {
+        return Err(format_err!(
+            "Failed to download file, status: {}",
+            res.status()
+        ));
+    }
+    // 边下载边写入临时文件，超过上限时放弃
+    let part = dir.join(format!(".{id}.part"));
+    let written = io::copy(&mut res.take(MAX_CACHED + 1), &mut File::create(&part)?)?;
+    if written > MAX_CACHED {
+        fs::remove_file(&part)?;
+        return Err(format_err!("文件过大，不预览：{}", attachment.name));
+    }
+    fs::rename(&part, &path)?;
+    Ok(path)
+}
+
+/// 下载目录中已有同名文件时，在文件名后追加序号
+fn unique_path(dir: &Path, name: &str) -> PathBuf {
+    let path = dir.join(name);
+    if !path.exists() {
+        return path;
+    }
+    let name = Path::new(name);
+    let stem = name
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let ext = name
+        .extension()
+        .map(|ext| format!(".{}", ext.to_string_lossy()))
+        .unwrap_or_default();
+    (1..)
+        .map(|i| dir.join(format!("{stem} ({i}){ext}")))
+        .find(|path| !path.exists())
+        .unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn attachment_msg() {
+        let attachment = Attachment {
+            id: "f1".to_string(),
+            name: "报告.pdf".to_string(),
+            size: 1536,
+            mime: "application/pdf".to_string(),
+        };
+        let msg = attachment.to_msg();
+        assert_eq!(
+            Attachment::from_msg(Some(ATTACHMENT), &msg),
+            Some(attachment)
+        );
+        assert_eq!(Attachment::from_msg(None, &msg), None);
+        assert_eq!(format_size(1536), "1.5 KB");
+        assert_eq!(format_size(10), "10 B");
+    }
+
+    #[test]
+    fn reject_unsafe_id() {
+        let mut attachment = Attachment {
+            id: "f1".to_string(),
+            name: "a.txt".to_string(),
+            size: 0,
+            mime: "text/plain".to_string(),
+        };
+        assert_eq!(attachment.safe_id().unwrap(), "f1");
+        for id in ["../f1", "a/b", "..", "/etc/passwd", ""] {
+            attachment.id = id.to_string();
+            assert!(attachment.safe_id().is_err(), "{id}");
+        }
+    }
+}
@@ -0,0 +1,76 @@
+use crate::datetime::datetime_format;
+use crate::proxy::file::Attachment;
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+
+/// 转发消息的 content_type，消息内容为 [Forwarded] 的 json
+pub(crate) const FORWARD: &str = "forward";
+
+/// 转发的消息，保留原发送者和发送时间
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub(crate) struct Forwarded {
+    pub(crate) from_uid: i32,
+    pub(crate) from_name: String,
+    #[serde(with = "datetime_format")]
+    pub(crate) time: DateTime<Local>,
+    /// 原消息内容
+    pub(crate) msg: String,
+    /// 原消息类型，文本消息为空
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) content_type: Option<String>,
+}
+
+impl Forwarded {
+    pub(crate) fn from_msg(content_type: Option<&str>, msg: &str) -> Option<Self> {
+        if content_type != Some(FORWARD) {
+            return None;
+        }
+        serde_json::from_str(msg).ok()
+    }
+
+    pub(crate) fn to_msg(&self) -> String {
+        serde_json::to_string(self).unwrap()
+    }
+
+    /// 转发的附件
+    pub(crate) fn attachment(&self) -> Option<Attachment> {
+        Attachment::from_msg(self.content_type.as_deref(), &self.msg)
+    }
+
+    /// 最近聊天中的消息预览
+    pub(crate) fn summary(&self) -> String {
+        let content = match self.attachment() {
+            Some(attachment) => attachment.summary(),
+            None => self.msg.clone(),
+        };
+        format!("[转发] {}: {content}", self.from_name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::proxy::file::ATTACHMENT;
+
+    #[test]
+    fn forwarded_msg() {
+        let attachment = Attachment {
+            id: "f1".to_string(),
+            name: "a.txt".to_string(),
+            size: 3,
+            mime: "text/plain".to_string(),
+        };
+        let forwarded = Forwarded {
+            from_uid: 1,
+            from_name: "alice".to_string(),
+            time: Local::now(),
+            msg: attachment.to_msg(),
+            content_type: Some(ATTACHMENT.to_string()),
+        };
+        let msg = forwarded.to_msg();
+        let parsed = Forwarded::from_msg(Some(FORWARD), &msg).unwrap();
+        assert_eq!(parsed.attachment(), Some(attachment));
+        assert_eq!(parsed.summary(), "[转发] alice: [文件] a.txt");
+        assert_eq!(Forwarded::from_msg(None, &msg), None);
+    }
+}
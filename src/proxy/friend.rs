@@ -1,26 +1,33 @@
 use crate::datetime::datetime_format;
 use crate::proxy::send_request;
 use crate::proxy::HOST;
+use crate::proxy::client;
 use crate::token::CURRENT_USER;
 use chrono::{DateTime, Local};
 use color_eyre::eyre::format_err;
-use reqwest::blocking::Client;
 use reqwest::StatusCode;
 use serde::{Deserialize, Serialize};
+use std::sync::atomic::AtomicUsize;
 use strum::Display;
 
-#[derive(Serialize, Deserialize)]
+#[derive(Clone, Serialize, Deserialize)]
 pub(crate) struct Friend {
     pub(crate) id: i32,
     pub(crate) name: String,
+    /// 备注
+    #[serde(default)]
+    pub(crate) remark: Option<String>,
+    /// 标签
+    #[serde(default)]
+    pub(crate) tags: Vec<String>,
 }
 
 pub(crate) fn friends() -> color_eyre::Result<Vec<Friend>> {
     send_request(move || {
         let current_user = CURRENT_USER.get_user();
         let token = current_user.token.clone().unwrap();
-        let res = Client::new()
-            .get(format!("{}/friend", HOST.as_str()))
+        let res = client()
+            .get(format!("{}/friend", HOST.url()))
             .header("Authorization", format!("Bearer {token}"))
             .send();
         match res {
@@ -50,6 +57,9 @@ pub(crate) fn friends() -> color_eyre::Result<Vec<Friend>> {
     })?
 }
 
+/// 待处理的好友请求数量，用于导航栏角标
+pub(crate) static PENDING_FRIEND_REQS: AtomicUsize = AtomicUsize::new(0);
+
 #[derive(Serialize, Deserialize)]
 pub(crate) struct FriendReq {
     pub(crate) id: i32,
@@ -76,8 +86,8 @@ pub(crate) fn friend_reqs() -> color_eyre::Result<Vec<FriendReq>> {
     send_request(move || {
         let current_user = CURRENT_USER.get_user();
         let token = current_user.token.clone().unwrap();
-        let res = Client::new()
-            .get(format!("{}/friend/req", HOST.as_str()))
+        let res = client()
+            .get(format!("{}/friend/req", HOST.url()))
             .header("Authorization", format!("Bearer {token}"))
             .send();
         match res {
@@ -107,14 +117,54 @@ pub(crate) fn friend_reqs() -> color_eyre::Result<Vec<FriendReq>> {
     })?
 }
 
-pub(crate) fn add_friend(uid: i32, friend_uid: i32) -> color_eyre::Result<()> {
+/// 发出的好友请求
+#[derive(Serialize, Deserialize)]
+pub(crate) struct SentFriendReq {
+    pub(crate) id: i32,
+    pub(crate) target_id: i32,
+    pub(crate) target_name: String,
+    #[serde(with = "datetime_format")]
+    pub(crate) create_time: DateTime<Local>,
+    pub(crate) reason: Option<String>,
+    pub(crate) status: FriendRequestStatus,
+}
+
+pub(crate) fn sent_friend_reqs() -> color_eyre::Result<Vec<SentFriendReq>> {
+    send_request(move || {
+        let current_user = CURRENT_USER.get_user();
+        let token = current_user.token.clone().unwrap();
+        let res = client()
+            .get(format!("{}/friend/req/sent", HOST.url()))
+            .header("Authorization", format!("Bearer {token}"))
+            .send();
+        match res {
+            Ok(res) => match res.status() {
+                StatusCode::OK => {
+                    let res = res.json::<Vec<SentFriendReq>>();
+                    res.map_err(|err| format_err!("Failed to get sent friend reqs, err: {err}"))
+                }
+                _ => Err(format_err!(
+                    "Failed to get sent friend reqs, status: {}",
+                    res.status()
+                )),
+            },
+            Err(err) => Err(format_err!("Failed to get sent friend reqs, err: {err}")),
+        }
+    })?
+}
+
+pub(crate) fn add_friend(
+    uid: i32,
+    friend_uid: i32,
+    reason: Option<String>,
+) -> color_eyre::Result<()> {
     send_request(move || {
         let current_user = CURRENT_USER.get_user();
         let token = current_user.token.clone().unwrap();
-        let res = Client::new()
-            .post(format!("{}/friend/req/{friend_uid}", HOST.as_str()))
+        let res = client()
+            .post(format!("{}/friend/req/{friend_uid}", HOST.url()))
             .header("Authorization", format!("Bearer {token}"))
-            .json(&serde_json::json!({}))
+            .json(&serde_json::json!({ "reason": reason }))
             .send();
         match res {
             Ok(res) => match res.status() {
@@ -134,6 +184,29 @@ pub(crate) fn add_friend(uid: i32, friend_uid: i32) -> color_eyre::Result<()> {
     })?
 }
 
+pub(crate) fn delete_friend(friend_uid: i32) -> color_eyre::Result<()> {
+    send_request(move || {
+        let current_user = CURRENT_USER.get_user();
+        let token = current_user.token.clone().unwrap();
+        let res = client()
+            .delete(format!("{}/friend/{friend_uid}", HOST.url()))
+            .header("Authorization", format!("Bearer {token}"))
+            .send();
+        match res {
+            Ok(res) => match res.status() {
+                StatusCode::OK => Ok(()),
+                _ => Err(format_err!(
+                    "Failed to delete friend, friend_uid: {friend_uid}, status: {}",
+                    res.status()
+                )),
+            },
+            Err(err) => Err(format_err!(
+                "Failed to delete friend, friend_uid: {friend_uid}, err: {err}"
+            )),
+        }
+    })?
+}
+
 pub(crate) fn review_friend_req(
     req_id: i32,
     status: FriendRequestStatus,
@@ -141,8 +214,8 @@ pub(crate) fn review_friend_req(
     send_request(move || {
         let current_user = CURRENT_USER.get_user();
         let token = current_user.token.clone().unwrap();
-        let res = Client::new()
-            .post(format!("{}/friend/req", HOST.as_str()))
+        let res = client()
+            .post(format!("{}/friend/req", HOST.url()))
             .header("Authorization", format!("Bearer {token}"))
             .json(&serde_json::json!({
                 "id": req_id,
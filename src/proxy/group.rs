@@ -1,32 +1,46 @@
+use crate::datetime::{datetime_format, opt_datetime_format};
 use crate::proxy::send_request;
 use crate::proxy::HOST;
+use crate::proxy::client;
 use crate::token::CURRENT_USER;
+use chrono::{DateTime, Local};
 use color_eyre::eyre::format_err;
-use reqwest::blocking::Client;
 use reqwest::StatusCode;
 use serde::{Deserialize, Serialize};
+use std::time::Duration;
 
 #[derive(Serialize, Deserialize)]
 pub(crate) struct DetailRes {
     pub(crate) group_id: i32,
     pub(crate) name: String,
     pub(crate) users: Vec<GroupUser>,
+    /// 是否开启全员禁言，管理员除外
+    #[serde(default)]
+    pub(crate) forbid_all: bool,
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Clone, Serialize, Deserialize)]
 pub(crate) struct GroupUser {
     pub(crate) id: i32,
     pub(crate) name: String,
     pub(crate) admin: bool,
     pub(crate) forbid: bool,
+    #[serde(default)]
+    pub(crate) owner: bool,
+    /// 禁言截止时间，为空表示永久禁言
+    #[serde(default, with = "opt_datetime_format")]
+    pub(crate) forbid_until: Option<DateTime<Local>>,
+    /// 入群时间
+    #[serde(default, with = "opt_datetime_format")]
+    pub(crate) join_time: Option<DateTime<Local>>,
 }
 
 pub(crate) fn detail(gid: i32) -> color_eyre::Result<DetailRes> {
     send_request(move || {
         let current_user = CURRENT_USER.get_user();
         let token = current_user.token.clone().unwrap();
-        let res = Client::new()
-            .get(format!("{}/group/{gid}", HOST.as_str()))
+        let res = client()
+            .get(format!("{}/group/{gid}", HOST.url()))
             .header("Authorization", format!("Bearer {token}"))
             .send();
         match res {
@@ -49,12 +63,53 @@ pub(crate) fn detail(gid: i32) -> color_eyre::Result<DetailRes> {
     })?
 }
 
+/// Create group request
+#[derive(Serialize)]
+struct CreateGroupReq {
+    /// Group name
+    name: String,
+    /// Initial members, except the creator
+    members: Vec<i32>,
+}
+
+pub(crate) fn create(name: String, members: Vec<i32>) -> color_eyre::Result<i32> {
+    send_request(move || {
+        let current_user = CURRENT_USER.get_user();
+        let token = current_user.token.clone().unwrap();
+        let res = client()
+            .post(format!("{}/group", HOST.url()))
+            .header("Authorization", format!("Bearer {token}"))
+            .json(&CreateGroupReq {
+                name: name.clone(),
+                members,
+            })
+            .send();
+        match res {
+            Ok(res) => match res.status() {
+                StatusCode::OK => {
+                    let res = res.json::<i32>();
+                    res.map_err(|err| {
+                        format_err!("Failed to create group, name: {name}, err: {err}")
+                    })
+                }
+                _ => Err(format_err!(
+                    "Failed to create group, name: {name}, status: {}",
+                    res.status()
+                )),
+            },
+            Err(err) => Err(format_err!(
+                "Failed to create group, name: {name}, err: {err}"
+            )),
+        }
+    })?
+}
+
 pub(crate) fn invite(uid: i32, gid: i32) -> color_eyre::Result<()> {
     send_request(move || {
         let current_user = CURRENT_USER.get_user();
         let token = current_user.token.clone().unwrap();
-        let res = Client::new()
-            .put(format!("{}/group/{gid}/{uid}", HOST.as_str()))
+        let res = client()
+            .put(format!("{}/group/{gid}/{uid}", HOST.url()))
             .header("Authorization", format!("Bearer {token}"))
             .send();
         match res {
@@ -76,8 +131,8 @@ pub(crate) fn evict(gid: i32, uid: i32) -> color_eyre::Result<()> {
     send_request(move || {
         let current_user = CURRENT_USER.get_user();
         let token = current_user.token.clone().unwrap();
-        let res = Client::new()
-            .delete(format!("{}/group/{gid}/{uid}", HOST.as_str()))
+        let res = client()
+            .delete(format!("{}/group/{gid}/{uid}", HOST.url()))
             .header("Authorization", format!("Bearer {token}"))
             .send();
         match res {
@@ -95,13 +150,22 @@ pub(crate) fn evict(gid: i32, uid: i32) -> color_eyre::Result<()> {
     })?
 }
 
-pub(crate) fn forbid(gid: i32, uid: i32) -> color_eyre::Result<()> {
+#[derive(Serialize)]
+struct ForbidReq {
+    /// 禁言时长，单位秒，为空表示永久禁言
+    duration: Option<u64>,
+}
+
+pub(crate) fn forbid(gid: i32, uid: i32, duration: Option<Duration>) -> color_eyre::Result<()> {
     send_request(move || {
         let current_user = CURRENT_USER.get_user();
         let token = current_user.token.clone().unwrap();
-        let res = Client::new()
-            .put(format!("{}/group/{gid}/forbid/{uid}", HOST.as_str()))
+        let res = client()
+            .put(format!("{}/group/{gid}/forbid/{uid}", HOST.url()))
             .header("Authorization", format!("Bearer {token}"))
+            .json(&ForbidReq {
+                duration: duration.map(|d| d.as_secs()),
+            })
             .send();
         match res {
             Ok(res) => match res.status() {
@@ -122,8 +186,8 @@ pub(crate) fn un_forbid(gid: i32, uid: i32) -> color_eyre::Result<()> {
     send_request(move || {
         let current_user = CURRENT_USER.get_user();
         let token = current_user.token.clone().unwrap();
-        let res = Client::new()
-            .delete(format!("{}/group/{gid}/forbid/{uid}", HOST.as_str()))
+        let res = client()
+            .delete(format!("{}/group/{gid}/forbid/{uid}", HOST.url()))
             .header("Authorization", format!("Bearer {token}"))
             .send();
         match res {
@@ -141,12 +205,43 @@ pub(crate) fn un_forbid(gid: i32, uid: i32) -> color_eyre::Result<()> {
     })?
 }
 
+/// 开启或关闭全员禁言，管理员不受影响
+pub(crate) fn forbid_all(gid: i32, enable: bool) -> color_eyre::Result<()> {
+    send_request(move || {
+        let current_user = CURRENT_USER.get_user();
+        let token = current_user.token.clone().unwrap();
+        let url = format!("{}/group/{gid}/forbid", HOST.url());
+        let client = client();
+        let req = if enable {
+            client.put(url)
+        } else {
+            client.delete(url)
+        };
+        let res = req
+            .header("Authorization", format!("Bearer {token}"))
+            .send();
+        match res {
+            Ok(res) => match res.status() {
+                StatusCode::OK => Ok(()),
+                StatusCode::FORBIDDEN => Err(format_err!("{}", res.text()?)),
+                _ => Err(format_err!(
+                    "Failed to set forbid all, gid: {gid}, enable: {enable}, status: {}",
+                    res.status()
+                )),
+            },
+            Err(err) => Err(format_err!(
+                "Failed to set forbid all, gid: {gid}, enable: {enable}, err: {err}",
+            )),
+        }
+    })?
+}
+
 pub(crate) fn set_manager(gid: i32, uid: i32) -> color_eyre::Result<()> {
     send_request(move || {
         let current_user = CURRENT_USER.get_user();
         let token = current_user.token.clone().unwrap();
-        let res = Client::new()
-            .patch(format!("{}/group/{gid}/admin/{uid}", HOST.as_str()))
+        let res = client()
+            .patch(format!("{}/group/{gid}/admin/{uid}", HOST.url()))
             .header("Authorization", format!("Bearer {token}"))
             .send();
         match res {
@@ -163,3 +258,269 @@ pub(crate) fn set_manager(gid: i32, uid: i32) -> color_eyre::Result<()> {
         }
     })?
 }
+
+pub(crate) fn revoke_manager(gid: i32, uid: i32) -> color_eyre::Result<()> {
+    send_request(move || {
+        let current_user = CURRENT_USER.get_user();
+        let token = current_user.token.clone().unwrap();
+        let res = client()
+            .delete(format!("{}/group/{gid}/admin/{uid}", HOST.url()))
+            .header("Authorization", format!("Bearer {token}"))
+            .send();
+        match res {
+            Ok(res) => match res.status() {
+                StatusCode::OK => Ok(()),
+                _ => Err(format_err!(
+                    "Failed to revoke manager of group member, gid: {gid}, uid: {uid}, status: {}",
+                    res.status()
+                )),
+            },
+            Err(err) => Err(format_err!(
+                "Failed to revoke manager of group member, gid: {gid}, uid: {uid}, err: {err}",
+            )),
+        }
+    })?
+}
+
+pub(crate) fn transfer_owner(gid: i32, uid: i32) -> color_eyre::Result<()> {
+    send_request(move || {
+        let current_user = CURRENT_USER.get_user();
+        let token = current_user.token.clone().unwrap();
+        let res = client()
+            .put(format!("{}/group/{gid}/owner/{uid}", HOST.url()))
+            .header("Authorization", format!("Bearer {token}"))
+            .send();
+        match res {
+            Ok(res) => match res.status() {
+                StatusCode::OK => Ok(()),
+                _ => Err(format_err!(
+                    "Failed to transfer group owner, gid: {gid}, uid: {uid}, status: {}",
+                    res.status()
+                )),
+            },
+            Err(err) => Err(format_err!(
+                "Failed to transfer group owner, gid: {gid}, uid: {uid}, err: {err}",
+            )),
+        }
+    })?
+}
+
+pub(crate) fn leave(gid: i32) -> color_eyre::Result<()> {
+    send_request(move || {
+        let current_user = CURRENT_USER.get_user();
+        let token = current_user.token.clone().unwrap();
+        let res = client()
+            .delete(format!("{}/group/{gid}/leave", HOST.url()))
+            .header("Authorization", format!("Bearer {token}"))
+            .send();
+        match res {
+            Ok(res) => match res.status() {
+                StatusCode::OK => Ok(()),
+                _ => Err(format_err!(
+                    "Failed to leave group, gid: {gid}, status: {}",
+                    res.status()
+                )),
+            },
+            Err(err) => Err(format_err!("Failed to leave group, gid: {gid}, err: {err}",)),
+        }
+    })?
+}
+
+pub(crate) fn dissolve(gid: i32) -> color_eyre::Result<()> {
+    send_request(move || {
+        let current_user = CURRENT_USER.get_user();
+        let token = current_user.token.clone().unwrap();
+        let res = client()
+            .delete(format!("{}/group/{gid}", HOST.url()))
+            .header("Authorization", format!("Bearer {token}"))
+            .send();
+        match res {
+            Ok(res) => match res.status() {
+                StatusCode::OK => Ok(()),
+                _ => Err(format_err!(
+                    "Failed to dissolve group, gid: {gid}, status: {}",
+                    res.status()
+                )),
+            },
+            Err(err) => Err(format_err!(
+                "Failed to dissolve group, gid: {gid}, err: {err}",
+            )),
+        }
+    })?
+}
+
+pub(crate) fn rename(gid: i32, name: String) -> color_eyre::Result<()> {
+    send_request(move || {
+        let current_user = CURRENT_USER.get_user();
+        let token = current_user.token.clone().unwrap();
+        let res = client()
+            .patch(format!("{}/group/{gid}", HOST.url()))
+            .header("Authorization", format!("Bearer {token}"))
+            .json(&serde_json::json!({ "name": name }))
+            .send();
+        match res {
+            Ok(res) => match res.status() {
+                StatusCode::OK => Ok(()),
+                _ => Err(format_err!(
+                    "Failed to rename group, gid: {gid}, name: {name}, status: {}",
+                    res.status()
+                )),
+            },
+            Err(err) => Err(format_err!(
+                "Failed to rename group, gid: {gid}, name: {name}, err: {err}",
+            )),
+        }
+    })?
+}
+
+/// Group announcement
+#[derive(Clone, Serialize, Deserialize)]
+pub(crate) struct Announcement {
+    pub(crate) content: String,
+    /// Name of the admin who last edited the announcement
+    pub(crate) update_by: String,
+    #[serde(with = "datetime_format")]
+    pub(crate) update_time: DateTime<Local>,
+}
+
+pub(crate) fn announcement(gid: i32) -> color_eyre::Result<Option<Announcement>> {
+    send_request(move || {
+        let current_user = CURRENT_USER.get_user();
+        let token = current_user.token.clone().unwrap();
+        let res = client()
+            .get(format!("{}/group/{gid}/announcement", HOST.url()))
+            .header("Authorization", format!("Bearer {token}"))
+            .send();
+        match res {
+            Ok(res) => match res.status() {
+                StatusCode::OK => {
+                    let res = res.json::<Option<Announcement>>();
+                    res.map_err(|err| {
+                        format_err!("Failed to get group announcement, gid: {gid}, err: {err}")
+                    })
+                }
+                StatusCode::NOT_FOUND => Ok(None),
+                _ => Err(format_err!(
+                    "Failed to get group announcement, gid: {gid}, status: {}",
+                    res.status()
+                )),
+            },
+            Err(err) => Err(format_err!(
+                "Failed to get group announcement, gid: {gid}, err: {err}"
+            )),
+        }
+    })?
+}
+
+pub(crate) fn set_announcement(gid: i32, content: String) -> color_eyre::Result<()> {
+    send_request(move || {
+        let current_user = CURRENT_USER.get_user();
+        let token = current_user.token.clone().unwrap();
+        let res = client()
+            .put(format!("{}/group/{gid}/announcement", HOST.url()))
+            .header("Authorization", format!("Bearer {token}"))
+            .json(&serde_json::json!({ "content": content }))
+            .send();
+        match res {
+            Ok(res) => match res.status() {
+                StatusCode::OK => Ok(()),
+                _ => Err(format_err!(
+                    "Failed to set group announcement, gid: {gid}, status: {}",
+                    res.status()
+                )),
+            },
+            Err(err) => Err(format_err!(
+                "Failed to set group announcement, gid: {gid}, err: {err}",
+            )),
+        }
+    })?
+}
+
+/// Pinned group message
+#[derive(Clone, Serialize, Deserialize)]
+pub(crate) struct PinnedMsg {
+    pub(crate) mid: i64,
+    pub(crate) msg: String,
+    pub(crate) from_uid: i32,
+    pub(crate) name_of_from_uid: String,
+    #[serde(with = "datetime_format")]
+    pub(crate) time: DateTime<Local>,
+    /// Name of the admin who pinned the message
+    pub(crate) pinned_by: String,
+}
+
+pub(crate) fn pins(gid: i32) -> color_eyre::Result<Vec<PinnedMsg>> {
+    send_request(move || {
+        let current_user = CURRENT_USER.get_user();
+        let token = current_user.token.clone().unwrap();
+        let res = client()
+            .get(format!("{}/group/{gid}/pin", HOST.url()))
+            .header("Authorization", format!("Bearer {token}"))
+            .send();
+        match res {
+            Ok(res) => match res.status() {
+                StatusCode::OK => {
+                    let res = res.json::<Vec<PinnedMsg>>();
+                    res.map_err(|err| {
+                        format_err!("Failed to get pinned messages, gid: {gid}, err: {err}")
+                    })
+                }
+                _ => Err(format_err!(
+                    "Failed to get pinned messages, gid: {gid}, status: {}",
+                    res.status()
+                )),
+            },
+            Err(err) => Err(format_err!(
+                "Failed to get pinned messages, gid: {gid}, err: {err}"
+            )),
+        }
+    })?
+}
+
+pub(crate) fn pin(gid: i32, mid: i64) -> color_eyre::Result<()> {
+    send_request(move || {
+        let current_user = CURRENT_USER.get_user();
+        let token = current_user.token.clone().unwrap();
+        let res = client()
+            .put(format!("{}/group/{gid}/pin/{mid}", HOST.url()))
+            .header("Authorization", format!("Bearer {token}"))
+            .send();
+        match res {
+            Ok(res) => match res.status() {
+                StatusCode::OK => Ok(()),
+                StatusCode::FORBIDDEN => Err(format_err!("{}", res.text()?)),
+                _ => Err(format_err!(
+                    "Failed to pin message, gid: {gid}, mid: {mid}, status: {}",
+                    res.status()
+                )),
+            },
+            Err(err) => Err(format_err!(
+                "Failed to pin message, gid: {gid}, mid: {mid}, err: {err}",
+            )),
+        }
+    })?
+}
+
+pub(crate) fn unpin(gid: i32, mid: i64) -> color_eyre::Result<()> {
+    send_request(move || {
+        let current_user = CURRENT_USER.get_user();
+        let token = current_user.token.clone().unwrap();
+        let res = client()
+            .delete(format!("{}/group/{gid}/pin/{mid}", HOST.url()))
+            .header("Authorization", format!("Bearer {token}"))
+            .send();
+        match res {
+            Ok(res) => match res.status() {
+                StatusCode::OK => Ok(()),
+                StatusCode::FORBIDDEN => Err(format_err!("{}", res.text()?)),
+                _ => Err(format_err!(
+                    "Failed to unpin message, gid: {gid}, mid: {mid}, status: {}",
+                    res.status()
+                )),
+            },
+            Err(err) => Err(format_err!(
+                "Failed to unpin message, gid: {gid}, mid: {mid}, err: {err}",
+            )),
+        }
+    })?
+}
@@ -0,0 +1,165 @@
+use crate::proxy::HOST;
+use crate::proxy::client;
+use crate::proxy::send_request;
+use crate::token::CURRENT_USER;
+use color_eyre::eyre::format_err;
+use ratatui::style::{Color, Style};
+use ratatui::text::Span;
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{LazyLock, Mutex};
+use strum::Display;
+
+// 存储好友及群成员的在线状态
+pub(crate) static PRESENCES: LazyLock<PresenceStore> =
+    LazyLock::new(|| PresenceStore(Mutex::new(HashMap::new())));
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Display, Serialize, Deserialize)]
+pub(crate) enum Presence {
+    #[strum(to_string = "在线")]
+    Online,
+    #[strum(to_string = "离开")]
+    Away,
+    #[strum(to_string = "忙碌")]
+    Busy,
+    #[default]
+    #[strum(to_string = "离线")]
+    Offline,
+}
+
+impl Presence {
+    pub(crate) fn color(&self) -> Color {
+        match self {
+            Presence::Online => Color::Green,
+            Presence::Away => Color::Yellow,
+            Presence::Busy => Color::Red,
+            Presence::Offline => Color::DarkGray,
+        }
+    }
+
+    /// 手动切换状态时的下一个状态
+    pub(crate) fn next(self) -> Self {
+        match self {
+            Presence::Online => Presence::Away,
+            Presence::Away => Presence::Busy,
+            Presence::Busy => Presence::Offline,
+            Presence::Offline => Presence::Online,
+        }
+    }
+}
+
+/// Presence of a user, pushed by the event stream or fetched on demand
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct PresenceStatus {
+    pub(crate) presence: Presence,
+    /// Custom status text
+    pub(crate) status_text: Option<String>,
+}
+
+impl PresenceStatus {
+    pub(crate) fn dot(&self) -> Span<'static> {
+        Span::styled("● ", Style::default().fg(self.presence.color()))
+    }
+
+    /// 状态描述，如：忙碌(开会中)
+    pub(crate) fn describe(&self) -> String {
+        match &self.status_text {
+            Some(text) if !text.is_empty() => format!("{}({text})", self.presence),
+            _ => self.presence.to_string(),
+        }
+    }
+}
+
+/// Presence message on the event stream
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct PresenceMessage {
+    pub(crate) uid: i32,
+    #[serde(flatten)]
+    pub(crate) status: PresenceStatus,
+}
+
+pub(crate) struct PresenceStore(Mutex<HashMap<i32, PresenceStatus>>);
+
+impl PresenceStore {
+    pub(crate) fn get(&self, uid: i32) -> PresenceStatus {
+        self.0
+            .lock()
+            .unwrap()
+            .get(&uid)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    pub(crate) fn set(&self, uid: i32, status: PresenceStatus) {
+        self.0.lock().unwrap().insert(uid, status);
+    }
+
+    pub(crate) fn clear(&self) {
+        self.0.lock().unwrap().clear();
+    }
+
+    pub(crate) fn take(&self) -> HashMap<i32, PresenceStatus> {
+        std::mem::take(&mut self.0.lock().unwrap())
+    }
+
+    pub(crate) fn restore(&self, presences: HashMap<i32, PresenceStatus>) {
+        *self.0.lock().unwrap() = presences;
+    }
+}
+
+/// 拉取指定用户的在线状态，并写入 PRESENCES
+pub(crate) fn fetch(uids: Vec<i32>) -> color_eyre::Result<()> {
+    if uids.is_empty() {
+        return Ok(());
+    }
+    let presences = send_request(move || {
+        let token = CURRENT_USER.get_user().token.clone().unwrap();
+        let res = client()
+            .post(format!("{}/user/presence", HOST.url()))
+            .header("Authorization", format!("Bearer {token}"))
+            .json(&uids)
+            .send();
+        match res {
+            Ok(res) => match res.status() {
+                StatusCode::OK => {
+                    let res = res.json::<Vec<PresenceMessage>>();
+                    res.map_err(|err| format_err!("Failed to get presence, err: {err}"))
+                }
+                _ => Err(format_err!(
+                    "Failed to get presence, status: {}",
+                    res.status()
+                )),
+            },
+            Err(err) => Err(format_err!("Failed to get presence, err: {err}")),
+        }
+    })??;
+    presences
+        .into_iter()
+        .for_each(|p| PRESENCES.set(p.uid, p.status));
+    Ok(())
+}
+
+/// 设置当前用户的在线状态
+pub(crate) fn set(status: PresenceStatus) -> color_eyre::Result<()> {
+    let uid = CURRENT_USER.get_user().user.unwrap().id;
+    PRESENCES.set(uid, status.clone());
+    send_request(move || {
+        let token = CURRENT_USER.get_user().token.clone().unwrap();
+        let res = client()
+            .put(format!("{}/user/presence", HOST.url()))
+            .header("Authorization", format!("Bearer {token}"))
+            .json(&status)
+            .send();
+        match res {
+            Ok(res) => match res.status() {
+                StatusCode::OK => Ok(()),
+                _ => Err(format_err!(
+                    "Failed to set presence, status: {}",
+                    res.status()
+                )),
+            },
+            Err(err) => Err(format_err!("Failed to set presence, err: {err}")),
+        }
+    })?
+}
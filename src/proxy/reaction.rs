@@ -0,0 +1,96 @@
+use crate::components::event::{MessageTarget, MessageTargetGroup, MessageTargetUser};
+use crate::proxy::{HOST, client, send_request};
+use crate::token::CURRENT_USER;
+use color_eyre::eyre::format_err;
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+
+/// 消息上的表情回应，按表情聚合
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub(crate) struct Reaction {
+    pub(crate) emoji: String,
+    /// 回应该表情的用户
+    pub(crate) uids: Vec<i32>,
+}
+
+#[derive(Serialize)]
+struct ReactionReq {
+    emoji: String,
+}
+
+/// 添加或取消对消息的表情回应
+pub(crate) fn react(
+    target: MessageTarget,
+    mid: i64,
+    emoji: String,
+    add: bool,
+) -> color_eyre::Result<()> {
+    let url = match target {
+        MessageTarget::User(MessageTargetUser { uid }) => {
+            format!("{}/user/{uid}/msg/{mid}/reaction", HOST.url())
+        }
+        MessageTarget::Group(MessageTargetGroup { gid }) => {
+            format!("{}/group/{gid}/msg/{mid}/reaction", HOST.url())
+        }
+    };
+    send_request(move || {
+        let token = CURRENT_USER.get_user().token.clone().unwrap();
+        let req = if add {
+            client().put(url)
+        } else {
+            client().delete(url)
+        };
+        let res = req
+            .header("Authorization", format!("Bearer {token}"))
+            .json(&ReactionReq { emoji })
+            .send();
+        match res {
+            Ok(res) => match res.status() {
+                StatusCode::OK => Ok(()),
+                StatusCode::FORBIDDEN => Err(format_err!("{}", res.text()?)),
+                _ => Err(format_err!(
+                    "Failed to react to message, mid: {mid}, status: {}",
+                    res.status()
+                )),
+            },
+            Err(err) => Err(format_err!(
+                "Failed to react to message, mid: {mid}, err: {err}"
+            )),
+        }
+    })?
+}
+
+/// 更新消息的表情回应，重复的通知不会重复计数
+pub(crate) fn apply(reactions: &mut Vec<Reaction>, emoji: &str, uid: i32, added: bool) {
+    match reactions.iter_mut().find(|r| r.emoji == emoji) {
+        Some(reaction) if added && !reaction.uids.contains(&uid) => reaction.uids.push(uid),
+        Some(reaction) if !added => reaction.uids.retain(|u| *u != uid),
+        None if added => reactions.push(Reaction {
+            emoji: emoji.to_string(),
+            uids: vec![uid],
+        }),
+        _ => {}
+    }
+    reactions.retain(|r| !r.uids.is_empty());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn apply_reactions() {
+        let mut reactions = Vec::new();
+        apply(&mut reactions, "👍", 1, true);
+        apply(&mut reactions, "👍", 2, true);
+        apply(&mut reactions, "👍", 2, true);
+        apply(&mut reactions, "🎉", 1, true);
+        assert_eq!(reactions[0].uids, vec![1, 2]);
+        assert_eq!(reactions.len(), 2);
+
+        apply(&mut reactions, "🎉", 1, false);
+        apply(&mut reactions, "😂", 1, false);
+        assert_eq!(reactions.len(), 1);
+        assert_eq!(reactions[0].emoji, "👍");
+    }
+}
@@ -0,0 +1,131 @@
+use crate::config::{get_data_dir, profile_key};
+use crate::proxy::friend::Friend;
+use crate::proxy::{HOST, client, send_request};
+use crate::token::CURRENT_USER;
+use color_eyre::eyre::format_err;
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{LazyLock, Mutex};
+use tracing::warn;
+
+// 好友备注与标签，仅当前用户可见，本地缓存并与服务端同步
+pub(crate) static REMARKS: LazyLock<RemarkBook> =
+    LazyLock::new(|| RemarkBook(Mutex::new(HashMap::new())));
+
+#[derive(Clone, Default, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct Remark {
+    pub(crate) remark: Option<String>,
+    #[serde(default)]
+    pub(crate) tags: Vec<String>,
+}
+
+pub(crate) struct RemarkBook(Mutex<HashMap<i32, Remark>>);
+
+impl RemarkBook {
+    pub(crate) fn get(&self, uid: i32) -> Remark {
+        self.0
+            .lock()
+            .unwrap()
+            .get(&uid)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// 有备注时显示备注，否则显示用户名
+    pub(crate) fn display_name(&self, uid: i32, name: &str) -> String {
+        self.get(uid).remark.unwrap_or(name.to_string())
+    }
+
+    /// 启动时加载本地缓存，服务端不可用时也能显示备注
+    pub(crate) fn load_local(&self) {
+        self.0.lock().unwrap().clear();
+        let Some(path) = local_path() else {
+            return;
+        };
+        let Ok(content) = fs::read_to_string(&path) else {
+            return;
+        };
+        match serde_json::from_str::<HashMap<i32, Remark>>(&content) {
+            Ok(remarks) => *self.0.lock().unwrap() = remarks,
+            Err(err) => warn!("fail to parse remarks {}: {err}", path.display()),
+        }
+    }
+
+    /// 使用服务端返回的好友列表覆盖本地备注
+    pub(crate) fn sync(&self, friends: &[Friend]) {
+        let remarks = friends
+            .iter()
+            .map(|f| {
+                let remark = Remark {
+                    remark: f.remark.clone(),
+                    tags: f.tags.clone(),
+                };
+                (f.id, remark)
+            })
+            .collect();
+        *self.0.lock().unwrap() = remarks;
+        self.save_local();
+    }
+
+    fn set(&self, uid: i32, remark: Remark) {
+        self.0.lock().unwrap().insert(uid, remark);
+        self.save_local();
+    }
+
+    pub(crate) fn take(&self) -> HashMap<i32, Remark> {
+        std::mem::take(&mut self.0.lock().unwrap())
+    }
+
+    pub(crate) fn restore(&self, remarks: HashMap<i32, Remark>) {
+        *self.0.lock().unwrap() = remarks;
+    }
+
+    fn save_local(&self) {
+        let Some(path) = local_path() else {
+            return;
+        };
+        let content = serde_json::to_string(&*self.0.lock().unwrap()).unwrap();
+        if let Err(err) = fs::create_dir_all(get_data_dir()).and_then(|_| fs::write(&path, content))
+        {
+            warn!("fail to save remarks {}: {err}", path.display());
+        }
+    }
+}
+
+/// 按服务器配置与用户区分本地缓存文件
+fn local_path() -> Option<PathBuf> {
+    let uid = CURRENT_USER.get_user().user?.id;
+    let profile = HOST.profile().map(|profile| profile.name);
+    let profile = profile_key(profile.as_deref());
+    Some(get_data_dir().join(format!("remarks-{profile}-{uid}.json")))
+}
+
+/// 修改好友备注与标签
+pub(crate) fn update(friend_uid: i32, remark: Remark) -> color_eyre::Result<()> {
+    let body = remark.clone();
+    send_request(move || {
+        let token = CURRENT_USER.get_user().token.clone().unwrap();
+        let res = client()
+            .put(format!("{}/friend/{friend_uid}/remark", HOST.url()))
+            .header("Authorization", format!("Bearer {token}"))
+            .json(&body)
+            .send();
+        match res {
+            Ok(res) => match res.status() {
+                StatusCode::OK => Ok(()),
+                _ => Err(format_err!(
+                    "Failed to update remark, friend_uid: {friend_uid}, status: {}",
+                    res.status()
+                )),
+            },
+            Err(err) => Err(format_err!(
+                "Failed to update remark, friend_uid: {friend_uid}, err: {err}"
+            )),
+        }
+    })??;
+    REMARKS.set(friend_uid, remark);
+    Ok(())
+}
@@ -0,0 +1,87 @@
+use crate::datetime::datetime_format;
+use crate::proxy::{HOST, client, send_request};
+use crate::token::CURRENT_USER;
+use chrono::{DateTime, Local};
+use color_eyre::eyre::format_err;
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+
+/// 已登录的会话（设备）
+#[derive(Clone, Serialize, Deserialize)]
+pub(crate) struct LoginSession {
+    pub(crate) id: String,
+    /// 设备描述，如客户端名称
+    pub(crate) device: Option<String>,
+    pub(crate) ip: Option<String>,
+    /// 最后活跃时间
+    #[serde(with = "datetime_format")]
+    pub(crate) last_active: DateTime<Local>,
+    /// 是否为当前会话
+    #[serde(default)]
+    pub(crate) current: bool,
+}
+
+/// 注销当前 token
+pub(crate) fn logout() -> color_eyre::Result<()> {
+    send_request(move || {
+        let token = CURRENT_USER.get_user().token.clone().unwrap();
+        let res = client()
+            .delete(format!("{}/token", HOST.url()))
+            .header("Authorization", format!("Bearer {token}"))
+            .send();
+        match res {
+            Ok(res) => match res.status() {
+                // token 已失效也视为注销成功
+                StatusCode::OK | StatusCode::UNAUTHORIZED => Ok(()),
+                _ => Err(format_err!("Failed to logout, status: {}", res.status())),
+            },
+            Err(err) => Err(format_err!("Failed to logout, err: {err}")),
+        }
+    })?
+}
+
+pub(crate) fn sessions() -> color_eyre::Result<Vec<LoginSession>> {
+    send_request(move || {
+        let token = CURRENT_USER.get_user().token.clone().unwrap();
+        let res = client()
+            .get(format!("{}/token/sessions", HOST.url()))
+            .header("Authorization", format!("Bearer {token}"))
+            .send();
+        match res {
+            Ok(res) => match res.status() {
+                StatusCode::OK => {
+                    let res = res.json::<Vec<LoginSession>>();
+                    res.map_err(|err| format_err!("Failed to get sessions, err: {err}"))
+                }
+                _ => Err(format_err!(
+                    "Failed to get sessions, status: {}",
+                    res.status()
+                )),
+            },
+            Err(err) => Err(format_err!("Failed to get sessions, err: {err}")),
+        }
+    })?
+}
+
+/// 注销其他设备上的会话
+pub(crate) fn revoke(id: String) -> color_eyre::Result<()> {
+    send_request(move || {
+        let token = CURRENT_USER.get_user().token.clone().unwrap();
+        let res = client()
+            .delete(format!("{}/token/sessions/{id}", HOST.url()))
+            .header("Authorization", format!("Bearer {token}"))
+            .send();
+        match res {
+            Ok(res) => match res.status() {
+                StatusCode::OK => Ok(()),
+                _ => Err(format_err!(
+                    "Failed to revoke session, id: {id}, status: {}",
+                    res.status()
+                )),
+            },
+            Err(err) => Err(format_err!(
+                "Failed to revoke session, id: {id}, err: {err}"
+            )),
+        }
+    })?
+}
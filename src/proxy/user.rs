@@ -1,10 +1,9 @@
 use crate::datetime::datetime_format;
 use crate::datetime::opt_datetime_format;
-use crate::proxy::{send_request, HOST};
+use crate::proxy::{client, send_request, HOST};
 use crate::token::CURRENT_USER;
 use chrono::{DateTime, Local};
 use color_eyre::eyre::format_err;
-use reqwest::blocking::Client;
 use reqwest::StatusCode;
 use serde::{Deserialize, Serialize};
 
@@ -33,8 +32,8 @@ pub(crate) struct UserDetail {
 pub(crate) fn detail_by_id(uid: i32) -> color_eyre::Result<UserDetail> {
     send_request(move || {
         let token = CURRENT_USER.get_user().token.clone().unwrap();
-        let res = Client::new()
-            .get(format!("{}/user/detail/{uid}", HOST.as_str()))
+        let res = client()
+            .get(format!("{}/user/detail/{uid}", HOST.url()))
             .header("Authorization", format!("Bearer {token}"))
             .send();
         match res {
@@ -57,8 +56,8 @@ pub(crate) fn search(name: String) -> color_eyre::Result<Vec<UserDetail>> {
     send_request(move || {
         let current_user = CURRENT_USER.get_user();
         let token = current_user.token.clone().unwrap();
-        let res = Client::new()
-            .get(format!("{}/user/search/{name}", HOST.as_str()))
+        let res = client()
+            .get(format!("{}/user/search/{name}", HOST.url()))
             .header("Authorization", format!("Bearer {token}"))
             .send();
         match res {
@@ -80,3 +79,66 @@ pub(crate) fn search(name: String) -> color_eyre::Result<Vec<UserDetail>> {
         }
     })?
 }
+
+#[derive(Serialize)]
+pub(crate) struct UpdateUserReq {
+    pub(crate) name: String,
+    pub(crate) email: Option<String>,
+    pub(crate) phone: Option<String>,
+}
+
+/// 修改当前用户资料
+pub(crate) fn update(req: UpdateUserReq) -> color_eyre::Result<()> {
+    send_request(move || {
+        let token = CURRENT_USER.get_user().token.clone().unwrap();
+        let res = client()
+            .put(format!("{}/user/detail", HOST.url()))
+            .header("Authorization", format!("Bearer {token}"))
+            .json(&req)
+            .send();
+        match res {
+            Ok(res) => match res.status() {
+                StatusCode::OK => Ok(()),
+                // 用户名已存在或格式错误时，服务端返回原因
+                StatusCode::BAD_REQUEST | StatusCode::CONFLICT => {
+                    Err(format_err!("{}", res.text()?))
+                }
+                _ => Err(format_err!(
+                    "Failed to update user detail, status: {}",
+                    res.status()
+                )),
+            },
+            Err(err) => Err(format_err!("Failed to update user detail, err: {err}")),
+        }
+    })?
+}
+
+/// 修改密码，需要校验旧密码
+pub(crate) fn change_password(
+    old_password: String,
+    new_password: String,
+) -> color_eyre::Result<()> {
+    send_request(move || {
+        let token = CURRENT_USER.get_user().token.clone().unwrap();
+        let res = client()
+            .put(format!("{}/user/password", HOST.url()))
+            .header("Authorization", format!("Bearer {token}"))
+            .json(&serde_json::json!({
+                "old_password": old_password,
+                "new_password": new_password,
+            }))
+            .send();
+        match res {
+            Ok(res) => match res.status() {
+                StatusCode::OK => Ok(()),
+                StatusCode::FORBIDDEN => Err(format_err!("原密码错误")),
+                StatusCode::BAD_REQUEST => Err(format_err!("{}", res.text()?)),
+                _ => Err(format_err!(
+                    "Failed to change password, status: {}",
+                    res.status()
+                )),
+            },
+            Err(err) => Err(format_err!("Failed to change password, err: {err}")),
+        }
+    })?
+}
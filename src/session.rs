@@ -0,0 +1,296 @@
+use crate::config::{get_data_dir, profile_key};
+use crate::proxy::send_request;
+use aes_gcm::aead::rand_core::RngCore;
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use argon2::Argon2;
+use color_eyre::eyre::format_err;
+use keyring::Entry;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use tracing::warn;
+
+const SERVICE: &str = "chat-tui";
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+
+/// 保存的会话，只保存可续期的 token，不保存密码
+#[derive(Clone, Serialize, Deserialize)]
+pub(crate) struct SavedSession {
+    pub(crate) user_name: String,
+    pub(crate) token: String,
+}
+
+/// 会话按 (服务器配置, 用户) 区分，同一服务器的多个账号互不影响
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct SessionKey {
+    /// 服务器配置名称，使用环境变量中的服务器时为 None
+    pub(crate) profile: Option<String>,
+    pub(crate) uid: i32,
+}
+
+impl SessionKey {
+    fn account(&self) -> String {
+        format!(
+            "{}#{}",
+            self.profile.as_deref().unwrap_or("default"),
+            self.uid
+        )
+    }
+
+    fn session_file(&self) -> PathBuf {
+        let profile = profile_key(self.profile.as_deref());
+        get_data_dir().join(format!("session-{profile}#{}.enc", self.uid))
+    }
+}
+
+/// 已记住登录的账号，保存在数据目录，不包含 token
+#[derive(Clone, Serialize, Deserialize)]
+pub(crate) struct Remembered {
+    pub(crate) key: SessionKey,
+    /// 会话保存在系统密钥环，否则为加密文件
+    pub(crate) keyring: bool,
+}
+
+#[derive(Clone)]
+pub(crate) enum Store {
+    /// 系统密钥环（Secret Service）
+    Keyring,
+    /// 系统密钥环不可用时，使用口令加密的文件
+    File { passphrase: String },
+}
+
+fn entry(key: &SessionKey) -> keyring::Result<Entry> {
+    Entry::new(SERVICE, &key.account())
+}
+
+/// 访问系统密钥环。密钥环内部会启动单独的 tokio 运行时，
+/// 在运行时中需要放到阻塞线程执行，其他线程（如 token 续期线程）直接调用
+fn with_keyring<F, R>(f: F) -> color_eyre::Result<keyring::Result<R>>
+where
+    F: FnOnce() -> keyring::Result<R> + Send + 'static,
+    R: Send + 'static,
+{
+    if tokio::runtime::Handle::try_current().is_ok() {
+        send_request(f)
+    } else {
+        Ok(f())
+    }
+}
+
+fn index_path() -> PathBuf {
+    get_data_dir().join("sessions.json")
+}
+
+/// 已记住登录的账号
+pub(crate) fn remembered() -> Vec<Remembered> {
+    fs::read_to_string(index_path())
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn save_index(index: &[Remembered]) -> color_eyre::Result<()> {
+    fs::create_dir_all(get_data_dir())?;
+    fs::write(index_path(), serde_json::to_string(index)?)?;
+    Ok(())
+}
+
+/// 系统密钥环是否可用，没有保存过会话也视为可用
+pub(crate) fn keyring_available() -> bool {
+    with_keyring(|| Entry::new(SERVICE, "probe").and_then(|e| e.get_password()))
+        .map(|res| match res {
+            Ok(_) | Err(keyring::Error::NoEntry) => true,
+            Err(err) => {
+                warn!("keyring unavailable: {err}");
+                false
+            }
+        })
+        .unwrap_or(false)
+}
+
+/// 从系统密钥环读取会话
+pub(crate) fn load_from_keyring(key: &SessionKey) -> Option<SavedSession> {
+    let key = key.clone();
+    let secret = with_keyring(move || entry(&key).and_then(|e| e.get_password())).ok()?;
+    match secret {
+        Ok(secret) => match serde_json::from_str(&secret) {
+            Ok(session) => Some(session),
+            Err(err) => {
+                warn!("fail to parse saved session: {err}");
+                None
+            }
+        },
+        Err(keyring::Error::NoEntry) => None,
+        Err(err) => {
+            warn!("fail to read keyring: {err}");
+            None
+        }
+    }
+}
+
+/// 使用口令解密文件中的会话
+pub(crate) fn load_from_file(
+    key: &SessionKey,
+    passphrase: &str,
+) -> color_eyre::Result<SavedSession> {
+    let data = fs::read(key.session_file())?;
+    let plain = decrypt(passphrase, &data)?;
+    Ok(serde_json::from_slice(&plain)?)
+}
+
+/// 开启记住登录，保存会话并记录该账号
+pub(crate) fn remember(
+    key: &SessionKey,
+    store: &Store,
+    session: &SavedSession,
+) -> color_eyre::Result<()> {
+    save(key, store, session)?;
+    let mut index = remembered();
+    let keyring = matches!(store, Store::Keyring);
+    match index.iter_mut().find(|r| &r.key == key) {
+        Some(remembered) if remembered.keyring == keyring => return Ok(()),
+        Some(remembered) => remembered.keyring = keyring,
+        None => index.push(Remembered {
+            key: key.clone(),
+            keyring,
+        }),
+    }
+    save_index(&index)
+}
+
+/// 更新保存的会话，token 续期后调用
+pub(crate) fn save(
+    key: &SessionKey,
+    store: &Store,
+    session: &SavedSession,
+) -> color_eyre::Result<()> {
+    let content = serde_json::to_string(session)?;
+    match store {
+        Store::Keyring => {
+            let key = key.clone();
+            with_keyring(move || entry(&key).and_then(|e| e.set_password(&content)))??;
+        }
+        Store::File { passphrase } => {
+            fs::create_dir_all(get_data_dir())?;
+            fs::write(key.session_file(), encrypt(passphrase, content.as_bytes())?)?;
+        }
+    }
+    Ok(())
+}
+
+/// 删除该账号保存的会话，会话失效、退出登录或不再记住登录时调用
+pub(crate) fn forget(key: &SessionKey) {
+    let entry_key = key.clone();
+    if let Ok(Err(err)) =
+        with_keyring(move || entry(&entry_key).and_then(|e| e.delete_credential()))
+        && !matches!(err, keyring::Error::NoEntry)
+    {
+        warn!("fail to delete session from keyring: {err}");
+    }
+    let file = key.session_file();
+    if file.exists()
+        && let Err(err) = fs::remove_file(file)
+    {
+        warn!("fail to delete session file: {err}");
+    }
+    let mut index = remembered();
+    let len = index.len();
+    index.retain(|r| &r.key != key);
+    if index.len() != len
+        && let Err(err) = save_index(&index)
+    {
+        warn!("fail to save remembered sessions: {err}");
+    }
+}
+
+fn derive_key(passphrase: &str, salt: &[u8]) -> color_eyre::Result<Key<Aes256Gcm>> {
+    let mut key = Key::<Aes256Gcm>::default();
+    Argon2::default()
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|err| format_err!("fail to derive key: {err}"))?;
+    Ok(key)
+}
+
+/// 加密结果为 salt + nonce + 密文
+fn encrypt(passphrase: &str, plain: &[u8]) -> color_eyre::Result<Vec<u8>> {
+    let mut salt = [0u8; SALT_LEN];
+    OsRng.fill_bytes(&mut salt);
+    let cipher = Aes256Gcm::new(&derive_key(passphrase, &salt)?);
+    let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+    let encrypted = cipher
+        .encrypt(&nonce, plain)
+        .map_err(|err| format_err!("fail to encrypt session: {err}"))?;
+    Ok([salt.as_slice(), nonce.as_slice(), &encrypted].concat())
+}
+
+fn decrypt(passphrase: &str, data: &[u8]) -> color_eyre::Result<Vec<u8>> {
+    if data.len() < SALT_LEN + NONCE_LEN {
+        return Err(format_err!("会话文件已损坏"));
+    }
+    let (salt, rest) = data.split_at(SALT_LEN);
+    let (nonce, encrypted) = rest.split_at(NONCE_LEN);
+    let cipher = Aes256Gcm::new(&derive_key(passphrase, salt)?);
+    cipher
+        .decrypt(Nonce::from_slice(nonce), encrypted)
+        .map_err(|_| format_err!("口令错误"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encrypt_roundtrip() {
+        let data = encrypt("passphrase", b"token").unwrap();
+        assert_eq!(decrypt("passphrase", &data).unwrap(), b"token");
+        assert!(decrypt("wrong", &data).is_err());
+        assert!(decrypt("passphrase", &data[..10]).is_err());
+    }
+
+    #[test]
+    fn save_outside_runtime() {
+        keyring::set_default_credential_builder(keyring::mock::default_credential_builder());
+        let key = SessionKey {
+            profile: Some("test".to_string()),
+            uid: 1,
+        };
+        // token 续期线程不在 tokio 运行时中
+        let res = std::thread::spawn(move || {
+            save(
+                &key,
+                &Store::Keyring,
+                &SavedSession {
+                    user_name: "alice".to_string(),
+                    token: "token".to_string(),
+                },
+            )
+        })
+        .join();
+        assert!(res.unwrap().is_ok());
+    }
+
+    #[test]
+    fn sessions_keyed_by_account() {
+        let alice = SessionKey {
+            profile: Some("work".to_string()),
+            uid: 1,
+        };
+        let bob = SessionKey {
+            profile: Some("work".to_string()),
+            uid: 2,
+        };
+        assert_ne!(alice.account(), bob.account());
+        assert_ne!(alice.session_file(), bob.session_file());
+
+        let escape = SessionKey {
+            profile: Some("../../tmp".to_string()),
+            uid: 1,
+        };
+        assert_eq!(
+            escape.session_file().parent(),
+            Some(get_data_dir().as_path())
+        );
+    }
+}